@@ -100,12 +100,21 @@ typedef enum zbar_symbol_type_e
     ZBAR_ISBN13	     = 14,  /**< ISBN-13 (from EAN-13). @since 0.4 */
     ZBAR_COMPOSITE   = 15,  /**< EAN/UPC composite */
     ZBAR_I25	     = 25,  /**< Interleaved 2 of 5. @since 0.4 */
+    ZBAR_ITF14	     = 26,  /**< ITF-14 (from Interleaved 2 of 5) */
+    ZBAR_DATABAR_STK = 31,  /**< GS1 DataBar Stacked */
+    ZBAR_DATABAR_STK_OMNI = 32, /**< GS1 DataBar Stacked Omnidirectional */
+    ZBAR_DATABAR_EXP_STK  = 33, /**< GS1 DataBar Expanded Stacked */
     ZBAR_DATABAR     = 34,  /**< GS1 DataBar (RSS). @since 0.11 */
     ZBAR_DATABAR_EXP = 35,  /**< GS1 DataBar Expanded. @since 0.11 */
+    ZBAR_DATABAR_LTD = 36,  /**< GS1 DataBar Limited */
     ZBAR_CODABAR     = 38,  /**< Codabar. @since 0.11 */
     ZBAR_CODE39	     = 39,  /**< Code 39. @since 0.4 */
+    ZBAR_DATAMATRIX  = 48,  /**< Data Matrix ECC 200 */
+    ZBAR_AZTEC	     = 49,  /**< Aztec Code */
     ZBAR_PDF417	     = 57,  /**< PDF417. @since 0.6 */
     ZBAR_QRCODE	     = 64,  /**< QR Code. @since 0.10 */
+    ZBAR_MICROQR     = 65,  /**< Micro QR Code */
+    ZBAR_RMQR	     = 66,  /**< rMQR Code (rectangular Micro QR) */
     ZBAR_SQCODE	     = 80,  /**< SQ Code. @since 0.20.1 */
     ZBAR_CODE93	     = 93,  /**< Code 93. @since 0.11 */
     ZBAR_CODE128     = 128, /**< Code 128 */
@@ -186,9 +195,15 @@ typedef enum zbar_config_e
 
     ZBAR_CFG_POSITION = 0x80, /**< enable scanner to collect position data */
     ZBAR_CFG_TEST_INVERTED,   /**< if fails to decode, test inverted */
+    ZBAR_CFG_TEST_MIRRORED,   /**< if fails to decode, test mirrored QR */
 
     ZBAR_CFG_X_DENSITY = 0x100, /**< image scanner vertical scan density */
     ZBAR_CFG_Y_DENSITY,		/**< image scanner horizontal scan density */
+    ZBAR_CFG_DIAGONAL,		/**< image scanner diagonal scan angle step */
+
+    ZBAR_CFG_EDGE_THRESHOLD = 0x200, /**< scanner minimum edge gradient */
+    ZBAR_CFG_EWMA_WEIGHT,	     /**< scanner smoothing weight (percent) */
+    ZBAR_CFG_THRESHOLD_DECAY,	     /**< scanner edge threshold decay rate */
 } zbar_config_t;
 
 /** decoder symbology modifier flags.
@@ -207,6 +222,21 @@ typedef enum zbar_modifier_e
      */
     ZBAR_MOD_AIM,
 
+    /** symbol was decoded from its mirror image
+     * (eg, a QR code seen through the back of a transparent label)
+     */
+    ZBAR_MOD_MIRRORED,
+
+    /** data is to be prepended to the next symbol
+     * (eg, Code 128 FNC2 message append)
+     */
+    ZBAR_MOD_APPEND,
+
+    /** symbol initializes or reprograms the reader
+     * (eg, Code 128 FNC3)
+     */
+    ZBAR_MOD_READER_INIT,
+
     /** number of modifiers */
     ZBAR_MOD_NUM,
 } zbar_modifier_t;
@@ -473,6 +503,22 @@ extern int zbar_symbol_get_loc_y(const zbar_symbol_t *symbol, unsigned index);
 extern zbar_orientation_t
 zbar_symbol_get_orientation(const zbar_symbol_t *symbol);
 
+/** retrieve the structured append header of a symbol that holds one
+ * part of a message split across several symbols.
+ * @param index is set to the 0-based position of the part
+ * @param size is set to the number of parts in the message
+ * @param parity is set to the parity of the whole message
+ * @param data_parity is set to the parity of the data of this part
+ * @returns 0 if the symbol is a structured append part
+ * @returns -1 otherwise
+ * @note parities are the XOR of the raw message bytes, before any
+ * character set conversion
+ */
+extern int zbar_symbol_get_structured_append(const zbar_symbol_t *symbol,
+					     unsigned *index, unsigned *size,
+					     unsigned *parity,
+					     unsigned *data_parity);
+
 /** iterate the set to which this symbol belongs (there can be only one).
  * @returns the next symbol in the set, or
  * @returns NULL when no more results are available
@@ -1520,6 +1566,22 @@ extern void zbar_scanner_destroy(zbar_scanner_t *scanner);
  */
 extern zbar_symbol_type_t zbar_scanner_reset(zbar_scanner_t *scanner);
 
+/** set edge detection filter parameters.
+ * @returns 0 for success, non-0 for failure (config does not apply to
+ * the scanner or value out of range)
+ * @see zbar_config_t
+ */
+extern int zbar_scanner_set_config(zbar_scanner_t *scanner, zbar_config_t cfg,
+				   int val);
+
+/** get edge detection filter parameters.
+ * @returns 0 for success, non-0 for failure (config does not apply to
+ * the scanner)
+ * @see zbar_config_t
+ */
+extern int zbar_scanner_get_config(const zbar_scanner_t *scanner,
+				   zbar_config_t cfg, int *val);
+
 /** mark start of a new scan pass. resets color to ::ZBAR_SPACE.
  * also updates an associated decoder.
  * @returns any decode results flushed from the pipeline
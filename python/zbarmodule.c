@@ -47,12 +47,23 @@ static const enumdef symbol_defs[] = { { "NONE", ZBAR_NONE },
 				       { "ISBN13", ZBAR_ISBN13 },
 				       { "DATABAR", ZBAR_DATABAR },
 				       { "DATABAR_EXP", ZBAR_DATABAR_EXP },
+				       { "DATABAR_LTD", ZBAR_DATABAR_LTD },
+				       { "DATABAR_STK", ZBAR_DATABAR_STK },
+				       { "DATABAR_STK_OMNI",
+					 ZBAR_DATABAR_STK_OMNI },
+				       { "DATABAR_EXP_STK",
+					 ZBAR_DATABAR_EXP_STK },
 				       { "I25", ZBAR_I25 },
+				       { "ITF14", ZBAR_ITF14 },
 				       { "CODABAR", ZBAR_CODABAR },
 				       { "CODE39", ZBAR_CODE39 },
 				       { "PDF417", ZBAR_PDF417 },
 				       { "QRCODE", ZBAR_QRCODE },
+				       { "MICROQR", ZBAR_MICROQR },
+				       { "RMQR", ZBAR_RMQR },
 				       { "SQCODE", ZBAR_SQCODE },
+				       { "DATAMATRIX", ZBAR_DATAMATRIX },
+				       { "AZTEC", ZBAR_AZTEC },
 				       { "CODE93", ZBAR_CODE93 },
 				       { "CODE128", ZBAR_CODE128 },
 				       {
@@ -68,14 +79,23 @@ static const enumdef config_defs[] = { { "ENABLE", ZBAR_CFG_ENABLE },
 				       { "MAX_LEN", ZBAR_CFG_MAX_LEN },
 				       { "UNCERTAINTY", ZBAR_CFG_UNCERTAINTY },
 				       { "POSITION", ZBAR_CFG_POSITION },
+				       { "TEST_INVERTED", ZBAR_CFG_TEST_INVERTED },
+				       { "TEST_MIRRORED", ZBAR_CFG_TEST_MIRRORED },
 				       { "X_DENSITY", ZBAR_CFG_X_DENSITY },
 				       { "Y_DENSITY", ZBAR_CFG_Y_DENSITY },
+				       { "DIAGONAL", ZBAR_CFG_DIAGONAL },
+				       { "EDGE_THRESHOLD", ZBAR_CFG_EDGE_THRESHOLD },
+				       { "EWMA_WEIGHT", ZBAR_CFG_EWMA_WEIGHT },
+				       { "THRESHOLD_DECAY", ZBAR_CFG_THRESHOLD_DECAY },
 				       {
 					   NULL,
 				       } };
 
 static const enumdef modifier_defs[] = { { "GS1", ZBAR_MOD_GS1 },
 					 { "AIM", ZBAR_MOD_AIM },
+					 { "MIRRORED", ZBAR_MOD_MIRRORED },
+					 { "APPEND", ZBAR_MOD_APPEND },
+					 { "READER_INIT", ZBAR_MOD_READER_INIT },
 					 {
 					     NULL,
 					 } };
@@ -42,6 +42,18 @@ extern "C" {
 
     pub fn zbar_image_set_size(img: *mut libc::c_void, w: libc::c_uint, h: libc::c_uint);
 
+    pub fn zbar_image_get_width(img: *const libc::c_void) -> libc::c_uint;
+
+    pub fn zbar_image_get_height(img: *const libc::c_void) -> libc::c_uint;
+
+    pub fn zbar_image_set_crop(
+        img: *mut libc::c_void,
+        x: libc::c_uint,
+        y: libc::c_uint,
+        w: libc::c_uint,
+        h: libc::c_uint,
+    );
+
     pub fn zbar_image_set_data(
         img: *mut libc::c_void,
         data: *const libc::c_void,
@@ -51,6 +63,13 @@ extern "C" {
 
     pub fn zbar_image_free_data(img: *mut libc::c_void);
 
+    pub fn zbar_image_convert(img: *const libc::c_void, format: libc::c_ulong)
+        -> *mut libc::c_void;
+
+    pub fn zbar_image_get_data(img: *const libc::c_void) -> *const libc::c_void;
+
+    pub fn zbar_image_get_data_length(img: *const libc::c_void) -> libc::c_ulong;
+
     pub fn zbar_process_image(proc: *mut libc::c_void, img: *mut libc::c_void) -> libc::c_int;
 
     pub fn zbar_image_first_symbol(img: *const libc::c_void) -> *const libc::c_void;
@@ -69,6 +88,34 @@ extern "C" {
 
     pub fn zbar_symbol_get_data(sym: *const libc::c_void) -> *const libc::c_char;
 
+    pub fn zbar_symbol_get_data_length(sym: *const libc::c_void) -> libc::c_uint;
+
+    pub fn zbar_symbol_get_quality(sym: *const libc::c_void) -> libc::c_int;
+
+    pub fn zbar_symbol_get_orientation(sym: *const libc::c_void) -> libc::c_int;
+
+    pub fn zbar_get_orientation_name(orient: libc::c_int) -> *const libc::c_char;
+
+    pub fn zbar_symbol_get_modifiers(sym: *const libc::c_void) -> libc::c_uint;
+
+    pub fn zbar_get_modifier_name(modifier: libc::c_int) -> *const libc::c_char;
+
+    pub fn zbar_symbol_get_configs(sym: *const libc::c_void) -> libc::c_uint;
+
+    pub fn zbar_get_config_name(config: libc::c_int) -> *const libc::c_char;
+
+    pub fn zbar_symbol_get_count(sym: *const libc::c_void) -> libc::c_int;
+
+    pub fn zbar_symbol_get_structured_append(
+        sym: *const libc::c_void,
+        index: *mut libc::c_uint,
+        size: *mut libc::c_uint,
+        parity: *mut libc::c_uint,
+        data_parity: *mut libc::c_uint,
+    ) -> libc::c_int;
+
+    pub fn zbar_symbol_first_component(sym: *const libc::c_void) -> *const libc::c_void;
+
     pub fn zbar_symbol_xml(
         sym: *const libc::c_void,
         buf: *mut *mut libc::c_char,
@@ -82,10 +129,30 @@ extern "C" {
     pub fn zbar_processor_user_wait(proc: *mut libc::c_void, timeout: libc::c_int) -> libc::c_int;
 
     pub fn zbar_processor_destroy(proc: *mut libc::c_void);
+
+    pub fn zbar_decoder_create() -> *mut libc::c_void;
+
+    pub fn zbar_decoder_destroy(dcode: *mut libc::c_void);
+
+    pub fn zbar_scanner_create(dcode: *mut libc::c_void) -> *mut libc::c_void;
+
+    pub fn zbar_scanner_destroy(scn: *mut libc::c_void);
+
+    pub fn zbar_scanner_new_scan(scn: *mut libc::c_void) -> libc::c_int;
+
+    pub fn zbar_scanner_flush(scn: *mut libc::c_void) -> libc::c_int;
+
+    pub fn zbar_scanner_get_edge(
+        scn: *const libc::c_void,
+        offset: libc::c_uint,
+        prec: libc::c_int,
+    ) -> libc::c_uint;
+
+    pub fn zbar_scan_y(scn: *mut libc::c_void, y: libc::c_int) -> libc::c_int;
 }
 
 #[repr(C)]
-#[derive(PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ZbarSymbolType {
     /**< no symbol decoded */
     ZbarNone = 0,
@@ -111,18 +178,36 @@ pub enum ZbarSymbolType {
     ZbarComposite = 15,
     /**< Interleaved 2 of 5. @since 0.4 */
     ZbarI25 = 25,
+    /**< ITF-14 (from Interleaved 2 of 5) */
+    ZbarItf14 = 26,
+    /**< GS1 DataBar Stacked */
+    ZbarDatabarStk = 31,
+    /**< GS1 DataBar Stacked Omnidirectional */
+    ZbarDatabarStkOmni = 32,
+    /**< GS1 DataBar Expanded Stacked */
+    ZbarDatabarExpStk = 33,
     /**< GS1 DataBar (RSS). @since 0.11 */
     ZbarDatabar = 34,
     /**< GS1 DataBar Expanded. @since 0.11 */
     ZbarDatabarExp = 35,
+    /**< GS1 DataBar Limited */
+    ZbarDatabarLtd = 36,
     /**< Codabar. @since 0.11 */
     ZbarCodabar = 38,
     /**< Code 39. @since 0.4 */
     ZbarCode39 = 39,
+    /**< Data Matrix ECC 200 */
+    ZbarDatamatrix = 48,
+    /**< Aztec Code */
+    ZbarAztec = 49,
     /**< PDF417. @since 0.6 */
     ZbarPdf417 = 57,
     /**< QR Code. @since 0.10 */
     ZbarQrcode = 64,
+    /**< Micro QR Code */
+    ZbarMicroqr = 65,
+    /**< rMQR Code (rectangular Micro QR) */
+    ZbarRmqr = 66,
     /**< SQ Code. @since 0.20.1 */
     ZbarSqcode = 80,
     /**< Code 93. @since 0.11 */
@@ -157,6 +242,7 @@ pub enum ZbarSymbolType {
 }
 
 #[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ZbarConfig {
     /**< enable symbology/feature */
     Enable = 0,
@@ -180,9 +266,20 @@ pub enum ZbarConfig {
     Position = 0x80,
     /**< if fails to decode, test inverted */
     TestInverted,
+    /**< if fails to decode, test mirrored QR */
+    TestMirrored,
 
     /**< image scanner vertical scan density */
     XDensity = 0x100,
     /**< image scanner horizontal scan density */
     YDensity,
+    /**< image scanner diagonal scan angle step */
+    Diagonal,
+
+    /**< scanner minimum edge gradient */
+    EdgeThreshold = 0x200,
+    /**< scanner smoothing weight (percent) */
+    EwmaWeight,
+    /**< scanner edge threshold decay rate */
+    ThresholdDecay,
 }
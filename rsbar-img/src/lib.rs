@@ -6,8 +6,13 @@ use std::time::SystemTime;
 use anyhow::{anyhow, Result};
 use log::LevelFilter;
 
-pub use crate::utils::cli_args::Args;
-use crate::utils::XmlPrinter;
+pub use crate::utils::cli_args::{Args, ImageSize};
+pub use crate::utils::{
+    BackgroundColor, Candidate, CandidateKind, Channels, Contrast, Coordinate, EdgeDetection,
+    FourCC, LoadOptions, Preprocess, RawImage, Region, Scanner, Strategy, StructuredAppend, Symbol,
+    Threshold,
+};
+use crate::utils::{Reassembler, XmlPrinter};
 
 pub fn run(args: Args) -> Result<()> {
     let start_time = SystemTime::now();
@@ -16,9 +21,9 @@ pub fn run(args: Args) -> Result<()> {
 
     check_images(&args)?;
 
-    let processor = initialize_processor(&args)?;
+    let mut scanner = initialize_scanner(&args)?;
 
-    let detected_symbol_count = scan_images(&args, processor)?;
+    let detected_symbol_count = scan_images(&args, &mut scanner)?;
 
     print_scan_result(
         args,
@@ -28,7 +33,7 @@ pub fn run(args: Args) -> Result<()> {
             .map_or(f32::NAN, |time| time.as_secs_f32()),
     );
 
-    drop_processor(processor);
+    drop(scanner);
 
     if detected_symbol_count == 0 {
         return Err(anyhow!("No symbol detected"));
@@ -62,74 +67,51 @@ fn check_images(args: &Args) -> Result<()> {
     Ok(())
 }
 
-fn initialize_processor(args: &Args) -> Result<*mut libc::c_void> {
-    let Args {
-        display, nodbus, ..
-    } = args;
+fn initialize_scanner(args: &Args) -> Result<Scanner> {
+    let mut scanner = Scanner::with_display(args.display, !args.nodbus)?;
 
-    unsafe {
-        let processor = ffi::zbar_processor_create(0);
-
-        assert!(!processor.is_null());
-
-        if cfg!(feature = "dbus") {
-            ffi::zbar_processor_request_dbus(processor, (!nodbus).into());
-        }
-
-        if ffi::zbar_processor_init(processor, std::ptr::null(), (*display).into()) != 0 {
-            ffi::_zbar_error_spew(processor, 0);
-            return Err(anyhow!("Failed to initialize the processor"));
-        }
-
-        apply_arguments_to_processor(processor, args)?;
-
-        Ok(processor)
-    }
-}
-
-fn parse_configs(args: &Args, processor: *mut libc::c_void) -> Result<()> {
     args.config
         .iter()
-        .try_for_each(|setting| utils::parse_config(processor, setting))
-}
-
-fn apply_arguments_to_processor(processor: *mut libc::c_void, args: &Args) -> Result<()> {
-    unsafe {
-        ffi::zbar_processor_set_visible(processor, args.display.into());
-    }
+        .try_for_each(|setting| scanner.set_config(setting))?;
 
-    parse_configs(args, processor)?;
+    scanner.set_crop(args.crop);
+    scanner.set_regions(args.roi.clone());
+    scanner.set_preprocessing(args.preprocess.clone());
+    scanner.set_try_harder(args.try_harder);
+    scanner.set_pyramid_levels(args.pyramid);
+    scanner.set_rectify(args.rectify);
 
-    Ok(())
+    Ok(scanner)
 }
 
-fn scan_images(args: &Args, processor: *mut libc::c_void) -> Result<u8> {
+fn scan_images(args: &Args, scanner: &mut Scanner) -> Result<usize> {
     if args.xml {
         XmlPrinter::print_head();
     }
 
+    // Structured append messages may be split across several images
+    let mut reassembler = Reassembler::default();
+
     let detected_symbol_count = args
         .images
         .iter()
         .enumerate()
-        .map(|(idx, image_path)| utils::scan_image(image_path, idx, processor, args))
-        .collect::<Result<Vec<u8>, _>>()
+        .map(|(idx, image_path)| {
+            utils::scan_image(image_path, idx, scanner, &mut reassembler, args)
+        })
+        .collect::<Result<Vec<usize>, _>>()
         .map(|symbol_counts| symbol_counts.iter().sum());
 
     if args.xml {
         XmlPrinter::print_foot();
     }
 
-    detected_symbol_count
-}
+    reassembler.report_incomplete();
 
-fn drop_processor(processor: *mut libc::c_void) {
-    unsafe {
-        ffi::zbar_processor_destroy(processor);
-    }
+    detected_symbol_count
 }
 
-fn print_no_symbol_detected_warning(detected_symbol_count: u8) {
+fn print_no_symbol_detected_warning(detected_symbol_count: usize) {
     if log::log_enabled!(log::Level::Warn) && detected_symbol_count == 0 {
         let mut warning_str = String::from(
             "WARNING: barcode data was not detected in some image(s)\n\
@@ -143,7 +125,9 @@ fn print_no_symbol_detected_warning(detected_symbol_count: u8) {
         );
 
         #[cfg(feature = "databar")]
-        warning_str.push_str("\t- DataBar, DataBar Expanded\n");
+        warning_str.push_str(
+            "\t- DataBar, DataBar Limited, DataBar Stacked, DataBar Expanded, DataBar Expanded Stacked\n",
+        );
 
         #[cfg(feature = "code128")]
         warning_str.push_str("\t- Code 128\n");
@@ -158,10 +142,10 @@ fn print_no_symbol_detected_warning(detected_symbol_count: u8) {
         warning_str.push_str("\t- Codabar\n");
 
         #[cfg(feature = "i25")]
-        warning_str.push_str("\t- Interleaved 2 of 5\n");
+        warning_str.push_str("\t- Interleaved 2 of 5, ITF-14\n");
 
         #[cfg(feature = "qrcode")]
-        warning_str.push_str("\t- QR code\n");
+        warning_str.push_str("\t- QR code, Micro QR code, rMQR code\n");
 
         #[cfg(feature = "sqcode")]
         warning_str.push_str("\t- SQ code\n");
@@ -169,6 +153,12 @@ fn print_no_symbol_detected_warning(detected_symbol_count: u8) {
         #[cfg(feature = "pdf417")]
         warning_str.push_str("\t- PDF 417\n");
 
+        #[cfg(feature = "datamatrix")]
+        warning_str.push_str("\t- Data Matrix\n");
+
+        #[cfg(feature = "aztec")]
+        warning_str.push_str("\t- Aztec\n");
+
         warning_str.push_str(
             "  - is the barcode large enough in the image?\n  \
             - is the barcode mostly in focus?\n  \
@@ -186,7 +176,7 @@ fn print_no_symbol_detected_warning(detected_symbol_count: u8) {
     }
 }
 
-fn print_scan_result(args: Args, detected_symbol_count: u8, elapsed_time: f32) {
+fn print_scan_result(args: Args, detected_symbol_count: usize, elapsed_time: f32) {
     log::info!("scanned {detected_symbol_count} barcode symbols from {} images in {elapsed_time:.2} seconds", args.image_count());
 
     print_no_symbol_detected_warning(detected_symbol_count);
@@ -0,0 +1,163 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, Error, Result};
+use image::{DynamicImage, GrayImage, Luma, RgbImage};
+
+use super::load_image::{BackgroundColor, LUMA_COEFFICIENTS};
+
+/// Iterations of the power method used to find the direction of largest color variance
+const POWER_ITERATIONS: usize = 32;
+
+/// Color planes of an image scanned in addition to its luma, so colored codes whose colors
+/// have about the same brightness can still be found
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Channels {
+    /// Only scan the luma of the image
+    #[default]
+    Luma,
+    /// Also scan the red, green and blue channels separately
+    Rgb,
+    /// Also scan the projection of the colors onto the direction of their largest variance
+    Projection,
+    /// Also scan the red, green and blue channels and the projection
+    All,
+}
+
+impl Channels {
+    /// Extract the planes to scan besides the luma, none for grayscale images
+    pub(crate) fn extract(
+        self,
+        image: &DynamicImage,
+        background: BackgroundColor,
+    ) -> Vec<GrayImage> {
+        if self == Self::Luma || !image.color().has_color() {
+            return Vec::new();
+        }
+
+        let rgb = flatten(image, background);
+        let mut planes = Vec::new();
+
+        if matches!(self, Self::Rgb | Self::All) {
+            planes.extend((0..3).map(|channel| {
+                GrayImage::from_fn(rgb.width(), rgb.height(), |x, y| {
+                    Luma([rgb.get_pixel(x, y)[channel]])
+                })
+            }));
+        }
+
+        if matches!(self, Self::Projection | Self::All) {
+            planes.push(project(&rgb));
+        }
+
+        planes
+    }
+}
+
+impl fmt::Display for Channels {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Luma => write!(f, "luma"),
+            Self::Rgb => write!(f, "rgb"),
+            Self::Projection => write!(f, "projection"),
+            Self::All => write!(f, "all"),
+        }
+    }
+}
+
+impl FromStr for Channels {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "luma" => Ok(Self::Luma),
+            "rgb" => Ok(Self::Rgb),
+            "projection" => Ok(Self::Projection),
+            "all" => Ok(Self::All),
+            _ => Err(anyhow!(
+                "Expected `luma`, `rgb`, `projection` or `all`, got `{s}`"
+            )),
+        }
+    }
+}
+
+/// Composite the image onto the background color
+fn flatten(image: &DynamicImage, background: BackgroundColor) -> RgbImage {
+    let rgba = image.to_rgba8();
+
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let pixel = rgba.get_pixel(x, y);
+        let alpha = f32::from(pixel[3]) / 255.0;
+
+        image::Rgb(std::array::from_fn(|channel| {
+            (alpha * f32::from(pixel[channel]) + (1.0 - alpha) * f32::from(background.0[channel]))
+                .round() as u8
+        }))
+    })
+}
+
+/// Project the colors onto their first principal component, stretched to the full 8-bit range
+///
+/// The direction is oriented to agree with the luma, so dark codes stay dark
+fn project(rgb: &RgbImage) -> GrayImage {
+    let count = rgb.pixels().len().max(1) as f64;
+    let color = |pixel: &image::Rgb<u8>| pixel.0.map(f64::from);
+    let mean = rgb.pixels().fold([0.0; 3], |sum, pixel| {
+        let color = color(pixel);
+
+        std::array::from_fn(|idx| sum[idx] + color[idx] / count)
+    });
+    let covariance = rgb.pixels().fold([[0.0; 3]; 3], |sum, pixel| {
+        let color = color(pixel);
+
+        std::array::from_fn(|row| {
+            std::array::from_fn(|col| {
+                sum[row][col] + (color[row] - mean[row]) * (color[col] - mean[col]) / count
+            })
+        })
+    });
+    let mut direction = LUMA_COEFFICIENTS.map(f64::from);
+
+    for _ in 0..POWER_ITERATIONS {
+        let next: [f64; 3] = std::array::from_fn(|row| {
+            (0..3)
+                .map(|col| covariance[row][col] * direction[col])
+                .sum()
+        });
+        let norm = next.iter().map(|value| value * value).sum::<f64>().sqrt();
+
+        if norm == 0.0 {
+            break;
+        }
+
+        direction = next.map(|value| value / norm);
+    }
+
+    if direction
+        .iter()
+        .zip(LUMA_COEFFICIENTS)
+        .map(|(value, coefficient)| value * f64::from(coefficient))
+        .sum::<f64>()
+        < 0.0
+    {
+        direction = direction.map(|value| -value);
+    }
+
+    let projected = |pixel: &image::Rgb<u8>| {
+        color(pixel)
+            .iter()
+            .zip(direction)
+            .map(|(channel, weight)| channel * weight)
+            .sum::<f64>()
+    };
+    let (low, high) = rgb
+        .pixels()
+        .map(projected)
+        .fold((f64::MAX, f64::MIN), |(low, high), value| {
+            (low.min(value), high.max(value))
+        });
+    let range = (high - low).max(f64::EPSILON);
+
+    GrayImage::from_fn(rgb.width(), rgb.height(), |x, y| {
+        Luma([((projected(rgb.get_pixel(x, y)) - low) / range * 255.0).round() as u8])
+    })
+}
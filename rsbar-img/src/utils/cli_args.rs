@@ -1,8 +1,11 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, str::FromStr};
 
+use anyhow::{anyhow, Error, Result};
 use clap::Parser;
 use clap_verbosity_flag::Verbosity;
 
+use super::{BackgroundColor, Channels, FourCC, LoadOptions, Preprocess, Region};
+
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None)]
 pub struct Args {
@@ -49,10 +52,103 @@ pub struct Args {
     /// Disable XML output format (default)
     #[arg(long = "noxml")]
     _no_xml: bool,
+
+    /// Color transparent parts of the image(s) are composited onto (white, black or #rrggbb)
+    #[arg(long, value_name = "COLOR", default_value = "white")]
+    pub background: BackgroundColor,
+
+    /// Color planes of the image file(s) scanned in addition to the luma, to find colored codes
+    /// (luma, rgb, projection onto the direction of largest color variance, or all)
+    #[arg(long, value_name = "CHANNELS", default_value = "luma")]
+    pub channels: Channels,
+
+    /// Read the image file(s) as raw pixel data in the given format (e.g. NV12, YUYV, RGB3)
+    #[arg(long, value_name = "FOURCC", requires = "size")]
+    pub raw_format: Option<FourCC>,
+
+    /// Size of the raw image(s)
+    #[arg(long, value_name = "WIDTHxHEIGHT", requires = "raw_format")]
+    pub size: Option<ImageSize>,
+
+    /// Number of bytes between the starts of two rows in the raw image(s) (default: no padding)
+    #[arg(long, value_name = "BYTES", requires = "raw_format")]
+    pub stride: Option<usize>,
+
+    /// Only scan the given part of the image(s), in pixels or percentages (e.g. 10,10,50%,50%)
+    #[arg(long, value_name = "X,Y,WIDTH,HEIGHT")]
+    pub crop: Option<Region>,
+
+    /// Scan the given part of the image(s) separately, may be repeated (same syntax as --crop)
+    #[arg(long, value_name = "X,Y,WIDTH,HEIGHT")]
+    pub roi: Vec<Region>,
+
+    /// Transform the image(s) before scanning, steps are applied in order
+    /// (rotate:DEGREES, scale:FACTOR, contrast[:auto|FACTOR], sharpen[:SIGMA],
+    /// threshold[:otsu|adaptive[:RADIUS]|0-255], invert)
+    #[arg(long, value_name = "STEP[,STEP...]", value_delimiter = ',')]
+    pub preprocess: Vec<Preprocess>,
+
+    /// If no symbol is found in an image, rescan it with fallbacks (rescaling, rotation,
+    /// inversion, adaptive thresholding, full scan density) until one succeeds; the fallback used
+    /// is logged with `-vv`
+    #[arg(long)]
+    pub try_harder: bool,
+
+    /// Also scan up to LEVELS - 1 successively halved copies of each image, to find small or
+    /// noisy codes in huge images (4 levels if no value is given)
+    #[arg(
+        long,
+        value_name = "LEVELS",
+        default_value = "1",
+        default_missing_value = "4",
+        num_args = 0..=1,
+        require_equals = true,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub pyramid: u32,
+
+    /// Also rescan regions of parallel bars warped to a fronto-parallel view, to decode 1D bar
+    /// codes on tilted surfaces
+    #[arg(long)]
+    pub rectify: bool,
+
+    /// Also report regions looking like bar codes (as `Candidate:KIND:X,Y,WIDTH,HEIGHT:CONFIDENCE`),
+    /// whether or not they could be decoded; only decoded symbols count for the exit status
+    #[arg(long)]
+    pub locate: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl FromStr for ImageSize {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (width, height) = s
+            .split_once(['x', 'X'])
+            .ok_or_else(|| anyhow!("Expected a size like `1920x1080`, got `{s}`"))?;
+
+        Ok(Self {
+            width: width.trim().parse()?,
+            height: height.trim().parse()?,
+        })
+    }
 }
 
 impl Args {
     pub fn image_count(&self) -> usize {
         self.images.len()
     }
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            background: self.background,
+            channels: self.channels,
+            ..Default::default()
+        }
+    }
 }
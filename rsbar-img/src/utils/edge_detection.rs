@@ -0,0 +1,22 @@
+/// Parameters of the filter finding the edges between bars and spaces along scan lines, see
+/// the `edge-threshold`, `ewma-weight` and `threshold-decay` configs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EdgeDetection {
+    /// Minimum intensity gradient of an edge, higher values ignore more noise
+    pub threshold: u32,
+    /// Weight in percent (1 to 100) of new samples in the moving average smoothing the
+    /// intensities, lower values smooth more
+    pub ewma_weight: u32,
+    /// How slowly the threshold returns to its minimum after a strong edge, in element widths
+    pub threshold_decay: u32,
+}
+
+impl Default for EdgeDetection {
+    fn default() -> Self {
+        Self {
+            threshold: 4,
+            ewma_weight: 78,
+            threshold_decay: 8,
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, Error, Result};
+
+/// Four character code identifying the pixel format of an image buffer
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FourCC([u8; 4]);
+
+/// Memory layout of the pixel formats understood by zbar
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PixelLayout {
+    /// A single plane with a fixed number of bytes per pixel
+    Packed { bytes_per_pixel: usize },
+    /// A luma plane followed by two separate, subsampled chroma planes
+    Planar {
+        x_subsampling: u32,
+        y_subsampling: u32,
+    },
+    /// A luma plane followed by one interleaved, subsampled chroma plane
+    SemiPlanar {
+        x_subsampling: u32,
+        y_subsampling: u32,
+    },
+}
+
+/// A single plane of an image buffer, with the row size in bytes and the row count
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Plane {
+    pub row_bytes: usize,
+    pub rows: usize,
+    pub stride: usize,
+}
+
+impl FourCC {
+    pub const GREY: Self = Self::new(b"GREY");
+    pub const Y800: Self = Self::new(b"Y800");
+    pub const YUYV: Self = Self::new(b"YUYV");
+    pub const UYVY: Self = Self::new(b"UYVY");
+    pub const NV12: Self = Self::new(b"NV12");
+    pub const NV21: Self = Self::new(b"NV21");
+    pub const I420: Self = Self::new(b"I420");
+    pub const YV12: Self = Self::new(b"YV12");
+    pub const RGB3: Self = Self::new(b"RGB3");
+    pub const BGR3: Self = Self::new(b"BGR3");
+    pub const RGB4: Self = Self::new(b"RGB4");
+    pub const BGR4: Self = Self::new(b"BGR4");
+
+    pub const fn new(code: &[u8; 4]) -> Self {
+        Self(*code)
+    }
+
+    pub(crate) const fn as_zbar_format(self) -> u64 {
+        u32::from_le_bytes(self.0) as u64
+    }
+
+    pub(crate) fn layout(self) -> Option<PixelLayout> {
+        use PixelLayout::*;
+
+        let layout = match &self.0 {
+            b"GREY" | b"Y800" | b"Y8  " | b"RGB1" | b"BGR1" => Packed { bytes_per_pixel: 1 },
+            b"YUYV" | b"YUY2" | b"UYVY" | b"YVYU" | b"RGBP" | b"RGBO" | b"RGBR" | b"RGBQ"
+            | b"R444" => Packed { bytes_per_pixel: 2 },
+            b"RGB3" | b"BGR3" => Packed { bytes_per_pixel: 3 },
+            b"RGB4" | b"BGR4" => Packed { bytes_per_pixel: 4 },
+            b"I420" | b"YU12" | b"YV12" => Planar {
+                x_subsampling: 1,
+                y_subsampling: 1,
+            },
+            b"422P" => Planar {
+                x_subsampling: 1,
+                y_subsampling: 0,
+            },
+            b"411P" => Planar {
+                x_subsampling: 2,
+                y_subsampling: 0,
+            },
+            b"YUV9" | b"YVU9" => Planar {
+                x_subsampling: 2,
+                y_subsampling: 2,
+            },
+            b"NV12" | b"NV21" => SemiPlanar {
+                x_subsampling: 1,
+                y_subsampling: 1,
+            },
+            _ => return None,
+        };
+
+        Some(layout)
+    }
+
+    /// Planes of an image with this format, where `stride` is the luma row size in bytes.
+    /// Chroma plane sizes follow the (floored) subsampling used by zbar.
+    pub(crate) fn planes(self, width: u32, height: u32, stride: usize) -> Result<Vec<Plane>> {
+        let layout = self
+            .layout()
+            .ok_or_else(|| anyhow!("Unsupported pixel format `{self}`"))?;
+        let (width, height) = (width as usize, height as usize);
+
+        let luma = |bytes_per_pixel: usize| -> Result<Plane> {
+            let row_bytes = width
+                .checked_mul(bytes_per_pixel)
+                .ok_or_else(|| anyhow!("Image width {width} is too large"))?;
+
+            if stride < row_bytes {
+                return Err(anyhow!(
+                    "Stride of {stride} bytes is smaller than a row of {row_bytes} bytes"
+                ));
+            }
+
+            Ok(Plane {
+                row_bytes,
+                rows: height,
+                stride,
+            })
+        };
+
+        let planes = match layout {
+            PixelLayout::Packed { bytes_per_pixel } => vec![luma(bytes_per_pixel)?],
+            PixelLayout::Planar {
+                x_subsampling,
+                y_subsampling,
+            } => {
+                let chroma = Plane {
+                    row_bytes: width >> x_subsampling,
+                    rows: height >> y_subsampling,
+                    stride: stride >> x_subsampling,
+                };
+
+                vec![luma(1)?, chroma, chroma]
+            }
+            PixelLayout::SemiPlanar {
+                x_subsampling,
+                y_subsampling,
+            } => {
+                let chroma = Plane {
+                    row_bytes: (width >> x_subsampling) * 2,
+                    rows: height >> y_subsampling,
+                    stride,
+                };
+
+                vec![luma(1)?, chroma]
+            }
+        };
+
+        Ok(planes)
+    }
+}
+
+impl fmt::Display for FourCC {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(&self.0).trim_end())
+    }
+}
+
+impl FromStr for FourCC {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let code: [u8; 4] = format!("{:<4}", s.to_ascii_uppercase())
+            .into_bytes()
+            .try_into()
+            .map_err(|_| anyhow!("A FourCC must have at most 4 characters, got `{s}`"))?;
+        let fourcc = Self(code);
+
+        if fourcc.layout().is_none() {
+            return Err(anyhow!("Unsupported pixel format `{s}`"));
+        }
+
+        Ok(fourcc)
+    }
+}
@@ -0,0 +1,296 @@
+use std::{fmt, io::Cursor, path::Path, str::FromStr};
+
+use anyhow::{anyhow, Context, Error, Result};
+use image::{imageops, DynamicImage, GrayImage, ImageBuffer, Luma, Pixel, Primitive};
+
+use super::{
+    channels::Channels,
+    load_pdf::{is_pdf, load_pdf_images},
+    preprocess::{contrast_range, stretch_contrast},
+    transform::Transform,
+};
+
+/// Rec. 709 luma coefficients, the same as used by the `image` crate
+pub(crate) const LUMA_COEFFICIENTS: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// Key value (average brightness) targeted when tone mapping HDR images
+const TONE_MAPPING_KEY: f32 = 0.18;
+
+/// Options controlling how image files are turned into grayscale images for scanning
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// Color transparent pixels are composited onto
+    pub background: BackgroundColor,
+    /// Rotate/flip the image as specified by its EXIF orientation tag
+    pub apply_orientation: bool,
+    /// Color planes of image files scanned in addition to their luma
+    pub channels: Channels,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            background: BackgroundColor::WHITE,
+            apply_orientation: true,
+            channels: Channels::Luma,
+        }
+    }
+}
+
+/// An opaque RGB color
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BackgroundColor(pub [u8; 3]);
+
+impl BackgroundColor {
+    pub const WHITE: Self = Self([255, 255, 255]);
+    pub const BLACK: Self = Self([0, 0, 0]);
+
+    fn luma(self) -> f32 {
+        self.0
+            .iter()
+            .zip(LUMA_COEFFICIENTS)
+            .map(|(&channel, coefficient)| f32::from(channel) / 255.0 * coefficient)
+            .sum()
+    }
+}
+
+impl fmt::Display for BackgroundColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [red, green, blue] = self.0;
+
+        write!(f, "#{red:02x}{green:02x}{blue:02x}")
+    }
+}
+
+impl FromStr for BackgroundColor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "white" => Ok(Self::WHITE),
+            "black" => Ok(Self::BLACK),
+            color => {
+                let hex = color.strip_prefix('#').unwrap_or(color);
+
+                if hex.len() != 6 || !hex.is_ascii() {
+                    return Err(anyhow!(
+                        "Expected `white`, `black` or a color like `#rrggbb`, got `{s}`"
+                    ));
+                }
+
+                let channel = |idx: usize| u8::from_str_radix(&hex[idx..idx + 2], 16);
+
+                Ok(Self([channel(0)?, channel(2)?, channel(4)?]))
+            }
+        }
+    }
+}
+
+/// A grayscale image ready for scanning, with the mapping of its points back to the file's pixels
+pub(crate) struct LoadedImage {
+    pub luma: GrayImage,
+    /// Color planes scanned in addition to the luma, in the same coordinates
+    pub channels: Vec<GrayImage>,
+    pub transform: Transform,
+    /// Number of the PDF page the image was extracted from
+    pub page: Option<u32>,
+}
+
+/// Load an image file, or all raster images embedded in a PDF file
+pub(crate) fn load_images(path: &Path, options: &LoadOptions) -> Result<Vec<LoadedImage>> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to open image `{}`", path.display()))?;
+
+    if !is_pdf(&bytes) {
+        return load_image(&bytes, options)
+            .map(|image| vec![image])
+            .with_context(|| format!("Failed to open image `{}`", path.display()));
+    }
+
+    let images = load_pdf_images(&bytes)
+        .with_context(|| format!("Failed to open PDF `{}`", path.display()))?;
+
+    Ok(images
+        .into_iter()
+        .map(|image| LoadedImage {
+            channels: options.channels.extract(&image.image, options.background),
+            luma: to_luma(image.image, options.background),
+            transform: Transform::IDENTITY,
+            page: Some(image.page),
+        })
+        .collect())
+}
+
+fn load_image(bytes: &[u8], options: &LoadOptions) -> Result<LoadedImage> {
+    let image = image::io::Reader::new(Cursor::new(bytes))
+        .with_guessed_format()?
+        .decode()?;
+
+    let channels = options.channels.extract(&image, options.background);
+    let luma = to_luma(image, options.background);
+    let orientation = if options.apply_orientation {
+        read_exif_orientation(bytes).unwrap_or(1)
+    } else {
+        1
+    };
+    let (luma, transform) = apply_orientation(luma, orientation);
+
+    Ok(LoadedImage {
+        luma,
+        channels: channels
+            .into_iter()
+            .map(|channel| apply_orientation(channel, orientation).0)
+            .collect(),
+        transform,
+        page: None,
+    })
+}
+
+fn read_exif_orientation(bytes: &[u8]) -> Option<u32> {
+    let exif = exif::Reader::new()
+        .read_from_container(&mut Cursor::new(bytes))
+        .ok()?;
+
+    exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?
+        .value
+        .get_uint(0)
+}
+
+/// Turn the stored pixels into the upright image, see the EXIF specification for the
+/// meaning of the orientation values
+fn apply_orientation(luma: GrayImage, orientation: u32) -> (GrayImage, Transform) {
+    let (w, h) = (f64::from(luma.width()), f64::from(luma.height()));
+
+    match orientation {
+        2 => (
+            imageops::flip_horizontal(&luma),
+            Transform::affine(-1.0, 0.0, w, 0.0, 1.0, 0.0),
+        ),
+        3 => (
+            imageops::rotate180(&luma),
+            Transform::affine(-1.0, 0.0, w, 0.0, -1.0, h),
+        ),
+        4 => (
+            imageops::flip_vertical(&luma),
+            Transform::affine(1.0, 0.0, 0.0, 0.0, -1.0, h),
+        ),
+        5 => (
+            imageops::flip_horizontal(&imageops::rotate90(&luma)),
+            Transform::affine(0.0, 1.0, 0.0, 1.0, 0.0, 0.0),
+        ),
+        6 => (
+            imageops::rotate90(&luma),
+            Transform::affine(0.0, 1.0, 0.0, -1.0, 0.0, h),
+        ),
+        7 => (
+            imageops::flip_horizontal(&imageops::rotate270(&luma)),
+            Transform::affine(0.0, -1.0, w, -1.0, 0.0, h),
+        ),
+        8 => (
+            imageops::rotate270(&luma),
+            Transform::affine(0.0, -1.0, w, 1.0, 0.0, 0.0),
+        ),
+        _ => (luma, Transform::IDENTITY),
+    }
+}
+
+fn to_luma(image: DynamicImage, background: BackgroundColor) -> GrayImage {
+    let linear_background = srgb_to_linear(background.luma());
+    let background = background.luma();
+    let to_u8 = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let to_u16 = |value: f32| (value.clamp(0.0, 1.0) * f32::from(u16::MAX)).round() as u16;
+    let to_f32 = |value: f32| value;
+
+    match image {
+        DynamicImage::ImageLuma8(_) | DynamicImage::ImageRgb8(_) => image.into_luma8(),
+        DynamicImage::ImageLumaA8(image) => flatten(&image, background, to_u8),
+        DynamicImage::ImageRgba8(image) => flatten(&image, background, to_u8),
+        DynamicImage::ImageLuma16(image) => stretch_luma(&flatten(&image, background, to_u16)),
+        DynamicImage::ImageLumaA16(image) => stretch_luma(&flatten(&image, background, to_u16)),
+        DynamicImage::ImageRgb16(image) => stretch_luma(&flatten(&image, background, to_u16)),
+        DynamicImage::ImageRgba16(image) => stretch_luma(&flatten(&image, background, to_u16)),
+        DynamicImage::ImageRgb32F(image) => {
+            tone_map_hdr(&flatten(&image, linear_background, to_f32))
+        }
+        DynamicImage::ImageRgba32F(image) => {
+            tone_map_hdr(&flatten(&image, linear_background, to_f32))
+        }
+        _ => image.into_luma8(),
+    }
+}
+
+/// Compute the luma of every pixel after compositing it onto the background, as a fraction of
+/// the full range, and store it with `to_subpixel`
+fn flatten<P, S>(
+    image: &ImageBuffer<P, Vec<P::Subpixel>>,
+    background: f32,
+    to_subpixel: impl Fn(f32) -> S,
+) -> ImageBuffer<Luma<S>, Vec<S>>
+where
+    P: Pixel,
+    P::Subpixel: Into<f32>,
+    S: Primitive,
+{
+    let max: f32 = P::Subpixel::DEFAULT_MAX_VALUE.into();
+
+    ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        let [red, green, blue, alpha] = image.get_pixel(x, y).to_rgba().0.map(Into::into);
+        let alpha = (alpha / max).clamp(0.0, 1.0);
+        let luma: f32 = [red, green, blue]
+            .iter()
+            .zip(LUMA_COEFFICIENTS)
+            .map(|(channel, coefficient)| channel / max * coefficient)
+            .sum();
+
+        Luma([to_subpixel(alpha * luma + (1.0 - alpha) * background)])
+    })
+}
+
+/// Map the luma range between the darkest and brightest percentiles onto the full 8-bit range,
+/// so high bit depth images only using part of their range keep their contrast
+fn stretch_luma(luma: &ImageBuffer<Luma<u16>, Vec<u16>>) -> GrayImage {
+    let mut histogram = vec![0u64; usize::from(u16::MAX) + 1];
+
+    luma.pixels()
+        .for_each(|pixel| histogram[usize::from(pixel[0])] += 1);
+
+    let range = contrast_range(&histogram);
+
+    GrayImage::from_fn(luma.width(), luma.height(), |x, y| {
+        Luma([stretch_contrast(f64::from(luma.get_pixel(x, y)[0]), range)])
+    })
+}
+
+/// Global Reinhard tone mapping of linear luminance, normalized by the log-average luminance
+fn tone_map_hdr(luma: &ImageBuffer<Luma<f32>, Vec<f32>>) -> GrayImage {
+    let pixel_count = luma.len().max(1) as f32;
+    let log_average = (luma
+        .pixels()
+        .map(|pixel| (pixel[0].max(0.0) + 1e-4).ln())
+        .sum::<f32>()
+        / pixel_count)
+        .exp();
+
+    GrayImage::from_fn(luma.width(), luma.height(), |x, y| {
+        let scaled = luma.get_pixel(x, y)[0].max(0.0) * TONE_MAPPING_KEY / log_average;
+        let mapped = scaled / (1.0 + scaled);
+
+        Luma([(linear_to_srgb(mapped) * 255.0).round() as u8])
+    })
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -0,0 +1,523 @@
+use std::{collections::HashSet, io::Read};
+
+use anyhow::{anyhow, Context, Result};
+use image::{DynamicImage, GrayImage, Luma, Rgb, RgbImage};
+use jpeg_decoder::PixelFormat;
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+
+/// Default width of CCITT encoded images, see the PDF specification
+const CCITT_DEFAULT_COLUMNS: i64 = 1728;
+
+/// A raster image embedded in a PDF file
+pub(crate) struct PdfImage {
+    /// Number of the page showing the image, starting at 1
+    pub page: u32,
+    pub image: DynamicImage,
+}
+
+pub(crate) fn is_pdf(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"%PDF-")
+}
+
+/// Extract the raster images of every page, skipping (with a warning) the ones that can't be decoded
+pub(crate) fn load_pdf_images(bytes: &[u8]) -> Result<Vec<PdfImage>> {
+    let doc = Document::load_mem(bytes)?;
+    let mut images = Vec::new();
+
+    for (page, page_id) in doc.get_pages() {
+        let mut visited = HashSet::new();
+        let (resources, resource_ids) = doc.get_page_resources(page_id);
+        let resources = resources.into_iter().chain(
+            resource_ids
+                .into_iter()
+                .filter_map(|id| doc.get_dictionary(id).ok()),
+        );
+
+        for resources in resources {
+            for stream in image_streams(&doc, resources, &mut visited) {
+                match decode_image(&doc, stream) {
+                    Ok(image) => images.push(PdfImage { page, image }),
+                    Err(err) => log::warn!("skipping image on page {page}: {err:#}"),
+                }
+            }
+        }
+    }
+
+    Ok(images)
+}
+
+/// Find the image XObjects of a resource dictionary, including the ones drawn by form XObjects
+fn image_streams<'a>(
+    doc: &'a Document,
+    resources: &'a Dictionary,
+    visited: &mut HashSet<ObjectId>,
+) -> Vec<&'a Stream> {
+    let Ok(Object::Dictionary(xobjects)) = resources.get_deref(b"XObject", doc) else {
+        return Vec::new();
+    };
+    let mut streams = Vec::new();
+
+    for (_, xobject) in xobjects.iter() {
+        let Ok((id, Object::Stream(stream))) = doc.dereference(xobject) else {
+            continue;
+        };
+
+        if id.map_or(false, |id| !visited.insert(id)) {
+            continue;
+        }
+
+        match stream.dict.get(b"Subtype").and_then(Object::as_name) {
+            Ok(b"Image") => streams.push(stream),
+            Ok(b"Form") => {
+                if let Ok(Object::Dictionary(resources)) = stream.dict.get_deref(b"Resources", doc)
+                {
+                    streams.extend(image_streams(doc, resources, visited));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    streams
+}
+
+fn get_int(dict: &Dictionary, key: &[u8], doc: &Document) -> Option<i64> {
+    dict.get_deref(key, doc).and_then(Object::as_i64).ok()
+}
+
+fn get_bool(dict: &Dictionary, key: &[u8], doc: &Document) -> Option<bool> {
+    dict.get_deref(key, doc).and_then(Object::as_bool).ok()
+}
+
+fn decode_image(doc: &Document, stream: &Stream) -> Result<DynamicImage> {
+    let dict = &stream.dict;
+    let dimension = |key: &[u8]| {
+        get_int(dict, key, doc)
+            .and_then(|value| u32::try_from(value).ok())
+            .filter(|&value| value > 0)
+            .ok_or_else(|| anyhow!("Missing or invalid image {}", String::from_utf8_lossy(key)))
+    };
+    let (width, height) = (dimension(b"Width")?, dimension(b"Height")?);
+    let filters = stream.filters().unwrap_or_default();
+    let mut data = stream.content.clone();
+
+    for (idx, filter) in filters.iter().enumerate() {
+        let params = decode_params(doc, dict, idx);
+
+        data = match filter.as_str() {
+            "FlateDecode" | "Fl" => apply_predictor(doc, inflate(&data)?, params)?,
+            "DCTDecode" | "DCT" => decode_dct(&data, width, height)?,
+            "CCITTFaxDecode" | "CCF" => decode_ccitt(doc, &data, params, width, height)?,
+            filter => return Err(anyhow!("Unsupported image filter `{filter}`")),
+        };
+    }
+
+    samples_to_image(doc, dict, &data, width, height)
+}
+
+/// Parameters of the filter at `idx`, `DecodeParms` being either a dictionary or one per filter
+fn decode_params<'a>(
+    doc: &'a Document,
+    dict: &'a Dictionary,
+    idx: usize,
+) -> Option<&'a Dictionary> {
+    match dict.get_deref(b"DecodeParms", doc).ok()? {
+        Object::Dictionary(params) => Some(params),
+        Object::Array(params) => doc.dereference(params.get(idx)?).ok()?.1.as_dict().ok(),
+        _ => None,
+    }
+}
+
+fn inflate(data: &[u8]) -> Result<Vec<u8>> {
+    let mut inflated = Vec::new();
+
+    flate2::read::ZlibDecoder::new(data)
+        .read_to_end(&mut inflated)
+        .context("Failed to inflate image data")?;
+
+    Ok(inflated)
+}
+
+/// Undo the PNG or TIFF prediction of Flate encoded data
+fn apply_predictor(doc: &Document, data: Vec<u8>, params: Option<&Dictionary>) -> Result<Vec<u8>> {
+    let Some(params) = params else {
+        return Ok(data);
+    };
+    let param =
+        |key: &[u8], default: i64| get_int(params, key, doc).unwrap_or(default).max(1) as usize;
+    let predictor = param(b"Predictor", 1);
+    let colors = param(b"Colors", 1);
+    let bits_per_component = param(b"BitsPerComponent", 8);
+    let columns = param(b"Columns", 1);
+    let bytes_per_pixel = (colors * bits_per_component + 7) / 8;
+    let row_len = (colors * bits_per_component * columns + 7) / 8;
+
+    match predictor {
+        1 => Ok(data),
+        2 if bits_per_component == 8 => Ok(data
+            .chunks(row_len)
+            .flat_map(|row| {
+                let mut row = row.to_vec();
+
+                for idx in bytes_per_pixel..row.len() {
+                    row[idx] = row[idx].wrapping_add(row[idx - bytes_per_pixel]);
+                }
+
+                row
+            })
+            .collect()),
+        10..=15 => {
+            let mut decoded = Vec::with_capacity(data.len());
+            let mut previous = vec![0u8; row_len];
+
+            for chunk in data.chunks(row_len + 1) {
+                let (&filter, encoded) = chunk.split_first().unwrap_or((&0, &[]));
+                let mut row = encoded.to_vec();
+
+                for idx in 0..row.len() {
+                    let left = if idx >= bytes_per_pixel {
+                        row[idx - bytes_per_pixel]
+                    } else {
+                        0
+                    };
+                    let up = previous[idx];
+                    let up_left = if idx >= bytes_per_pixel {
+                        previous[idx - bytes_per_pixel]
+                    } else {
+                        0
+                    };
+
+                    row[idx] = row[idx].wrapping_add(match filter {
+                        1 => left,
+                        2 => up,
+                        3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                        4 => paeth(left, up, up_left),
+                        _ => 0,
+                    });
+                }
+
+                decoded.extend_from_slice(&row);
+                previous[..row.len()].copy_from_slice(&row);
+            }
+
+            Ok(decoded)
+        }
+        predictor => Err(anyhow!("Unsupported predictor {predictor}")),
+    }
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = i16::from(left) + i16::from(up) - i16::from(up_left);
+    let distance = |value: u8| (estimate - i16::from(value)).abs();
+
+    if distance(left) <= distance(up) && distance(left) <= distance(up_left) {
+        left
+    } else if distance(up) <= distance(up_left) {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// Decode JPEG data into its samples, leaving the color space and decode array to `samples_to_image`
+fn decode_dct(data: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
+    let mut decoder = jpeg_decoder::Decoder::new(data);
+    let mut samples = decoder.decode().context("Failed to decode JPEG image")?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| anyhow!("Failed to decode JPEG image"))?;
+
+    if (u32::from(info.width), u32::from(info.height)) != (width, height) {
+        return Err(anyhow!("JPEG size doesn't match the image size"));
+    }
+
+    match info.pixel_format {
+        PixelFormat::L8 | PixelFormat::RGB24 => {}
+        // The decoder inverts the samples of Adobe CMYK data (only the black ones of YCCK data),
+        // which PDF files rather undo with a decode array
+        PixelFormat::CMYK32 => {
+            let inverted = if is_ycck(data) { 3..4 } else { 0..4 };
+
+            for pixel in samples.chunks_exact_mut(4) {
+                pixel[inverted.clone()]
+                    .iter_mut()
+                    .for_each(|sample| *sample = 255 - *sample);
+            }
+        }
+        format => return Err(anyhow!("Unsupported JPEG pixel format {format:?}")),
+    }
+
+    Ok(samples)
+}
+
+/// Whether the Adobe marker of JPEG data flags its 4 components as YCCK
+fn is_ycck(data: &[u8]) -> bool {
+    let mut segments = data.get(2..).unwrap_or_default();
+
+    // Markers are followed by their big endian length, which includes the length itself
+    while let [0xff, marker, high, low, rest @ ..] = segments {
+        let payload = rest
+            .get(..(usize::from(*high) << 8 | usize::from(*low)).saturating_sub(2))
+            .unwrap_or(rest);
+
+        match marker {
+            0xda => break,
+            0xee if payload.starts_with(b"Adobe") => return payload.get(11) == Some(&2),
+            _ => segments = &rest[payload.len()..],
+        }
+    }
+
+    false
+}
+
+/// Decode CCITT Group 3 (1D) or Group 4 data into 1 bit per pixel rows
+fn decode_ccitt(
+    doc: &Document,
+    data: &[u8],
+    params: Option<&Dictionary>,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>> {
+    let param = |key: &[u8]| params.and_then(|params| get_int(params, key, doc));
+    let k = param(b"K").unwrap_or(0);
+    let columns = u16::try_from(param(b"Columns").unwrap_or(CCITT_DEFAULT_COLUMNS))
+        .context("Too many columns for a CCITT image")?;
+    let rows = u16::try_from(param(b"Rows").unwrap_or_else(|| height.into())).ok();
+    let black_is_1 = params
+        .and_then(|params| get_bool(params, b"BlackIs1", doc))
+        .unwrap_or(false);
+    if u32::from(columns) != width {
+        return Err(anyhow!("CCITT columns don't match the image width"));
+    }
+
+    let row_len = (usize::from(columns) + 7) / 8;
+    let mut rows_data = Vec::with_capacity(row_len * height as usize);
+    let mut push_line = |transitions: &[u16]| {
+        let mut row = vec![0u8; row_len];
+
+        for (idx, color) in fax::decoder::pels(transitions, columns).enumerate() {
+            if (color == fax::Color::Black) == black_is_1 {
+                row[idx / 8] |= 0x80 >> (idx % 8);
+            }
+        }
+
+        rows_data.extend_from_slice(&row);
+    };
+    let decoded = match k {
+        k if k < 0 => fax::decoder::decode_g4(data.iter().copied(), columns, rows, &mut push_line),
+        0 => fax::decoder::decode_g3(data.iter().copied(), &mut push_line),
+        _ => return Err(anyhow!("Unsupported mixed CCITT Group 3 encoding")),
+    };
+
+    if decoded.is_none() && rows_data.is_empty() {
+        return Err(anyhow!("Failed to decode CCITT image"));
+    }
+
+    // Pad missing rows with white, like the decoder does for Group 4 data
+    rows_data.resize(
+        row_len * height as usize,
+        if black_is_1 { 0x00 } else { 0xff },
+    );
+
+    Ok(rows_data)
+}
+
+/// A color space the samples of an image are interpreted in
+enum ColorSpace {
+    Gray,
+    Rgb,
+    Cmyk,
+    Indexed {
+        base: Box<ColorSpace>,
+        palette: Vec<u8>,
+    },
+}
+
+impl ColorSpace {
+    fn parse(doc: &Document, object: &Object) -> Result<Self> {
+        let (_, object) = doc.dereference(object)?;
+
+        match object {
+            Object::Name(name) => match name.as_slice() {
+                b"DeviceGray" | b"G" | b"CalGray" => Ok(Self::Gray),
+                b"DeviceRGB" | b"RGB" | b"CalRGB" => Ok(Self::Rgb),
+                b"DeviceCMYK" | b"CMYK" => Ok(Self::Cmyk),
+                name => Err(anyhow!(
+                    "Unsupported color space `{}`",
+                    String::from_utf8_lossy(name)
+                )),
+            },
+            Object::Array(array) => {
+                let family = array.first().map(Object::as_name).transpose()?;
+
+                match family {
+                    Some(b"ICCBased") => {
+                        let profile = doc.dereference(array.get(1).ok_or(lopdf::Error::Type)?)?.1;
+
+                        match get_int(&profile.as_stream()?.dict, b"N", doc) {
+                            Some(1) => Ok(Self::Gray),
+                            Some(3) => Ok(Self::Rgb),
+                            Some(4) => Ok(Self::Cmyk),
+                            _ => Err(anyhow!("Unsupported ICC based color space")),
+                        }
+                    }
+                    Some(b"Indexed" | b"I") if array.len() == 4 => {
+                        let base = Self::parse(doc, &array[1])?;
+                        let palette = match doc.dereference(&array[3])?.1 {
+                            Object::String(palette, _) => palette.clone(),
+                            Object::Stream(stream) => stream
+                                .decompressed_content()
+                                .unwrap_or_else(|_| stream.content.clone()),
+                            _ => return Err(anyhow!("Invalid palette of indexed color space")),
+                        };
+
+                        Ok(Self::Indexed {
+                            base: Box::new(base),
+                            palette,
+                        })
+                    }
+                    Some(b"CalGray") => Ok(Self::Gray),
+                    Some(b"CalRGB") => Ok(Self::Rgb),
+                    Some(name) => Err(anyhow!(
+                        "Unsupported color space `{}`",
+                        String::from_utf8_lossy(name)
+                    )),
+                    None => Err(anyhow!("Invalid color space")),
+                }
+            }
+            _ => Err(anyhow!("Invalid color space")),
+        }
+    }
+
+    fn components(&self) -> usize {
+        match self {
+            Self::Gray | Self::Indexed { .. } => 1,
+            Self::Rgb => 3,
+            Self::Cmyk => 4,
+        }
+    }
+
+    /// Whether the colors are shades of gray
+    fn is_gray(&self) -> bool {
+        match self {
+            Self::Gray => true,
+            Self::Indexed { base, .. } => base.is_gray(),
+            Self::Rgb | Self::Cmyk => false,
+        }
+    }
+
+    /// Red, green and blue of normalized color components
+    fn rgb(&self, components: &[f32]) -> [f32; 3] {
+        match self {
+            Self::Gray => [components[0]; 3],
+            Self::Rgb => [components[0], components[1], components[2]],
+            Self::Cmyk => {
+                let black = 1.0 - components[3];
+
+                std::array::from_fn(|idx| (1.0 - components[idx]) * black)
+            }
+            Self::Indexed { base, palette } => {
+                let count = base.components();
+                let offset = components[0] as usize * count;
+                let color = palette
+                    .get(offset..offset + count)
+                    .map(|color| color.iter().map(|&c| f32::from(c) / 255.0).collect())
+                    .unwrap_or_else(|| vec![0.0; count]);
+
+                base.rgb(&color)
+            }
+        }
+    }
+}
+
+/// Convert the decoded samples of an image into a grayscale or RGB image, following its color
+/// space and decode array
+fn samples_to_image(
+    doc: &Document,
+    dict: &Dictionary,
+    data: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<DynamicImage> {
+    let is_mask = get_bool(dict, b"ImageMask", doc).unwrap_or(false);
+    let color_space = if is_mask {
+        ColorSpace::Gray
+    } else {
+        ColorSpace::parse(
+            doc,
+            dict.get(b"ColorSpace")
+                .map_err(|_| anyhow!("Missing image color space"))?,
+        )?
+    };
+    let bits_per_component = if is_mask {
+        1
+    } else {
+        get_int(dict, b"BitsPerComponent", doc).unwrap_or(8)
+    };
+
+    if ![1, 2, 4, 8, 16].contains(&bits_per_component) {
+        return Err(anyhow!(
+            "Unsupported bits per component {bits_per_component}"
+        ));
+    }
+
+    let bits = bits_per_component as usize;
+    let components = color_space.components();
+    let max_value = ((1u32 << bits) - 1) as f32;
+    let is_indexed = matches!(color_space, ColorSpace::Indexed { .. });
+    let decode = match dict.get_deref(b"Decode", doc).and_then(Object::as_array) {
+        Ok(decode) => decode
+            .iter()
+            .map(|value| value.as_float().unwrap_or(0.0))
+            .collect(),
+        Err(_) if is_indexed => vec![0.0, max_value],
+        Err(_) => [0.0, 1.0].repeat(components),
+    };
+    let row_len = (width as usize * components * bits + 7) / 8;
+
+    if data.len() < row_len * height as usize {
+        return Err(anyhow!("Image data is shorter than expected"));
+    }
+
+    let sample = |row: &[u8], idx: usize| -> u32 {
+        match bits {
+            8 => row[idx].into(),
+            16 => u32::from(row[2 * idx]) << 8 | u32::from(row[2 * idx + 1]),
+            _ => {
+                let bit = idx * bits;
+
+                u32::from(row[bit / 8] >> (8 - bits - bit % 8)) & ((1 << bits) - 1)
+            }
+        }
+    };
+    let mut color = vec![0.0; components];
+    let mut pixel = |x: u32, y: u32| {
+        let row = &data[y as usize * row_len..][..row_len];
+
+        for (component, value) in color.iter_mut().enumerate() {
+            let (min, max) = (
+                decode.get(2 * component).copied().unwrap_or(0.0),
+                decode.get(2 * component + 1).copied().unwrap_or(1.0),
+            );
+            let normalized = sample(row, x as usize * components + component) as f32 / max_value;
+
+            *value = min + normalized * (max - min);
+
+            if is_indexed {
+                *value = value.round();
+            }
+        }
+
+        color_space
+            .rgb(&color)
+            .map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8)
+    };
+
+    Ok(if color_space.is_gray() {
+        DynamicImage::ImageLuma8(GrayImage::from_fn(width, height, |x, y| {
+            Luma([pixel(x, y)[0]])
+        }))
+    } else {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| Rgb(pixel(x, y))))
+    })
+}
@@ -0,0 +1,638 @@
+use std::{f64::consts::PI, fmt, iter};
+
+use anyhow::{anyhow, Result};
+use image::{imageops, GrayImage};
+
+use crate::ffi::{self, ZbarSymbolType};
+
+use super::{load_image::LoadedImage, region::Rect, transform::Transform, Region};
+
+/// Side of the square cells the gradients are analyzed in
+const CELL_SIZE: u32 = 16;
+
+/// Minimum mean squared gradient of a cell containing bars
+const MIN_CELL_ENERGY: f64 = 400.0;
+
+/// Minimum coherence of the gradient orientations of a cell containing bars
+const MIN_CELL_COHERENCE: f64 = 0.75;
+
+/// Largest difference of the bar orientations of neighboring cells of one region, in degrees
+const MAX_ORIENTATION_DIFFERENCE: f64 = 20.0;
+
+/// Minimum extent of a region of bars across the bars, in cells, so single edges are ignored
+const MIN_LINEAR_EXTENT: f64 = 3.0;
+
+/// Extent across the bars from which a region of bars is fully trusted, in cells
+const FULL_CONFIDENCE_EXTENT: f64 = 6.0;
+
+/// Fractional bits of the edge positions read from the zbar scanner
+const EDGE_PRECISION: i32 = 4;
+
+/// Light samples appended to every row (or column), as a quiet zone at the image border
+const BORDER_SAMPLES: u32 = 4;
+
+/// Widths of the elements of a finder pattern, in modules
+const FINDER_RATIO: [f64; 5] = [1.0, 1.0, 3.0, 1.0, 1.0];
+
+/// Largest distance of the centers of the lines crossing one finder pattern, relative to its size
+const MAX_FINDER_OFFSET: f64 = 0.2;
+
+/// Largest number of rows (or columns) between lines crossing one finder pattern
+const MAX_FINDER_LINE_GAP: u32 = 2;
+
+/// Minimum share of a finder pattern's rows (or columns) crossing it in the 1:1:3:1:1 ratio, which
+/// ideally holds for the 3 of 7 modules of its center
+const MIN_FINDER_CROSSING: f64 = 0.2;
+
+/// Maximum share of a finder pattern's rows (or columns) crossing it in the 1:1:3:1:1 ratio
+const MAX_FINDER_CROSSING: f64 = 1.0;
+
+/// Largest ratio of the sizes of finder patterns belonging together
+const MAX_FINDER_SIZE_RATIO: f64 = 1.5;
+
+/// Largest distance of the finder patterns of one QR code, in finder sizes (a version 40 code
+/// is 177 modules wide, a finder pattern 7)
+const MAX_FINDER_DISTANCE: f64 = 25.0;
+
+/// The kind of bar code a candidate region looks like
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CandidateKind {
+    /// Parallel bars of a 1D bar code
+    Linear,
+    /// Finder patterns of a QR code
+    QrCode,
+}
+
+impl fmt::Display for CandidateKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Linear => write!(f, "linear"),
+            Self::QrCode => write!(f, "qrcode"),
+        }
+    }
+}
+
+/// A region of an image likely containing a bar code, whether or not it can be decoded
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    pub kind: CandidateKind,
+    /// Bounding box, in the coordinates of the image file's pixels
+    pub region: Region,
+    /// Likelihood of the region containing a bar code, from 0 to 1
+    pub confidence: f32,
+    /// Number of the PDF page the region was found on
+    pub page: Option<u32>,
+}
+
+/// Find the regions of the given parts of an image and of its color planes that look like bar
+/// codes, most likely first
+pub(crate) fn locate(image: &LoadedImage, rects: &[Rect]) -> Result<Vec<Candidate>> {
+    let mut found: Vec<(CandidateKind, Rect, f32)> = Vec::new();
+
+    for luma in iter::once(&image.luma).chain(&image.channels) {
+        for rect in rects {
+            let part = imageops::crop_imm(luma, rect.x, rect.y, rect.width, rect.height).to_image();
+            let offset =
+                Transform::affine(1.0, 0.0, f64::from(rect.x), 0.0, 1.0, f64::from(rect.y));
+            let transform = offset.then(&image.transform);
+
+            for (kind, rect, confidence) in locate_part(&part)? {
+                add_candidate(&mut found, (kind, rect.map(&transform), confidence));
+            }
+        }
+    }
+
+    found.sort_by(|(_, _, a), (_, _, b)| b.total_cmp(a));
+
+    Ok(found
+        .into_iter()
+        .map(|(kind, rect, confidence)| Candidate {
+            kind,
+            region: Region::new(rect.x, rect.y, rect.width, rect.height),
+            confidence,
+            page: image.page,
+        })
+        .collect())
+}
+
+/// Find the regions of a grayscale image that look like bar codes, in its own coordinates
+fn locate_part(luma: &GrayImage) -> Result<Vec<(CandidateKind, Rect, f32)>> {
+    let mut found = locate_qr_codes(luma)?;
+
+    found.append(&mut locate_linear(luma));
+
+    Ok(found)
+}
+
+/// Add a candidate unless one of the same kind was found around its center, as the same bar code
+/// usually shows in several color planes or overlapping regions of interest
+fn add_candidate(
+    found: &mut Vec<(CandidateKind, Rect, f32)>,
+    candidate: (CandidateKind, Rect, f32),
+) {
+    let (kind, rect, confidence) = candidate;
+    let center = Rect {
+        x: rect.x + rect.width / 2,
+        y: rect.y + rect.height / 2,
+        width: 1,
+        height: 1,
+    };
+    let same = found
+        .iter_mut()
+        .find(|(other_kind, other, _)| *other_kind == kind && other.intersect(&center).is_some());
+
+    match same {
+        Some(same) if same.2 < confidence => *same = candidate,
+        Some(_) => (),
+        None => found.push(candidate),
+    }
+}
+
+/// Gradient structure of a cell
+#[derive(Clone, Copy, Default)]
+struct Cell {
+    /// Sums of the products of the horizontal and vertical gradients
+    xx: f64,
+    xy: f64,
+    yy: f64,
+    pixels: u32,
+}
+
+impl Cell {
+    /// Direction across the bars, in radians, if the cell looks like it contains bars
+    fn bar_direction(&self) -> Option<f64> {
+        let energy = self.xx + self.yy;
+
+        if self.pixels == 0 || energy / f64::from(self.pixels) < MIN_CELL_ENERGY {
+            return None;
+        }
+
+        (self.coherence() >= MIN_CELL_COHERENCE)
+            .then(|| 0.5 * (2.0 * self.xy).atan2(self.xx - self.yy))
+    }
+
+    /// How much the gradients share one orientation, from 0 to 1
+    fn coherence(&self) -> f64 {
+        ((self.xx - self.yy).powi(2) + 4.0 * self.xy * self.xy).sqrt() / (self.xx + self.yy)
+    }
+}
+
+/// Find regions of parallel edges with strong contrast, as left by the bars of 1D bar codes
+fn locate_linear(luma: &GrayImage) -> Vec<(CandidateKind, Rect, f32)> {
+    bar_regions(luma)
+        .into_iter()
+        .map(|region| (CandidateKind::Linear, region.rect, region.confidence as f32))
+        .collect()
+}
+
+/// A region of parallel bars
+pub(crate) struct BarRegion {
+    /// Bounding box of the cells containing the bars
+    pub rect: Rect,
+    /// Mean direction across the bars, in radians
+    pub direction: f64,
+    pub confidence: f64,
+}
+
+/// Find regions of parallel edges with strong contrast, grouping neighboring cells with bars of
+/// about the same orientation
+pub(crate) fn bar_regions(luma: &GrayImage) -> Vec<BarRegion> {
+    let (width, height) = luma.dimensions();
+    let (cols, rows) = (
+        (width + CELL_SIZE - 1) / CELL_SIZE,
+        (height + CELL_SIZE - 1) / CELL_SIZE,
+    );
+    let mut cells = vec![Cell::default(); (cols * rows) as usize];
+    let pixel = |x: u32, y: u32| f64::from(luma.get_pixel(x, y)[0]);
+
+    for y in 1..height.saturating_sub(1) {
+        for x in 1..width.saturating_sub(1) {
+            let gx = pixel(x + 1, y) - pixel(x - 1, y);
+            let gy = pixel(x, y + 1) - pixel(x, y - 1);
+            let cell = &mut cells[((y / CELL_SIZE) * cols + x / CELL_SIZE) as usize];
+
+            cell.xx += gx * gx;
+            cell.xy += gx * gy;
+            cell.yy += gy * gy;
+            cell.pixels += 1;
+        }
+    }
+
+    let directions = cells.iter().map(Cell::bar_direction).collect::<Vec<_>>();
+    let max_difference = MAX_ORIENTATION_DIFFERENCE.to_radians();
+    let mut visited = vec![false; cells.len()];
+    let mut found = Vec::new();
+
+    for start in 0..cells.len() {
+        let Some(direction) = directions[start] else {
+            continue;
+        };
+
+        if visited[start] {
+            continue;
+        }
+
+        // Flood fill the neighboring cells with bars of about the same orientation
+        let mut component = vec![start];
+        let mut stack = vec![start];
+
+        visited[start] = true;
+
+        while let Some(idx) = stack.pop() {
+            let (col, row) = ((idx as u32 % cols) as i64, (idx as u32 / cols) as i64);
+
+            for (dc, dr) in [
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ] {
+                let (c, r) = (col + dc, row + dr);
+
+                if c < 0 || r < 0 || c >= i64::from(cols) || r >= i64::from(rows) {
+                    continue;
+                }
+
+                let neighbor = (r * i64::from(cols) + c) as usize;
+
+                if visited[neighbor] {
+                    continue;
+                }
+
+                if let Some(other) = directions[neighbor] {
+                    let difference = (other - direction).rem_euclid(PI);
+
+                    if difference.min(PI - difference) <= max_difference {
+                        visited[neighbor] = true;
+                        component.push(neighbor);
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        // Bars repeat across their direction, unlike the single edge of e.g. a box
+        let (cos, sin) = (direction.cos(), direction.sin());
+        let across = component
+            .iter()
+            .map(|&idx| f64::from(idx as u32 % cols) * cos + f64::from(idx as u32 / cols) * sin);
+        let extent =
+            across.clone().fold(f64::MIN, f64::max) - across.fold(f64::MAX, f64::min) + 1.0;
+
+        if extent < MIN_LINEAR_EXTENT {
+            continue;
+        }
+
+        let coherence = component
+            .iter()
+            .map(|&idx| cells[idx].coherence())
+            .sum::<f64>()
+            / component.len() as f64;
+        let confidence = coherence * (extent / FULL_CONFIDENCE_EXTENT).min(1.0);
+        let sum = component.iter().fold(Cell::default(), |sum, &idx| Cell {
+            xx: sum.xx + cells[idx].xx,
+            xy: sum.xy + cells[idx].xy,
+            yy: sum.yy + cells[idx].yy,
+            pixels: sum.pixels + cells[idx].pixels,
+        });
+        let (left, right) = bounds(component.iter().map(|&idx| idx as u32 % cols));
+        let (top, bottom) = bounds(component.iter().map(|&idx| idx as u32 / cols));
+        let rect = Rect {
+            x: left * CELL_SIZE,
+            y: top * CELL_SIZE,
+            width: ((right + 1) * CELL_SIZE).min(width) - left * CELL_SIZE,
+            height: ((bottom + 1) * CELL_SIZE).min(height) - top * CELL_SIZE,
+        };
+
+        found.push(BarRegion {
+            rect,
+            direction: 0.5 * (2.0 * sum.xy).atan2(sum.xx - sum.yy),
+            confidence,
+        });
+    }
+
+    found
+}
+
+fn bounds(values: impl Iterator<Item = u32> + Clone) -> (u32, u32) {
+    (values.clone().min().unwrap_or(0), values.max().unwrap_or(0))
+}
+
+/// A run of dark and light elements in the 1:1:3:1:1 ratio of a finder pattern, found along a
+/// row or column
+#[derive(Clone, Copy)]
+struct FinderLine {
+    /// Center of the run, along the line
+    center: f64,
+    /// Row or column of the line
+    line: u32,
+    /// Length of the run
+    len: f64,
+}
+
+/// A finder pattern, with its center and size in pixels
+#[derive(Clone, Copy)]
+struct Finder {
+    x: f64,
+    y: f64,
+    size: f64,
+}
+
+/// Find groups of QR code finder patterns, using the finder line detection of zbar's decoder
+fn locate_qr_codes(luma: &GrayImage) -> Result<Vec<(CandidateKind, Rect, f32)>> {
+    let mut finders = finder_patterns(&finder_lines(luma, false)?, false);
+
+    for finder in finder_patterns(&finder_lines(luma, true)?, true) {
+        // Finder patterns are usually crossed by both rows and columns
+        if !finders.iter().any(|other| {
+            (other.x - finder.x).hypot(other.y - finder.y) < other.size.max(finder.size) / 2.0
+        }) {
+            finders.push(finder);
+        }
+    }
+
+    Ok(group_finders(&finders)
+        .into_iter()
+        .filter_map(|group| qr_code_candidate(&group, luma.width(), luma.height()))
+        .collect())
+}
+
+/// Finder patterns crossed by several neighboring parallel finder lines with about the same
+/// center, unlike the lines matching the finder ratio by chance in other patterns
+fn finder_patterns(lines: &[FinderLine], vertical: bool) -> Vec<Finder> {
+    let mut clusters: Vec<Vec<FinderLine>> = Vec::new();
+
+    for line in lines {
+        let cluster = clusters.iter_mut().find(|cluster| {
+            cluster.last().map_or(false, |last| {
+                line.line - last.line <= MAX_FINDER_LINE_GAP
+                    && (line.center - last.center).abs() <= MAX_FINDER_OFFSET * last.len
+                    && line.len.max(last.len) <= MAX_FINDER_SIZE_RATIO * line.len.min(last.len)
+            })
+        });
+
+        match cluster {
+            Some(cluster) => cluster.push(*line),
+            None => clusters.push(vec![*line]),
+        }
+    }
+
+    clusters
+        .into_iter()
+        .filter_map(|cluster| {
+            let count = cluster.len() as f64;
+            let len = cluster.iter().map(|line| line.len).sum::<f64>() / count;
+            let centers = cluster.iter().map(|line| line.center);
+            let (first, last) = (cluster.first()?.line, cluster.last()?.line);
+            let drift =
+                centers.clone().fold(f64::MIN, f64::max) - centers.clone().fold(f64::MAX, f64::min);
+            let crossing = f64::from(last - first + 1);
+
+            // Bars of 1D codes can match the ratio too, but keep it much longer along the bars
+            if cluster.len() < 2
+                || crossing < MIN_FINDER_CROSSING * len
+                || crossing > MAX_FINDER_CROSSING * len
+                || drift > MAX_FINDER_OFFSET * len
+            {
+                return None;
+            }
+
+            let center = centers.sum::<f64>() / count;
+            let across = f64::from(first + last) / 2.0 + 0.5;
+
+            Some(if vertical {
+                Finder {
+                    x: across,
+                    y: center,
+                    size: len,
+                }
+            } else {
+                Finder {
+                    x: center,
+                    y: across,
+                    size: len,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Run zbar's scanner and decoder along every row (or column) of the image, collecting the
+/// finder lines reported by its QR finder detection
+fn finder_lines(luma: &GrayImage, vertical: bool) -> Result<Vec<FinderLine>> {
+    let (width, height) = luma.dimensions();
+    let (len, lines) = if vertical {
+        (height, width)
+    } else {
+        (width, height)
+    };
+    let mut found = Vec::new();
+
+    unsafe {
+        let decoder = ffi::zbar_decoder_create();
+        let scanner = ffi::zbar_scanner_create(decoder);
+
+        if decoder.is_null() || scanner.is_null() {
+            ffi::zbar_scanner_destroy(scanner);
+            ffi::zbar_decoder_destroy(decoder);
+
+            return Err(anyhow!("Failed to create a zbar scanner"));
+        }
+
+        let qr_code = ZbarSymbolType::ZbarQrcode as libc::c_int;
+        let scale = f64::from(1 << EDGE_PRECISION);
+
+        for line in 0..lines {
+            let mut edges: Vec<libc::c_uint> = Vec::new();
+            let mut last_edge = None;
+            let mut handle = |result: libc::c_int, scanner: *mut libc::c_void| {
+                let edge = ffi::zbar_scanner_get_edge(scanner, 0, EDGE_PRECISION);
+
+                if last_edge != Some(edge) {
+                    last_edge = Some(edge);
+                    edges.push(edge);
+                }
+
+                if result == qr_code {
+                    let positions = edges
+                        .iter()
+                        .map(|&edge| f64::from(edge) / scale)
+                        .collect::<Vec<_>>();
+
+                    found.extend(finder_line(&positions, line));
+                }
+            };
+
+            ffi::zbar_scanner_new_scan(scanner);
+
+            for pos in 0..len {
+                let (x, y) = if vertical { (line, pos) } else { (pos, line) };
+                let result = ffi::zbar_scan_y(scanner, luma.get_pixel(x, y)[0].into());
+
+                handle(result, scanner);
+            }
+
+            // Codes touching the border lack the light element the decoder waits for
+            for _ in 0..BORDER_SAMPLES {
+                let result = ffi::zbar_scan_y(scanner, u8::MAX.into());
+
+                handle(result, scanner);
+            }
+
+            for _ in 0..2 {
+                let result = ffi::zbar_scanner_flush(scanner);
+
+                handle(result, scanner);
+            }
+        }
+
+        ffi::zbar_scanner_destroy(scanner);
+        ffi::zbar_decoder_destroy(decoder);
+    }
+
+    Ok(found)
+}
+
+/// The finder line ending at the latest edges when the decoder reported one
+///
+/// The decoder usually reports a finder pattern after the light element following it, but
+/// right at its end when that element is cut off at the border, so both are tried
+fn finder_line(edges: &[f64], line: u32) -> Option<FinderLine> {
+    (0..2)
+        .filter_map(|end| {
+            let edge = |back: usize| {
+                edges
+                    .len()
+                    .checked_sub(1 + end + back)
+                    .map(|idx| edges[idx])
+            };
+            let bounds = (0..=5).map(edge).collect::<Option<Vec<_>>>()?;
+            let len = bounds[0] - bounds[5];
+            let error = FINDER_RATIO
+                .iter()
+                .enumerate()
+                .map(|(idx, ratio)| ((bounds[4 - idx] - bounds[5 - idx]) * 7.0 / len - ratio).abs())
+                .sum::<f64>();
+            let finder_line = FinderLine {
+                center: (bounds[2] + bounds[3]) / 2.0 + 0.5,
+                line,
+                len,
+            };
+
+            (len > 0.0).then_some((finder_line, error))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(finder_line, _)| finder_line)
+}
+
+/// Group finder patterns of about the same size close enough to belong to one QR code
+fn group_finders(finders: &[Finder]) -> Vec<Vec<Finder>> {
+    let mut by_size = (0..finders.len()).collect::<Vec<_>>();
+    let mut group_of = (0..finders.len()).collect::<Vec<_>>();
+
+    by_size.sort_by(|&a, &b| finders[a].size.total_cmp(&finders[b].size));
+
+    // Sorted by size, only the following finder patterns up to the size ratio can belong together
+    for (idx, &a) in by_size.iter().enumerate() {
+        let first = &finders[a];
+        let similar = by_size[idx + 1..]
+            .iter()
+            .take_while(|&&b| finders[b].size <= MAX_FINDER_SIZE_RATIO * first.size);
+
+        for &b in similar {
+            let second = &finders[b];
+
+            if (first.x - second.x).hypot(first.y - second.y) <= MAX_FINDER_DISTANCE * first.size {
+                let (root_a, root_b) = (group_root(&mut group_of, a), group_root(&mut group_of, b));
+
+                group_of[root_a.max(root_b)] = root_a.min(root_b);
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<Finder>> = Vec::new();
+    let mut group_idx: Vec<Option<usize>> = vec![None; finders.len()];
+
+    for (idx, finder) in finders.iter().enumerate() {
+        let root = group_root(&mut group_of, idx);
+
+        match group_idx[root] {
+            Some(group) => groups[group].push(*finder),
+            None => {
+                group_idx[root] = Some(groups.len());
+                groups.push(vec![*finder]);
+            }
+        }
+    }
+
+    groups
+}
+
+/// First finder pattern of the group of a finder pattern, halving the path to it on the way
+fn group_root(group_of: &mut [usize], mut idx: usize) -> usize {
+    while group_of[idx] != idx {
+        group_of[idx] = group_of[group_of[idx]];
+        idx = group_of[idx];
+    }
+
+    idx
+}
+
+/// Bounding box of the QR code a group of finder patterns belongs to, with a confidence growing
+/// with the number of finder patterns found and the consistency of their sizes
+fn qr_code_candidate(
+    group: &[Finder],
+    width: u32,
+    height: u32,
+) -> Option<(CandidateKind, Rect, f32)> {
+    let mut points = group
+        .iter()
+        .map(|finder| (finder.x, finder.y))
+        .collect::<Vec<_>>();
+
+    // Three finder patterns mark three corners, the fourth lies opposite the one between them
+    if let [a, b, c] = group {
+        let corners = [(a, b, c), (b, a, c), (c, a, b)];
+        let sq_dist = |p: &Finder, q: &Finder| (p.x - q.x).powi(2) + (p.y - q.y).powi(2);
+        let (corner, first, second) = corners
+            .into_iter()
+            .max_by(|(_, p, q), (_, r, s)| sq_dist(p, q).total_cmp(&sq_dist(r, s)))?;
+
+        points.push((first.x + second.x - corner.x, first.y + second.y - corner.y));
+    }
+
+    let sizes = group.iter().map(|finder| finder.size);
+    let (min_size, max_size) = (
+        sizes.clone().fold(f64::MAX, f64::min),
+        sizes.fold(f64::MIN, f64::max),
+    );
+    let margin = max_size / 2.0;
+    let left = points.iter().map(|&(x, _)| x).fold(f64::MAX, f64::min) - margin;
+    let right = points.iter().map(|&(x, _)| x).fold(f64::MIN, f64::max) + margin;
+    let top = points.iter().map(|&(_, y)| y).fold(f64::MAX, f64::min) - margin;
+    let bottom = points.iter().map(|&(_, y)| y).fold(f64::MIN, f64::max) + margin;
+    let clamp = |value: f64, extent: u32| value.round().clamp(0.0, f64::from(extent)) as u32;
+    let (left, top) = (clamp(left, width), clamp(top, height));
+    let (right, bottom) = (clamp(right, width), clamp(bottom, height));
+
+    if right <= left || bottom <= top {
+        return None;
+    }
+
+    let confidence = group.len().min(3) as f64 / 3.0 * min_size / max_size;
+
+    Some((
+        CandidateKind::QrCode,
+        Rect {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        },
+        confidence as f32,
+    ))
+}
@@ -1,8 +1,39 @@
+mod channels;
 pub mod cli_args;
+mod edge_detection;
+mod fourcc;
+mod load_image;
+mod load_pdf;
+mod locate;
 mod parse_config;
+mod preprocess;
+mod pyramid;
+mod raw_image;
+mod rectify;
+mod region;
 mod scan_image;
+mod scanner;
+mod structured_append;
+mod symbol;
+mod transform;
+mod try_harder;
 mod xml_printer;
 
-pub use parse_config::zbar_processor_parse_config as parse_config;
+pub use channels::Channels;
+pub use edge_detection::EdgeDetection;
+pub use fourcc::FourCC;
+pub use load_image::{BackgroundColor, LoadOptions};
+pub use locate::{Candidate, CandidateKind};
+pub use parse_config::{
+    zbar_processor_parse_config as parse_config,
+    zbar_processor_set_symbology_config as set_symbology_config,
+};
+pub use preprocess::{Contrast, Preprocess, Threshold};
+pub use raw_image::RawImage;
+pub use region::{Coordinate, Region};
 pub use scan_image::scan_image;
+pub use scanner::Scanner;
+pub use structured_append::{Reassembler, StructuredAppend};
+pub use symbol::Symbol;
+pub use try_harder::Strategy;
 pub use xml_printer::XmlPrinter;
@@ -1,18 +1,49 @@
+use std::ffi::CString;
+
 use anyhow::{anyhow, Result};
 
 use crate::ffi::{self, ZbarConfig, ZbarSymbolType};
 
+/// Parse and apply a config, returning the parsed symbology, config and value
 pub fn zbar_processor_parse_config(
     processor: *mut libc::c_void,
     config_string: &str,
+) -> Result<(ZbarSymbolType, ZbarConfig, libc::c_int)> {
+    let c_config_string = CString::new(config_string)?;
+    let mut sym: ZbarSymbolType = ZbarSymbolType::ZbarNone;
+    let mut cfg: ZbarConfig = ZbarConfig::Enable;
+    let mut val: libc::c_int = 0;
+
+    unsafe {
+        if ffi::zbar_parse_config(c_config_string.as_ptr(), &mut sym, &mut cfg, &mut val) != 0 {
+            return Err(anyhow!("Failed to parse the config `{config_string}`"));
+        }
+
+        if ffi::zbar_processor_set_config(processor, sym, cfg, val) != 0 {
+            return Err(anyhow!(
+                "Failed to set the config `{config_string}` for the processor"
+            ));
+        }
+    }
+
+    Ok((sym, cfg, val))
+}
+
+/// Apply a config (e.g. `test-inverted`) to a symbology (e.g. `code128`, or `*` for all)
+pub fn zbar_processor_set_symbology_config(
+    processor: *mut libc::c_void,
+    symbology: &str,
+    config: &str,
+    value: libc::c_int,
 ) -> Result<()> {
+    let config_string = format!("{symbology}.{config}={value}");
+    let c_config_string = CString::new(config_string.as_str())?;
     let mut sym: ZbarSymbolType = ZbarSymbolType::ZbarNone;
     let mut cfg: ZbarConfig = ZbarConfig::Enable;
     let mut val: libc::c_int = 0;
 
     unsafe {
-        if ffi::zbar_parse_config(config_string.as_ptr().cast(), &mut sym, &mut cfg, &mut val) != 0
-        {
+        if ffi::zbar_parse_config(c_config_string.as_ptr(), &mut sym, &mut cfg, &mut val) != 0 {
             return Err(anyhow!("Failed to parse the config `{config_string}`"));
         }
 
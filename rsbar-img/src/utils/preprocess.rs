@@ -0,0 +1,388 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, Error, Result};
+use image::{imageops, GrayImage, Luma};
+
+use super::transform::Transform;
+
+/// Share of the darkest and brightest pixels clipped by automatic contrast stretching, of
+/// preprocessed images as well as of high bit depth image files
+const CONTRAST_CLIP_RATIO: f64 = 0.005;
+
+/// Default radius of the neighborhood compared against by adaptive thresholding
+pub(crate) const ADAPTIVE_THRESHOLD_RADIUS: u32 = 15;
+
+/// How much darker than its neighborhood a pixel has to be to turn black when thresholding adaptively
+const ADAPTIVE_THRESHOLD_OFFSET: f64 = 8.0;
+
+/// Default standard deviation of the blur used for sharpening
+const SHARPEN_SIGMA: f32 = 1.0;
+
+/// Gray value of the area uncovered when rotating by arbitrary angles
+const FILL_VALUE: u8 = u8::MAX;
+
+/// A transformation applied to the grayscale image before it is handed to zbar
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Preprocess {
+    /// Rotate clockwise by the given number of degrees, enlarging the image to fit
+    Rotate(f64),
+    /// Resize by the given factor
+    Scale(f64),
+    /// Change the contrast
+    Contrast(Contrast),
+    /// Apply an unsharp mask with the given blur standard deviation
+    Sharpen(f32),
+    /// Turn the image into black and white
+    Threshold(Threshold),
+    /// Swap dark and light
+    Invert,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Contrast {
+    /// Stretch the range between the darkest and brightest pixels to the full range
+    Auto,
+    /// Multiply the distance to mid gray by the given factor
+    Factor(f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Threshold {
+    /// Global threshold chosen by Otsu's method
+    Otsu,
+    /// Compare every pixel to the mean of the surrounding square with the given radius
+    Adaptive(u32),
+    /// Global threshold, pixels below it turn black
+    Fixed(u8),
+}
+
+impl Preprocess {
+    /// Apply the transformation, returning the mapping of the new image's points back to the
+    /// given one
+    pub(crate) fn apply(&self, image: &GrayImage) -> (GrayImage, Transform) {
+        match *self {
+            Self::Rotate(degrees) => rotate(image, degrees),
+            Self::Scale(factor) => scale(image, factor),
+            Self::Contrast(contrast) => (contrast.apply(image), Transform::IDENTITY),
+            Self::Sharpen(sigma) => (imageops::unsharpen(image, sigma, 0), Transform::IDENTITY),
+            Self::Threshold(threshold) => (threshold.apply(image), Transform::IDENTITY),
+            Self::Invert => {
+                let mut inverted = image.clone();
+
+                imageops::invert(&mut inverted);
+
+                (inverted, Transform::IDENTITY)
+            }
+        }
+    }
+}
+
+/// Apply all steps in order, returning the mapping of the final image's points back to the
+/// given one
+pub(crate) fn preprocess(image: GrayImage, steps: &[Preprocess]) -> (GrayImage, Transform) {
+    steps
+        .iter()
+        .fold((image, Transform::IDENTITY), |(image, transform), step| {
+            let (image, step_transform) = step.apply(&image);
+
+            (image, step_transform.then(&transform))
+        })
+}
+
+fn rotate(image: &GrayImage, degrees: f64) -> (GrayImage, Transform) {
+    let degrees = degrees.rem_euclid(360.0);
+    let (w, h) = (f64::from(image.width()), f64::from(image.height()));
+
+    if degrees == 0.0 {
+        return (image.clone(), Transform::IDENTITY);
+    } else if degrees == 90.0 {
+        return (
+            imageops::rotate90(image),
+            Transform::affine(0.0, 1.0, 0.0, -1.0, 0.0, h),
+        );
+    } else if degrees == 180.0 {
+        return (
+            imageops::rotate180(image),
+            Transform::affine(-1.0, 0.0, w, 0.0, -1.0, h),
+        );
+    } else if degrees == 270.0 {
+        return (
+            imageops::rotate270(image),
+            Transform::affine(0.0, -1.0, w, 1.0, 0.0, 0.0),
+        );
+    }
+
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let rotated_width = (w * cos.abs() + h * sin.abs()).ceil();
+    let rotated_height = (w * sin.abs() + h * cos.abs()).ceil();
+    let (cx, cy) = ((w - 1.0) / 2.0, (h - 1.0) / 2.0);
+    let (rx, ry) = ((rotated_width - 1.0) / 2.0, (rotated_height - 1.0) / 2.0);
+    let transform = Transform::affine(
+        cos,
+        sin,
+        cx - cos * rx - sin * ry,
+        -sin,
+        cos,
+        cy + sin * rx - cos * ry,
+    );
+    let rotated = GrayImage::from_fn(rotated_width as u32, rotated_height as u32, |x, y| {
+        let (sx, sy) = transform.apply_f64(f64::from(x), f64::from(y));
+
+        Luma([sample_bilinear(image, sx, sy)])
+    });
+
+    (rotated, transform)
+}
+
+pub(crate) fn sample_bilinear(image: &GrayImage, x: f64, y: f64) -> u8 {
+    let (w, h) = (f64::from(image.width()), f64::from(image.height()));
+
+    if x < 0.0 || y < 0.0 || x > w - 1.0 || y > h - 1.0 {
+        return FILL_VALUE;
+    }
+
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let pixel = |x: f64, y: f64| {
+        f64::from(image.get_pixel(x.min(w - 1.0) as u32, y.min(h - 1.0) as u32)[0])
+    };
+    let top = pixel(x0, y0) * (1.0 - fx) + pixel(x0 + 1.0, y0) * fx;
+    let bottom = pixel(x0, y0 + 1.0) * (1.0 - fx) + pixel(x0 + 1.0, y0 + 1.0) * fx;
+
+    (top * (1.0 - fy) + bottom * fy).round() as u8
+}
+
+fn scale(image: &GrayImage, factor: f64) -> (GrayImage, Transform) {
+    let scaled_size = |size: u32| ((f64::from(size) * factor).round() as u32).max(1);
+    let (width, height) = (scaled_size(image.width()), scaled_size(image.height()));
+    let fx = f64::from(image.width()) / f64::from(width);
+    let fy = f64::from(image.height()) / f64::from(height);
+
+    (
+        imageops::resize(image, width, height, imageops::FilterType::Triangle),
+        Transform::affine(fx, 0.0, fx / 2.0 - 0.5, 0.0, fy, fy / 2.0 - 0.5),
+    )
+}
+
+fn histogram(image: &GrayImage) -> [u64; 256] {
+    let mut histogram = [0u64; 256];
+
+    image
+        .pixels()
+        .for_each(|pixel| histogram[usize::from(pixel[0])] += 1);
+
+    histogram
+}
+
+/// Values of the darkest and brightest percentiles of a histogram, clipping the share
+/// `CONTRAST_CLIP_RATIO` of the values at both ends (the full range if that leaves nothing)
+pub(crate) fn contrast_range(histogram: &[u64]) -> (f64, f64) {
+    let clip_count = (histogram.iter().sum::<u64>() as f64 * CONTRAST_CLIP_RATIO) as u64;
+    let percentile = |values: &mut dyn Iterator<Item = usize>| {
+        let mut count = 0;
+
+        for value in values {
+            count += histogram[value];
+
+            if count > clip_count {
+                return value as f64;
+            }
+        }
+
+        0.0
+    };
+    let low = percentile(&mut (0..histogram.len()));
+    let high = percentile(&mut (0..histogram.len()).rev());
+
+    if high > low {
+        (low, high)
+    } else {
+        (0.0, (histogram.len() - 1) as f64)
+    }
+}
+
+/// Map a value of the range `low..=high` linearly onto the full 8-bit range
+pub(crate) fn stretch_contrast(value: f64, (low, high): (f64, f64)) -> u8 {
+    ((value - low) / (high - low) * 255.0)
+        .clamp(0.0, 255.0)
+        .round() as u8
+}
+
+impl Contrast {
+    fn apply(&self, image: &GrayImage) -> GrayImage {
+        let range = match *self {
+            Self::Auto => contrast_range(&histogram(image)),
+            Self::Factor(factor) => (127.5 - 127.5 / factor, 127.5 + 127.5 / factor),
+        };
+
+        GrayImage::from_fn(image.width(), image.height(), |x, y| {
+            Luma([stretch_contrast(f64::from(image.get_pixel(x, y)[0]), range)])
+        })
+    }
+}
+
+impl Threshold {
+    fn apply(&self, image: &GrayImage) -> GrayImage {
+        let binarize = |is_light: bool| Luma([if is_light { u8::MAX } else { 0 }]);
+
+        match *self {
+            Self::Otsu => {
+                let threshold = otsu_threshold(image);
+
+                GrayImage::from_fn(image.width(), image.height(), |x, y| {
+                    binarize(image.get_pixel(x, y)[0] > threshold)
+                })
+            }
+            Self::Fixed(threshold) => GrayImage::from_fn(image.width(), image.height(), |x, y| {
+                binarize(image.get_pixel(x, y)[0] >= threshold)
+            }),
+            Self::Adaptive(radius) => {
+                let integral = integral_image(image);
+                let (w, h) = (image.width() as usize, image.height() as usize);
+
+                GrayImage::from_fn(image.width(), image.height(), |x, y| {
+                    let (x, y) = (x as usize, y as usize);
+                    let radius = radius as usize;
+                    let (left, top) = (x.saturating_sub(radius), y.saturating_sub(radius));
+                    let (right, bottom) = ((x + radius + 1).min(w), (y + radius + 1).min(h));
+                    let sum = integral[bottom * (w + 1) + right] + integral[top * (w + 1) + left]
+                        - integral[top * (w + 1) + right]
+                        - integral[bottom * (w + 1) + left];
+                    let mean = sum as f64 / ((right - left) * (bottom - top)) as f64;
+
+                    binarize(
+                        f64::from(image.get_pixel(x as u32, y as u32)[0])
+                            > mean - ADAPTIVE_THRESHOLD_OFFSET,
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// Threshold maximizing the variance between the dark and light pixels
+fn otsu_threshold(image: &GrayImage) -> u8 {
+    let histogram = histogram(image);
+    let total = image.len() as f64;
+    let total_sum: f64 = (0..256)
+        .map(|value| value as f64 * histogram[value] as f64)
+        .sum();
+    let (mut dark_count, mut dark_sum) = (0.0, 0.0);
+    let (mut best_threshold, mut best_variance) = (0, 0.0);
+
+    for (value, &count) in histogram.iter().enumerate() {
+        dark_count += count as f64;
+        dark_sum += value as f64 * count as f64;
+
+        let light_count = total - dark_count;
+
+        if dark_count == 0.0 || light_count == 0.0 {
+            continue;
+        }
+
+        let dark_mean = dark_sum / dark_count;
+        let light_mean = (total_sum - dark_sum) / light_count;
+        let variance = dark_count * light_count * (dark_mean - light_mean).powi(2);
+
+        if variance > best_variance {
+            best_variance = variance;
+            best_threshold = value as u8;
+        }
+    }
+
+    best_threshold
+}
+
+/// Sums of all pixels above and left of every position, with an extra leading row and column
+fn integral_image(image: &GrayImage) -> Vec<u64> {
+    let (w, h) = (image.width() as usize, image.height() as usize);
+    let mut integral = vec![0u64; (w + 1) * (h + 1)];
+
+    for y in 0..h {
+        let mut row_sum = 0;
+
+        for x in 0..w {
+            row_sum += u64::from(image.get_pixel(x as u32, y as u32)[0]);
+            integral[(y + 1) * (w + 1) + x + 1] = integral[y * (w + 1) + x + 1] + row_sum;
+        }
+    }
+
+    integral
+}
+
+impl fmt::Display for Preprocess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rotate(degrees) => write!(f, "rotate:{degrees}"),
+            Self::Scale(factor) => write!(f, "scale:{factor}"),
+            Self::Contrast(Contrast::Auto) => write!(f, "contrast:auto"),
+            Self::Contrast(Contrast::Factor(factor)) => write!(f, "contrast:{factor}"),
+            Self::Sharpen(sigma) => write!(f, "sharpen:{sigma}"),
+            Self::Threshold(Threshold::Otsu) => write!(f, "threshold:otsu"),
+            Self::Threshold(Threshold::Adaptive(radius)) => {
+                write!(f, "threshold:adaptive:{radius}")
+            }
+            Self::Threshold(Threshold::Fixed(threshold)) => write!(f, "threshold:{threshold}"),
+            Self::Invert => write!(f, "invert"),
+        }
+    }
+}
+
+impl FromStr for Preprocess {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, argument) = match s.trim().split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (s.trim(), None),
+        };
+        let number = |argument: Option<&str>| -> Result<f64> {
+            argument
+                .and_then(|argument| argument.parse::<f64>().ok())
+                .filter(|number| number.is_finite())
+                .ok_or_else(|| anyhow!("Expected a number for `{name}`, got `{s}`"))
+        };
+
+        match (name, argument) {
+            ("rotate", argument) => Ok(Self::Rotate(number(argument)?)),
+            ("scale", argument) => match number(argument)? {
+                factor if factor > 0.0 => Ok(Self::Scale(factor)),
+                _ => Err(anyhow!("Scale factor must be positive, got `{s}`")),
+            },
+            ("contrast", Some("auto") | None) => Ok(Self::Contrast(Contrast::Auto)),
+            ("contrast", argument) => match number(argument)? {
+                factor if factor > 0.0 => Ok(Self::Contrast(Contrast::Factor(factor))),
+                _ => Err(anyhow!("Contrast factor must be positive, got `{s}`")),
+            },
+            ("sharpen", None) => Ok(Self::Sharpen(SHARPEN_SIGMA)),
+            ("sharpen", argument) => match number(argument)? {
+                sigma if sigma > 0.0 => Ok(Self::Sharpen(sigma as f32)),
+                _ => Err(anyhow!("Sharpening sigma must be positive, got `{s}`")),
+            },
+            ("threshold", Some("otsu") | None) => Ok(Self::Threshold(Threshold::Otsu)),
+            ("threshold", Some("adaptive")) => Ok(Self::Threshold(Threshold::Adaptive(
+                ADAPTIVE_THRESHOLD_RADIUS,
+            ))),
+            ("threshold", Some(argument)) => {
+                if let Some(radius) = argument.strip_prefix("adaptive:") {
+                    return radius
+                        .parse()
+                        .map(|radius| Self::Threshold(Threshold::Adaptive(radius)))
+                        .map_err(|_| anyhow!("Expected a radius in pixels, got `{s}`"));
+                }
+
+                argument
+                    .parse()
+                    .map(|threshold| Self::Threshold(Threshold::Fixed(threshold)))
+                    .map_err(|_| {
+                        anyhow!("Expected `otsu`, `adaptive[:RADIUS]` or 0-255, got `{s}`")
+                    })
+            }
+            ("invert", None) => Ok(Self::Invert),
+            _ => Err(anyhow!(
+                "Unknown preprocessing step `{s}`, expected one of rotate:DEGREES, scale:FACTOR, \
+                 contrast[:auto|FACTOR], sharpen[:SIGMA], threshold[:otsu|adaptive[:RADIUS]|0-255], invert"
+            )),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use image::GrayImage;
+
+use super::{preprocess::Preprocess, transform::Transform};
+
+/// Levels whose shorter side would be smaller than this are not built
+const MIN_LEVEL_SIZE: u32 = 64;
+
+/// Factor every level is downscaled by, compared to the previous one
+const LEVEL_SCALE: f64 = 0.5;
+
+/// Build up to `levels` successively downscaled copies of an image, starting with the image
+/// itself, each with the transform mapping its points to the full resolution image
+pub(crate) fn pyramid(image: GrayImage, levels: u32) -> Vec<(GrayImage, Transform)> {
+    let mut pyramid = vec![(image, Transform::IDENTITY)];
+
+    while pyramid.len() < levels as usize {
+        let (previous, previous_transform) = &pyramid[pyramid.len() - 1];
+
+        if f64::from(previous.width().min(previous.height())) * LEVEL_SCALE
+            < f64::from(MIN_LEVEL_SIZE)
+        {
+            break;
+        }
+
+        let (level, transform) = Preprocess::Scale(LEVEL_SCALE).apply(previous);
+        let transform = transform.then(previous_transform);
+
+        pyramid.push((level, transform));
+    }
+
+    pyramid
+}
@@ -0,0 +1,173 @@
+use anyhow::{anyhow, Result};
+use image::GrayImage;
+
+use crate::ffi;
+
+use super::FourCC;
+
+/// An uncompressed pixel buffer in one of the formats understood by zbar
+#[derive(Clone, Debug)]
+pub struct RawImage<'a> {
+    data: &'a [u8],
+    width: u32,
+    height: u32,
+    stride: usize,
+    format: FourCC,
+}
+
+impl<'a> RawImage<'a> {
+    /// Wrap a pixel buffer whose (luma) rows are `stride` bytes apart
+    pub fn new(
+        data: &'a [u8],
+        width: u32,
+        height: u32,
+        stride: usize,
+        format: FourCC,
+    ) -> Result<Self> {
+        let image = Self {
+            data,
+            width,
+            height,
+            stride,
+            format,
+        };
+        let required_len = image.required_len()?;
+
+        if data.len() < required_len {
+            return Err(anyhow!(
+                "Buffer of {} bytes is too small for a {width}x{height} {format} image, expected {required_len} bytes",
+                data.len()
+            ));
+        }
+
+        Ok(image)
+    }
+
+    /// Wrap a pixel buffer without any padding between rows
+    pub fn packed(data: &'a [u8], width: u32, height: u32, format: FourCC) -> Result<Self> {
+        let stride = match format.planes(width, 1, usize::MAX)?.first() {
+            Some(plane) => plane.row_bytes,
+            None => 0,
+        };
+
+        Self::new(data, width, height, stride, format)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn format(&self) -> FourCC {
+        self.format
+    }
+
+    fn required_len(&self) -> Result<usize> {
+        self.format
+            .planes(self.width, self.height, self.stride)?
+            .iter()
+            .try_fold(0usize, |len, plane| {
+                plane
+                    .stride
+                    .checked_mul(plane.rows)
+                    .and_then(|plane_len| len.checked_add(plane_len))
+            })
+            .ok_or_else(|| anyhow!("Image size {}x{} is too large", self.width, self.height))
+    }
+
+    /// Copy the planes into a buffer without row padding, which is the layout zbar expects
+    fn packed_bytes(&self) -> Result<Vec<u8>> {
+        let planes = self.format.planes(self.width, self.height, self.stride)?;
+        let packed_len = planes
+            .iter()
+            .map(|plane| plane.row_bytes * plane.rows)
+            .sum();
+        let mut bytes = Vec::with_capacity(packed_len);
+        let mut offset = 0;
+
+        for plane in planes {
+            for row in 0..plane.rows {
+                let start = offset + row * plane.stride;
+
+                bytes.extend_from_slice(&self.data[start..start + plane.row_bytes]);
+            }
+
+            offset += plane.stride * plane.rows;
+        }
+
+        Ok(bytes)
+    }
+
+    pub(crate) fn to_zbar_image(&self) -> Result<*mut libc::c_void> {
+        into_zbar_image(self.packed_bytes()?, self.width, self.height, self.format)
+    }
+
+    /// Convert to a grayscale image, using the format conversion of zbar
+    pub(crate) fn to_luma(&self) -> Result<GrayImage> {
+        let zimage = self.to_zbar_image()?;
+
+        unsafe {
+            let converted = ffi::zbar_image_convert(zimage, FourCC::Y800.as_zbar_format());
+
+            ffi::zbar_image_destroy(zimage);
+
+            if converted.is_null() {
+                return Err(anyhow!(
+                    "Failed to convert {} image to grayscale",
+                    self.format
+                ));
+            }
+
+            let luma = std::slice::from_raw_parts(
+                ffi::zbar_image_get_data(converted).cast::<u8>(),
+                ffi::zbar_image_get_data_length(converted) as usize,
+            )
+            .to_vec();
+
+            ffi::zbar_image_destroy(converted);
+
+            GrayImage::from_raw(self.width, self.height, luma)
+                .ok_or_else(|| anyhow!("Failed to convert {} image to grayscale", self.format))
+        }
+    }
+}
+
+/// Hand a buffer without row padding over to a new zbar image, which releases it when destroyed
+pub(crate) fn into_zbar_image(
+    bytes: Vec<u8>,
+    width: u32,
+    height: u32,
+    format: FourCC,
+) -> Result<*mut libc::c_void> {
+    RawImage::packed(&bytes, width, height, format)?;
+
+    let len = bytes.len();
+    let bytes = Box::into_raw(bytes.into_boxed_slice());
+
+    unsafe {
+        let zimage = ffi::zbar_image_create();
+        assert!(!zimage.is_null());
+
+        ffi::zbar_image_set_format(zimage, format.as_zbar_format());
+        ffi::zbar_image_set_size(zimage, width, height);
+        ffi::zbar_image_set_data(
+            zimage,
+            bytes.cast::<libc::c_void>(),
+            len as libc::c_ulong,
+            free_boxed_data,
+        );
+
+        Ok(zimage)
+    }
+}
+
+/// Cleanup handler releasing the buffer handed over by `into_zbar_image`
+unsafe extern "C" fn free_boxed_data(zimage: *mut libc::c_void) {
+    let data = ffi::zbar_image_get_data(zimage) as *mut u8;
+    let len = ffi::zbar_image_get_data_length(zimage) as usize;
+
+    drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(data, len)));
+}
@@ -0,0 +1,294 @@
+use image::{GrayImage, Luma};
+
+use super::{
+    locate::{bar_regions, BarRegion},
+    preprocess::sample_bilinear,
+    region::Rect,
+    transform::Transform,
+};
+
+/// Smallest difference of the samples on both sides of a bar edge
+const MIN_EDGE_CONTRAST: f64 = 48.0;
+
+/// Minimum number of bar edges on the line through the middle of a bar code
+const MIN_EDGES: usize = 12;
+
+/// Largest gap between the edges of one bar code, relative to the median gap
+const MAX_GAP_RATIO: f64 = 4.0;
+
+/// Largest distance of the points a boundary line is fitted to, in pixels
+const MAX_LINE_RESIDUAL: f64 = 2.0;
+
+/// Rounds of dropping the points farthest from a fitted boundary line
+const LINE_FIT_ROUNDS: usize = 4;
+
+/// Light margin added around the rectified bar code, relative to its size
+const QUIET_ZONE: f64 = 0.15;
+
+/// Warp the regions of parallel bars to fronto-parallel views with vertical bars, each with the
+/// transform mapping its points back to the image
+///
+/// Only regions overlapping one of `rects` are considered.
+pub(crate) fn rectify(luma: &GrayImage, rects: &[Rect]) -> Vec<(GrayImage, Transform)> {
+    bar_regions(luma)
+        .iter()
+        .filter(|region| {
+            rects
+                .iter()
+                .any(|rect| rect.intersect(&region.rect).is_some())
+        })
+        .filter_map(|region| Frame::new(luma, region).quad())
+        .map(|quad| warp(luma, quad))
+        .collect()
+}
+
+/// Coordinates centered on a region of bars, with `u` running across and `v` along the bars
+struct Frame<'a> {
+    luma: &'a GrayImage,
+    center: (f64, f64),
+    across: (f64, f64),
+    /// Largest distance from the center that is searched
+    radius: i32,
+    /// Half the extent of the region along the bars
+    half_length: i32,
+}
+
+/// Bar edge found on a line across the bars, with the sign of its gradient
+#[derive(Clone, Copy)]
+struct Edge {
+    u: i32,
+    rising: bool,
+}
+
+impl<'a> Frame<'a> {
+    fn new(luma: &'a GrayImage, region: &BarRegion) -> Self {
+        let rect = &region.rect;
+        let (width, height) = (f64::from(rect.width), f64::from(rect.height));
+        let (sin, cos) = region.direction.sin_cos();
+
+        Self {
+            luma,
+            center: (
+                f64::from(rect.x) + width / 2.0,
+                f64::from(rect.y) + height / 2.0,
+            ),
+            across: (cos, sin),
+            radius: (width.hypot(height) / 2.0).ceil() as i32 + 16,
+            half_length: ((width * sin.abs() + height * cos.abs()) / 2.0).ceil() as i32,
+        }
+    }
+
+    fn point(&self, u: f64, v: f64) -> (f64, f64) {
+        let ((cx, cy), (ax, ay)) = (self.center, self.across);
+
+        (cx + u * ax - v * ay, cy + u * ay + v * ax)
+    }
+
+    fn sample(&self, u: i32, v: i32) -> f64 {
+        let (x, y) = self.point(f64::from(u), f64::from(v));
+
+        f64::from(sample_bilinear(self.luma, x, y))
+    }
+
+    /// Difference of the samples after and before `(u, v)`, across the bars
+    fn gradient(&self, u: i32, v: i32) -> f64 {
+        self.sample(u + 1, v) - self.sample(u - 1, v)
+    }
+
+    /// Edges along the line `v`, as the local maxima of the gradient's magnitude
+    fn edges(&self, v: i32) -> Vec<Edge> {
+        let gradients = (-self.radius..=self.radius)
+            .map(|u| self.gradient(u, v).abs())
+            .collect::<Vec<_>>();
+
+        (1..gradients.len().saturating_sub(1))
+            .filter(|&idx| {
+                gradients[idx] >= MIN_EDGE_CONTRAST
+                    && gradients[idx] >= gradients[idx - 1]
+                    && gradients[idx] > gradients[idx + 1]
+            })
+            .map(|idx| {
+                let u = idx as i32 - self.radius;
+
+                Edge {
+                    u,
+                    rising: self.gradient(u, v) > 0.0,
+                }
+            })
+            .collect()
+    }
+
+    /// The longest run of edges along the line `v` without a gap looking like a quiet zone
+    fn bar_run(&self, v: i32) -> Vec<Edge> {
+        let edges = self.edges(v);
+        let mut gaps = edges
+            .windows(2)
+            .map(|pair| pair[1].u - pair[0].u)
+            .collect::<Vec<_>>();
+
+        if gaps.is_empty() {
+            return edges;
+        }
+
+        gaps.sort_unstable();
+
+        let max_gap = f64::from(gaps[gaps.len() / 2]) * MAX_GAP_RATIO;
+        let mut longest = 0..0;
+        let mut start = 0;
+
+        // Ties keep the last run
+        for end in 1..=edges.len() {
+            if end == edges.len() || f64::from(edges[end].u - edges[end - 1].u) > max_gap {
+                if end - start >= longest.len() {
+                    longest = start..end;
+                }
+
+                start = end;
+            }
+        }
+
+        edges[longest].to_vec()
+    }
+
+    /// Follow a bar edge along the bars from `(u, v)` in the direction `step`, returning the
+    /// points it was found at
+    fn trace(&self, edge: Edge, v: i32, step: i32) -> Vec<(f64, f64)> {
+        let sign = if edge.rising { 1.0 } else { -1.0 };
+        let mut u = edge.u;
+        let mut points = Vec::new();
+        let mut v = v + step;
+
+        while v.abs() <= self.radius {
+            let (best, gradient) = (u - 1..=u + 1)
+                .map(|u| (u, sign * self.gradient(u, v)))
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .unwrap_or((u, 0.0));
+
+            if gradient < MIN_EDGE_CONTRAST {
+                break;
+            }
+
+            u = best;
+            points.push((f64::from(u), f64::from(v)));
+            v += step;
+        }
+
+        points
+    }
+
+    /// Corners of the bar code (top left, top right, bottom right and bottom left, with the
+    /// bars running from top to bottom), in image coordinates
+    fn quad(&self) -> Option<[(f64, f64); 4]> {
+        let (middle, run) = (-self.half_length..=self.half_length)
+            .map(|v| (v, self.bar_run(v)))
+            .max_by_key(|(_, run)| run.len())?;
+
+        if run.len() < MIN_EDGES {
+            return None;
+        }
+
+        let mut tops = Vec::new();
+        let mut bottoms = Vec::new();
+        let mut sides = [Vec::new(), Vec::new()];
+
+        for (idx, &edge) in run.iter().enumerate() {
+            let start = (f64::from(edge.u), f64::from(middle));
+            let up = self.trace(edge, middle, -1);
+            let down = self.trace(edge, middle, 1);
+
+            tops.push(*up.last().unwrap_or(&start));
+            bottoms.push(*down.last().unwrap_or(&start));
+
+            if idx == 0 || idx == run.len() - 1 {
+                // Lines along the bars, fitted with `v` as the free variable
+                let side = &mut sides[usize::from(idx != 0)];
+
+                side.extend(up.iter().chain(&down).chain([&start]).map(|&(u, v)| (v, u)));
+            }
+        }
+
+        let top = fit_line(&tops)?;
+        let bottom = fit_line(&bottoms)?;
+        let [left, right] = [fit_line(&sides[0])?, fit_line(&sides[1])?];
+        let corner = |(a, b): (f64, f64), (c, d): (f64, f64)| {
+            // Intersect v = a + b * u with u = c + d * v
+            let u = (c + d * a) / (1.0 - b * d);
+
+            self.point(u, a + b * u)
+        };
+
+        Some([
+            corner(top, left),
+            corner(top, right),
+            corner(bottom, right),
+            corner(bottom, left),
+        ])
+    }
+}
+
+/// Fit `y = a + b * x` to the points by least squares, ignoring the points far from the line
+fn fit_line(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let mut kept = points.to_vec();
+    let mut line = None;
+
+    for _ in 0..LINE_FIT_ROUNDS {
+        let n = kept.len() as f64;
+
+        if n < 2.0 {
+            break;
+        }
+
+        let (sx, sy) = kept
+            .iter()
+            .fold((0.0, 0.0), |(sx, sy), &(x, y)| (sx + x, sy + y));
+        let (mx, my) = (sx / n, sy / n);
+        let sxx = kept.iter().map(|&(x, _)| (x - mx).powi(2)).sum::<f64>();
+        let sxy = kept.iter().map(|&(x, y)| (x - mx) * (y - my)).sum::<f64>();
+        let b = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+        let a = my - b * mx;
+        let residual = |&(x, y): &(f64, f64)| (y - a - b * x).abs();
+        let mut residuals = kept.iter().map(residual).collect::<Vec<_>>();
+
+        residuals.sort_by(f64::total_cmp);
+        line = Some((a, b));
+
+        let max_residual = MAX_LINE_RESIDUAL.max(2.0 * residuals[residuals.len() / 2]);
+
+        kept.retain(|point| residual(point) <= max_residual);
+    }
+
+    line
+}
+
+/// Sample the quadrilateral (plus a quiet zone) into a rectangular image
+fn warp(luma: &GrayImage, quad: [(f64, f64); 4]) -> (GrayImage, Transform) {
+    let distance = |(x0, y0): (f64, f64), (x1, y1): (f64, f64)| (x1 - x0).hypot(y1 - y0);
+    let [top_left, top_right, bottom_right, bottom_left] = quad;
+    let width = distance(top_left, top_right)
+        .max(distance(bottom_left, bottom_right))
+        .max(1.0);
+    let height = distance(top_left, bottom_left)
+        .max(distance(top_right, bottom_right))
+        .max(1.0);
+    let (margin_x, margin_y) = (width * QUIET_ZONE, height * QUIET_ZONE);
+    let transform = Transform::affine(
+        1.0 / width,
+        0.0,
+        -margin_x / width,
+        0.0,
+        1.0 / height,
+        -margin_y / height,
+    )
+    .then(&Transform::square_to_quad(quad));
+    let warped = GrayImage::from_fn(
+        (width + 2.0 * margin_x).ceil() as u32,
+        (height + 2.0 * margin_y).ceil() as u32,
+        |x, y| {
+            let (sx, sy) = transform.apply_f64(f64::from(x), f64::from(y));
+
+            Luma([sample_bilinear(luma, sx, sy)])
+        },
+    );
+
+    (warped, transform)
+}
@@ -0,0 +1,183 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, Error, Result};
+
+use super::transform::Transform;
+
+/// A position or extent along one axis of an image
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Coordinate {
+    /// Absolute number of pixels
+    Pixels(u32),
+    /// Percentage of the image's width or height
+    Percent(f64),
+}
+
+impl Coordinate {
+    fn resolve(self, extent: u32) -> u32 {
+        match self {
+            Self::Pixels(pixels) => pixels,
+            Self::Percent(percent) => (f64::from(extent) * percent / 100.0)
+                .round()
+                .clamp(0.0, f64::from(u32::MAX)) as u32,
+        }
+    }
+}
+
+impl fmt::Display for Coordinate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pixels(pixels) => write!(f, "{pixels}"),
+            Self::Percent(percent) => write!(f, "{percent}%"),
+        }
+    }
+}
+
+impl FromStr for Coordinate {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+
+        match s.strip_suffix('%') {
+            Some(percent) => match percent.trim().parse::<f64>() {
+                Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(Self::Percent(percent)),
+                _ => Err(anyhow!(
+                    "Expected a percentage between 0% and 100%, got `{s}`"
+                )),
+            },
+            None => s
+                .parse()
+                .map(Self::Pixels)
+                .map_err(|_| anyhow!("Expected a number of pixels or a percentage, got `{s}`")),
+        }
+    }
+}
+
+/// A rectangular part of an image, in the coordinates of the image's pixels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Region {
+    pub x: Coordinate,
+    pub y: Coordinate,
+    pub width: Coordinate,
+    pub height: Coordinate,
+}
+
+impl Region {
+    /// Create a region from absolute pixel values
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x: Coordinate::Pixels(x),
+            y: Coordinate::Pixels(y),
+            width: Coordinate::Pixels(width),
+            height: Coordinate::Pixels(height),
+        }
+    }
+
+    /// Compute the pixels covered by the region in an image of the given size,
+    /// or `None` if the region lies outside of it
+    pub(crate) fn resolve(&self, width: u32, height: u32) -> Option<Rect> {
+        Rect {
+            x: self.x.resolve(width),
+            y: self.y.resolve(height),
+            width: self.width.resolve(width),
+            height: self.height.resolve(height),
+        }
+        .intersect(&Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        })
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.x, self.y, self.width, self.height)
+    }
+}
+
+impl FromStr for Region {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let coordinates = s
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<Coordinate>>>()?;
+
+        match coordinates[..] {
+            [x, y, width, height] => Ok(Self {
+                x,
+                y,
+                width,
+                height,
+            }),
+            _ => Err(anyhow!(
+                "Expected a region like `x,y,width,height` (e.g. `10,10,50%,50%`), got `{s}`"
+            )),
+        }
+    }
+}
+
+/// A non-empty rectangle of pixels
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self
+            .x
+            .saturating_add(self.width)
+            .min(other.x.saturating_add(other.width));
+        let bottom = self
+            .y
+            .saturating_add(self.height)
+            .min(other.y.saturating_add(other.height));
+
+        (right > x && bottom > y).then(|| Self {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        })
+    }
+
+    /// Smallest rectangle containing this one after mapping its corners, which lie on the pixel
+    /// edges like the points of zbar's polygons
+    pub fn map(&self, transform: &Transform) -> Self {
+        let (left, top) = (self.x as i32, self.y as i32);
+        let (right, bottom) = (left + self.width as i32, top + self.height as i32);
+        let corners = [(left, top), (right, top), (left, bottom), (right, bottom)]
+            .map(|point| transform.apply(point));
+        let min_x = corners.iter().map(|&(x, _)| x).min().unwrap_or(0).max(0);
+        let min_y = corners.iter().map(|&(_, y)| y).min().unwrap_or(0).max(0);
+        let max_x = corners
+            .iter()
+            .map(|&(x, _)| x)
+            .max()
+            .unwrap_or(0)
+            .max(min_x + 1);
+        let max_y = corners
+            .iter()
+            .map(|&(_, y)| y)
+            .max()
+            .unwrap_or(0)
+            .max(min_y + 1);
+
+        Self {
+            x: min_x as u32,
+            y: min_y as u32,
+            width: (max_x - min_x) as u32,
+            height: (max_y - min_y) as u32,
+        }
+    }
+}
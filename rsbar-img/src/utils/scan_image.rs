@@ -1,34 +1,37 @@
-use std::{ffi::CStr, path::Path};
+use std::path::Path;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{Context, Result};
 
-use crate::ffi::{self, ZbarSymbolType};
+use crate::ffi;
 
-use super::{cli_args::Args, XmlPrinter};
-
-const fn zbar_fourcc(code: &[u8; 4]) -> u64 {
-    u32::from_le_bytes(*code) as u64
-}
+use super::{
+    cli_args::Args, load_image::load_images, Candidate, RawImage, Reassembler, Scanner, Symbol,
+    XmlPrinter,
+};
 
 pub fn scan_image(
     filename: &Path,
     idx: usize,
-    processor: *mut libc::c_void,
+    scanner: &mut Scanner,
+    reassembler: &mut Reassembler,
     args: &Args,
-) -> Result<u8> {
-    let zimage = zbar_image_new(filename)?;
+) -> Result<usize> {
+    let (symbols, candidates) = scan_symbols(filename, scanner, args)?;
+    let symbols = reassemble(filename, symbols, reassembler);
 
     if args.xml {
         XmlPrinter::print_source_head(filename);
     }
 
-    process_image(processor, zimage, filename)?;
+    let symbol_count = output_result(&symbols, args, idx);
 
-    let symbol_count = output_result(zimage, args, idx);
+    print_strategies(filename, &symbols);
 
-    drop_zbar_image(zimage);
+    candidates
+        .iter()
+        .for_each(|candidate| print_candidate(args, candidate));
 
-    wait_for_user_quit_window(processor);
+    wait_for_user_quit_window(scanner);
 
     if args.xml {
         XmlPrinter::print_source_foot();
@@ -37,159 +40,182 @@ pub fn scan_image(
     Ok(symbol_count)
 }
 
-fn zbar_image_new(filename: &Path) -> Result<*mut libc::c_void> {
-    let image = image::open(filename)
-        .with_context(|| format!("Failed to open image `{}`", filename.display()))?;
-
-    unsafe {
-        let zimage = ffi::zbar_image_create();
-        assert!(!zimage.is_null());
-
-        ffi::zbar_image_set_format(zimage, zbar_fourcc(b"Y800"));
-
-        let width = image.width();
-        let height = image.height();
-        ffi::zbar_image_set_size(zimage, width, height);
-
-        let bloblen = (width * height) as usize;
-        let blob = libc::malloc(bloblen);
-        ffi::zbar_image_set_data(zimage, blob, bloblen as u64, ffi::zbar_image_free_data);
+/// Scan the file, also locating the candidate regions in the same images if asked to
+fn scan_symbols(
+    filename: &Path,
+    scanner: &mut Scanner,
+    args: &Args,
+) -> Result<(Vec<Symbol>, Vec<Candidate>)> {
+    let scanned = with_raw_image(filename, args, |image| {
+        let candidates = if args.locate {
+            scanner.locate_raw(image)?
+        } else {
+            Vec::new()
+        };
+
+        Ok((scanner.scan_raw(image)?, candidates))
+    })?;
+
+    if let Some(scanned) = scanned {
+        return Ok(scanned);
+    }
 
-        let bytes = image.into_luma8().into_raw();
+    let images = load_images(filename, &args.load_options())?;
+    let candidates = if args.locate {
+        scanner.locate_images(&images)?
+    } else {
+        Vec::new()
+    };
 
-        libc::memcpy(blob, bytes.as_ptr().cast(), bloblen);
+    Ok((scanner.scan_images(images)?, candidates))
+}
 
-        Ok(zimage)
-    }
+/// Hand the symbols over to the reassembler, keeping the ones it returns as complete
+fn reassemble(filename: &Path, symbols: Vec<Symbol>, reassembler: &mut Reassembler) -> Vec<Symbol> {
+    symbols
+        .into_iter()
+        .filter_map(|symbol| {
+            reassembler.add(symbol).unwrap_or_else(|err| {
+                log::warn!("{}: {err}", filename.display());
+                None
+            })
+        })
+        .collect()
 }
 
-fn process_image(
-    processor: *mut libc::c_void,
-    zimage: *mut libc::c_void,
+/// Process the file as raw pixels if a raw format was given
+fn with_raw_image<T>(
     filename: &Path,
-) -> Result<()> {
-    unsafe {
-        let processing_result = ffi::zbar_process_image(processor, zimage);
-
-        if processing_result == -1 {
-            return Err(anyhow!(
-                "Failed to process the image `{}`",
-                filename.display()
-            ));
-        }
+    args: &Args,
+    process: impl FnOnce(&RawImage) -> Result<T>,
+) -> Result<Option<T>> {
+    let (Some(format), Some(size)) = (args.raw_format, args.size) else {
+        return Ok(None);
+    };
+    let bytes = std::fs::read(filename)
+        .with_context(|| format!("Failed to open image `{}`", filename.display()))?;
+    let image = match args.stride {
+        Some(stride) => RawImage::new(&bytes, size.width, size.height, stride, format),
+        None => RawImage::packed(&bytes, size.width, size.height, format),
     }
+    .with_context(|| format!("Failed to read raw image `{}`", filename.display()))?;
 
-    Ok(())
+    process(&image).map(Some)
 }
 
-fn output_result(zimage: *mut libc::c_void, args: &Args, idx: usize) -> u8 {
-    let mut symbol_count: u8 = 0;
-
-    unsafe {
-        let mut symbol = ffi::zbar_image_first_symbol(zimage);
+/// Log the fallbacks symbols were found with, keeping them out of the parsable output
+fn print_strategies(filename: &Path, symbols: &[Symbol]) {
+    let mut strategies = symbols
+        .iter()
+        .filter_map(|symbol| symbol.strategy)
+        .collect::<Vec<_>>();
+
+    strategies.dedup();
+    strategies.iter().for_each(|strategy| {
+        log::info!(
+            "{}: symbol(s) found using fallback `{strategy}`",
+            filename.display()
+        )
+    });
+}
 
-        if args.xml && !symbol.is_null() {
-            XmlPrinter::print_index_head(idx as u8);
-        }
+fn output_result(symbols: &[Symbol], args: &Args, idx: usize) -> usize {
+    let symbols = if args.oneshot {
+        &symbols[..symbols.len().min(1)]
+    } else {
+        symbols
+    };
 
-        while !symbol.is_null() {
-            let symbol_type = ffi::zbar_symbol_get_type(symbol);
+    let mut start = 0;
 
-            if symbol_type == ZbarSymbolType::ZbarPartial {
-                continue;
-            }
+    // Symbols of PDF files are grouped by embedded image, using the image's index in the file
+    while start < symbols.len() {
+        let (image, page) = (symbols[start].image, symbols[start].page);
+        let end = symbols[start..]
+            .iter()
+            .position(|symbol| symbol.image != image)
+            .map_or(symbols.len(), |len| start + len);
 
-            print_symbol(args, symbol, symbol_type);
+        output_index(&symbols[start..end], args, image.unwrap_or(idx), page);
+        start = end;
+    }
 
-            symbol_count += 1;
+    symbols.len()
+}
 
-            if args.oneshot {
-                break;
-            }
+fn output_index(symbols: &[Symbol], args: &Args, idx: usize, page: Option<u32>) {
+    if args.xml {
+        XmlPrinter::print_index_head(idx, page);
+    }
 
-            symbol = ffi::zbar_symbol_next(symbol);
-        }
+    symbols.iter().for_each(|symbol| print_symbol(args, symbol));
 
-        if args.xml && symbol_count > 0 {
-            XmlPrinter::print_index_foot();
-        }
+    if args.xml {
+        XmlPrinter::print_index_foot();
     }
-
-    symbol_count
 }
 
-fn print_symbol_type(args: &Args, symbol_type: ZbarSymbolType) {
-    unsafe {
-        if !args.raw {
-            let symbol_name = ffi::zbar_get_symbol_name(symbol_type);
-
-            if let Ok(symbol_name) = CStr::from_ptr(symbol_name).to_str() {
-                print!("{symbol_name}:");
-            }
-        }
+fn print_page(args: &Args, symbol: &Symbol) {
+    if let (false, Some(page)) = (args.raw, symbol.page) {
+        print!("Page {page}:");
     }
 }
 
-fn print_polygon(args: &Args, symbol: *const libc::c_void) {
-    unsafe {
-        if args.polygon {
-            let point_count = ffi::zbar_symbol_get_loc_size(symbol);
-            let polygon_string = (0..point_count)
-                .map(|idx| {
-                    format!(
-                        "{},{}",
-                        ffi::zbar_symbol_get_loc_x(symbol, idx),
-                        ffi::zbar_symbol_get_loc_y(symbol, idx)
-                    )
-                })
-                .collect::<Vec<String>>()
-                .join(" ");
-
-            print!("{polygon_string}:");
-        }
+fn print_symbol_type(args: &Args, symbol: &Symbol) {
+    if !args.raw {
+        print!("{}:", symbol.symbol_type);
     }
 }
 
-fn print_symbol_data(symbol: *const libc::c_void) {
-    unsafe {
-        let symbol_data = ffi::zbar_symbol_get_data(symbol);
+fn print_polygon(args: &Args, symbol: &Symbol) {
+    if args.polygon {
+        let polygon_string = symbol
+            .polygon
+            .iter()
+            .map(|(x, y)| format!("{x},{y}"))
+            .collect::<Vec<String>>()
+            .join(" ");
 
-        if let Ok(symbol_data) = CStr::from_ptr(symbol_data).to_str() {
-            println!("{symbol_data}");
-        }
+        print!("{polygon_string}:");
     }
 }
 
-fn print_symbol_as_xml(symbol: *const libc::c_void) {
-    unsafe {
-        let symbol_xml = ffi::zbar_symbol_xml(symbol, &mut std::ptr::null_mut(), &mut 0);
+fn print_symbol_data(symbol: &Symbol) {
+    if let Some(symbol_data) = symbol.text() {
+        println!("{symbol_data}");
+    }
+}
 
-        if let Ok(symbol_xml) = CStr::from_ptr(symbol_xml).to_str() {
-            XmlPrinter::print_symbol(symbol_xml);
+fn print_candidate(args: &Args, candidate: &Candidate) {
+    if args.xml {
+        XmlPrinter::print_candidate(candidate);
+    } else {
+        if let Some(page) = candidate.page {
+            print!("Page {page}:");
         }
+
+        println!(
+            "Candidate:{}:{}:{:.2}",
+            candidate.kind, candidate.region, candidate.confidence
+        );
     }
 }
 
-fn print_symbol(args: &Args, symbol: *const libc::c_void, symbol_type: ZbarSymbolType) {
+fn print_symbol(args: &Args, symbol: &Symbol) {
     if args.xml {
-        print_symbol_as_xml(symbol);
+        XmlPrinter::print_symbol(&symbol.to_xml());
     } else {
-        print_symbol_type(args, symbol_type);
+        print_page(args, symbol);
+        print_symbol_type(args, symbol);
         print_polygon(args, symbol);
         print_symbol_data(symbol);
     }
 }
 
-fn drop_zbar_image(zimage: *mut libc::c_void) {
-    unsafe {
-        ffi::zbar_image_destroy(zimage);
-    }
-}
-
-fn wait_for_user_quit_window(processor: *mut libc::c_void) {
+fn wait_for_user_quit_window(scanner: &Scanner) {
     unsafe {
-        if ffi::zbar_processor_is_visible(processor) == 1 {
-            let rc = ffi::zbar_processor_user_wait(processor, -1);
+        if ffi::zbar_processor_is_visible(scanner.as_ptr()) == 1 {
+            let rc = ffi::zbar_processor_user_wait(scanner.as_ptr(), -1);
 
             if rc < 0 || rc == b'q'.into() || rc == b'Q'.into() {
                 // FIXME: Enable aborting further scanning of more images when hitting the "q" key
@@ -0,0 +1,444 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use image::GrayImage;
+
+use crate::ffi::{self, ZbarConfig, ZbarSymbolType};
+
+use super::{
+    load_image::{load_images, LoadedImage},
+    locate::locate,
+    parse_config,
+    preprocess::{preprocess, Preprocess},
+    pyramid::pyramid,
+    raw_image::into_zbar_image,
+    rectify::rectify,
+    region::{Rect, Region},
+    set_symbology_config,
+    transform::Transform,
+    try_harder::{fallback_strategies, Strategy},
+    Candidate, EdgeDetection, FourCC, LoadOptions, RawImage, Symbol,
+};
+
+/// Bar code scanner backed by a zbar processor
+pub struct Scanner {
+    processor: *mut libc::c_void,
+    crop: Option<Region>,
+    regions: Vec<Region>,
+    preprocessing: Vec<Preprocess>,
+    try_harder: bool,
+    pyramid_levels: u32,
+    rectify: bool,
+    /// Scan densities along the x and y axes, as configured
+    density: (i32, i32),
+}
+
+impl Scanner {
+    /// Create a scanner that doesn't display the scanned images
+    pub fn new() -> Result<Self> {
+        Self::with_display(false, false)
+    }
+
+    pub(crate) fn with_display(display: bool, dbus: bool) -> Result<Self> {
+        unsafe {
+            let processor = ffi::zbar_processor_create(0);
+
+            assert!(!processor.is_null());
+
+            let scanner = Self {
+                processor,
+                crop: None,
+                regions: Vec::new(),
+                preprocessing: Vec::new(),
+                try_harder: false,
+                pyramid_levels: 1,
+                rectify: false,
+                density: (1, 1),
+            };
+
+            if cfg!(feature = "dbus") {
+                ffi::zbar_processor_request_dbus(processor, dbus.into());
+            }
+
+            if ffi::zbar_processor_init(processor, std::ptr::null(), display.into()) != 0 {
+                ffi::_zbar_error_spew(processor, 0);
+                return Err(anyhow!("Failed to initialize the processor"));
+            }
+
+            ffi::zbar_processor_set_visible(processor, display.into());
+
+            Ok(scanner)
+        }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut libc::c_void {
+        self.processor
+    }
+
+    /// Set decoder/scanner config, using the syntax of the `--set` option (e.g. `qrcode.disable`)
+    pub fn set_config(&mut self, setting: &str) -> Result<()> {
+        match parse_config(self.processor, setting)? {
+            (_, ZbarConfig::XDensity, value) => self.density.0 = value,
+            (_, ZbarConfig::YDensity, value) => self.density.1 = value,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Retry a linear symbology (e.g. `code128`) on the inverted image when it is not found,
+    /// to decode light-on-dark bar codes
+    ///
+    /// For `*`, the whole image is rescanned inverted if nothing is found at all
+    pub fn set_test_inverted(&mut self, symbology: &str, enabled: bool) -> Result<()> {
+        set_symbology_config(self.processor, symbology, "test-inverted", enabled.into())
+    }
+
+    /// Set the parameters used to detect the edges between bars and spaces along scan lines
+    pub fn set_edge_detection(&mut self, params: EdgeDetection) -> Result<()> {
+        for (config, value) in [
+            (ZbarConfig::EdgeThreshold, params.threshold),
+            (ZbarConfig::EwmaWeight, params.ewma_weight),
+            (ZbarConfig::ThresholdDecay, params.threshold_decay),
+        ] {
+            let value = libc::c_int::try_from(value)?;
+
+            unsafe {
+                if ffi::zbar_processor_set_config(
+                    self.processor,
+                    ZbarSymbolType::ZbarNone,
+                    config,
+                    value,
+                ) != 0
+                {
+                    return Err(anyhow!("Failed to set the edge detection parameters"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Only search for bar codes inside the given part of the images
+    pub fn set_crop(&mut self, crop: Option<Region>) {
+        self.crop = crop;
+    }
+
+    /// Search each of the given parts of the images separately (restricted to the crop, if any),
+    /// instead of the whole image
+    pub fn set_regions(&mut self, regions: Vec<Region>) {
+        self.regions = regions;
+    }
+
+    /// Transform the grayscale images with the given steps (in order) before searching them
+    pub fn set_preprocessing(&mut self, steps: Vec<Preprocess>) {
+        self.preprocessing = steps;
+    }
+
+    /// Rescan images without any symbol using fallback strategies, until one of them succeeds
+    pub fn set_try_harder(&mut self, try_harder: bool) {
+        self.try_harder = try_harder;
+    }
+
+    /// Also scan up to `levels - 1` successively halved copies of the images, merging the symbols
+    /// found at every scale
+    pub fn set_pyramid_levels(&mut self, levels: u32) {
+        self.pyramid_levels = levels.max(1);
+    }
+
+    /// Also rescan the regions of parallel bars warped to a fronto-parallel view, to decode 1D
+    /// bar codes on tilted surfaces
+    pub fn set_rectify(&mut self, rectify: bool) {
+        self.rectify = rectify;
+    }
+
+    /// Scan an image file for bar codes, reporting positions in the file's pixel coordinates
+    ///
+    /// For PDF files, every embedded raster image is scanned, reporting positions in the
+    /// pixel coordinates of the image and the page it is shown on
+    pub fn scan_file(&mut self, path: &Path, options: &LoadOptions) -> Result<Vec<Symbol>> {
+        self.scan_images(load_images(path, options)?)
+    }
+
+    /// Scan the images loaded from a file
+    pub(crate) fn scan_images(&mut self, images: Vec<LoadedImage>) -> Result<Vec<Symbol>> {
+        let mut symbols = Vec::new();
+
+        for (idx, image) in images.into_iter().enumerate() {
+            let mut found = self.scan_luma(image.luma, &image.transform)?;
+
+            for channel in image.channels {
+                let channel_symbols = self.scan_luma(channel, &image.transform)?;
+
+                Symbol::merge(&mut found, channel_symbols);
+            }
+
+            found.iter_mut().for_each(|symbol| {
+                symbol.page = image.page;
+                symbol.image = image.page.map(|_| idx);
+            });
+            symbols.append(&mut found);
+        }
+
+        Ok(symbols)
+    }
+
+    /// Scan a raw pixel buffer for bar codes
+    pub fn scan_raw(&mut self, image: &RawImage) -> Result<Vec<Symbol>> {
+        if !self.preprocessing.is_empty() || self.pyramid_levels > 1 || self.rectify {
+            return self.scan_luma(image.to_luma()?, &Transform::IDENTITY);
+        }
+
+        let symbols = self.scan(image.to_zbar_image()?, &Transform::IDENTITY)?;
+
+        if !symbols.is_empty() || !self.try_harder {
+            return Ok(symbols);
+        }
+
+        self.scan_fallbacks(&image.to_luma()?, &Transform::IDENTITY)
+    }
+
+    /// Find the regions of an image file that look like bar codes inside the crop and regions,
+    /// whether or not they can be decoded, most likely first per image
+    pub fn locate_file(&self, path: &Path, options: &LoadOptions) -> Result<Vec<Candidate>> {
+        self.locate_images(&load_images(path, options)?)
+    }
+
+    /// Find the regions of a raw pixel buffer that look like bar codes inside the crop and regions
+    pub fn locate_raw(&self, image: &RawImage) -> Result<Vec<Candidate>> {
+        self.locate_images(&[LoadedImage {
+            luma: image.to_luma()?,
+            channels: Vec::new(),
+            transform: Transform::IDENTITY,
+            page: None,
+        }])
+    }
+
+    /// Find the regions of the images loaded from a file that look like bar codes
+    pub(crate) fn locate_images(&self, images: &[LoadedImage]) -> Result<Vec<Candidate>> {
+        let mut candidates = Vec::new();
+
+        for image in images {
+            let (width, height) = image.luma.dimensions();
+            let rects = self.scan_rects(width, height, &image.transform);
+
+            candidates.append(&mut locate(image, &rects)?);
+        }
+
+        Ok(candidates)
+    }
+
+    /// Scan a grayscale image, `transform` mapping its points to the reported ones
+    fn scan_luma(&mut self, luma: GrayImage, transform: &Transform) -> Result<Vec<Symbol>> {
+        if !self.try_harder {
+            return self.scan_preprocessed(luma, &self.preprocessing.clone(), transform);
+        }
+
+        let symbols =
+            self.scan_preprocessed(luma.clone(), &self.preprocessing.clone(), transform)?;
+
+        if !symbols.is_empty() {
+            return Ok(symbols);
+        }
+
+        self.scan_fallbacks(&luma, transform)
+    }
+
+    /// Try the fallback strategies in order, returning the symbols of the first successful one
+    fn scan_fallbacks(&mut self, luma: &GrayImage, transform: &Transform) -> Result<Vec<Symbol>> {
+        for strategy in fallback_strategies(luma.width(), luma.height(), self.density) {
+            let mut steps = self.preprocessing.clone();
+            let mut symbols = match strategy {
+                Strategy::Preprocess(step) => {
+                    steps.push(step);
+                    self.scan_preprocessed(luma.clone(), &steps, transform)?
+                }
+                Strategy::FullDensity => {
+                    let density = self.density;
+
+                    self.set_density((1, 1))?;
+
+                    let symbols = self.scan_preprocessed(luma.clone(), &steps, transform);
+
+                    self.set_density(density)?;
+                    symbols?
+                }
+            };
+
+            if !symbols.is_empty() {
+                symbols
+                    .iter_mut()
+                    .for_each(|symbol| symbol.strategy = Some(strategy));
+
+                return Ok(symbols);
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
+    fn scan_preprocessed(
+        &mut self,
+        luma: GrayImage,
+        steps: &[Preprocess],
+        transform: &Transform,
+    ) -> Result<Vec<Symbol>> {
+        let (luma, preprocess_transform) = preprocess(luma, steps);
+        let transform = preprocess_transform.then(transform);
+        let views = if self.rectify {
+            let (width, height) = luma.dimensions();
+
+            rectify(&luma, &self.scan_rects(width, height, &transform))
+        } else {
+            Vec::new()
+        };
+        let mut symbols = Vec::new();
+
+        for (level, level_transform) in pyramid(luma, self.pyramid_levels) {
+            let (width, height) = level.dimensions();
+            let zimage = into_zbar_image(level.into_raw(), width, height, FourCC::Y800)?;
+            let found = self.scan(zimage, &level_transform.then(&transform))?;
+
+            Symbol::merge(&mut symbols, found);
+        }
+
+        for (view, view_transform) in views {
+            let (width, height) = view.dimensions();
+            let zimage = into_zbar_image(view.into_raw(), width, height, FourCC::Y800)?;
+            // The view only shows the part of the image the bars were found in
+            let whole = Rect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            };
+            let found = self.scan_in(zimage, &[whole], &view_transform.then(&transform))?;
+
+            Symbol::merge(&mut symbols, found);
+        }
+
+        Ok(symbols)
+    }
+
+    fn set_density(&mut self, (x, y): (i32, i32)) -> Result<()> {
+        for (config, value) in [(ZbarConfig::XDensity, x), (ZbarConfig::YDensity, y)] {
+            unsafe {
+                if ffi::zbar_processor_set_config(
+                    self.processor,
+                    ZbarSymbolType::ZbarNone,
+                    config,
+                    value,
+                ) != 0
+                {
+                    return Err(anyhow!("Failed to set the scan density"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Scan a zbar image and destroy it afterwards
+    fn scan(&mut self, zimage: *mut libc::c_void, transform: &Transform) -> Result<Vec<Symbol>> {
+        let (width, height) = unsafe {
+            (
+                ffi::zbar_image_get_width(zimage),
+                ffi::zbar_image_get_height(zimage),
+            )
+        };
+        let rects = self.scan_rects(width, height, transform);
+
+        self.scan_in(zimage, &rects, transform)
+    }
+
+    /// Scan the given parts of a zbar image and destroy it afterwards
+    fn scan_in(
+        &mut self,
+        zimage: *mut libc::c_void,
+        rects: &[Rect],
+        transform: &Transform,
+    ) -> Result<Vec<Symbol>> {
+        let symbols = rects
+            .iter()
+            .try_fold(Vec::new(), |mut symbols, rect| -> Result<_> {
+                unsafe {
+                    ffi::zbar_image_set_crop(zimage, rect.x, rect.y, rect.width, rect.height);
+                }
+
+                self.process(zimage)?;
+                Symbol::merge(&mut symbols, unsafe { Symbol::collect_from_image(zimage) });
+
+                Ok(symbols)
+            });
+
+        unsafe {
+            ffi::zbar_image_destroy(zimage);
+        }
+
+        let mut symbols = symbols?;
+
+        symbols
+            .iter_mut()
+            .for_each(|symbol| symbol.transform(transform));
+
+        Ok(symbols)
+    }
+
+    /// Parts of the scanned image to search, computed from the crop and regions, which are
+    /// given in the coordinates `transform` maps to
+    fn scan_rects(&self, width: u32, height: u32, transform: &Transform) -> Vec<Rect> {
+        let image = Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        };
+
+        if width == 0 || height == 0 {
+            return vec![image];
+        }
+
+        let source = image.map(transform);
+        let crop = match self.crop {
+            Some(crop) => match crop.resolve(source.width, source.height) {
+                Some(crop) => crop,
+                None => return Vec::new(),
+            },
+            None => Rect {
+                x: 0,
+                y: 0,
+                ..source
+            },
+        };
+        let inverse = transform.inverse();
+
+        if self.regions.is_empty() {
+            return crop.map(&inverse).intersect(&image).into_iter().collect();
+        }
+
+        self.regions
+            .iter()
+            .filter_map(|region| region.resolve(source.width, source.height))
+            .filter_map(|region| region.intersect(&crop))
+            .filter_map(|region| region.map(&inverse).intersect(&image))
+            .collect()
+    }
+
+    fn process(&self, zimage: *mut libc::c_void) -> Result<()> {
+        unsafe {
+            if ffi::zbar_process_image(self.processor, zimage) == -1 {
+                return Err(anyhow!("Failed to process the image"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Scanner {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::zbar_processor_destroy(self.processor);
+        }
+    }
+}
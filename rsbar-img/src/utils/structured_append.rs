@@ -0,0 +1,204 @@
+use anyhow::{anyhow, Result};
+
+use crate::ffi;
+
+use super::Symbol;
+
+/// Structured append header of a symbol holding one part of a message split across
+/// several symbols
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StructuredAppend {
+    /// Position of the part in the message, starting at 0
+    pub index: u32,
+    /// Number of parts the message is split into
+    pub size: u32,
+    /// Parity of the whole message, as announced by the header
+    pub parity: u32,
+    /// Parity of the data of this part
+    pub data_parity: u32,
+}
+
+impl StructuredAppend {
+    /// Read the structured append header of a zbar symbol, if it has one
+    ///
+    /// # Safety
+    ///
+    /// `symbol` must point to a valid zbar symbol
+    pub(crate) unsafe fn from_zbar(symbol: *const libc::c_void) -> Option<Self> {
+        let (mut index, mut size, mut parity, mut data_parity) = (0, 0, 0, 0);
+        let rc = ffi::zbar_symbol_get_structured_append(
+            symbol,
+            &mut index,
+            &mut size,
+            &mut parity,
+            &mut data_parity,
+        );
+
+        (rc == 0).then_some(Self {
+            index,
+            size,
+            parity,
+            data_parity,
+        })
+    }
+}
+
+/// Parts of one structured append message found so far
+struct Group {
+    symbol_type: String,
+    size: u32,
+    parity: u32,
+    parts: Vec<Option<Symbol>>,
+}
+
+impl Group {
+    fn new(symbol_type: &str, header: StructuredAppend) -> Self {
+        Self {
+            symbol_type: symbol_type.to_owned(),
+            size: header.size,
+            parity: header.parity,
+            parts: vec![None; header.size as usize],
+        }
+    }
+
+    fn matches(&self, symbol_type: &str, header: StructuredAppend) -> bool {
+        self.symbol_type == symbol_type && self.size == header.size && self.parity == header.parity
+    }
+
+    fn is_complete(&self) -> bool {
+        self.parts.iter().all(Option::is_some)
+    }
+
+    /// Numbers (starting at 1) of the parts not found yet
+    fn missing(&self) -> Vec<String> {
+        (1..)
+            .zip(&self.parts)
+            .filter(|(_, part)| part.is_none())
+            .map(|(number, _)| number.to_string())
+            .collect()
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "structured append {} message with parity 0x{:02x}",
+            self.symbol_type, self.parity
+        )
+    }
+
+    /// Concatenate the parts, once all of them were found, into a symbol located like the
+    /// part completing the message
+    fn message(&self, last: Symbol) -> Result<Symbol> {
+        let parts = self.parts.iter().flatten();
+        let parity = parts
+            .clone()
+            .filter_map(|part| part.structured_append)
+            .fold(0, |parity, header| parity ^ header.data_parity);
+
+        if parity != self.parity {
+            return Err(anyhow!(
+                "{} failed the parity check (0x{parity:02x} in the data), dropping it",
+                self.describe()
+            ));
+        }
+
+        Ok(Symbol {
+            data: parts.flat_map(|part| part.data.iter().copied()).collect(),
+            structured_append: None,
+            ..last
+        })
+    }
+}
+
+/// Reassembly of structured append messages from parts spread over the images of a run, and
+/// of the data of symbols to be appended to the next symbol of the same type (e.g. Code 128
+/// FNC2), in the order they are found
+#[derive(Default)]
+pub struct Reassembler {
+    groups: Vec<Group>,
+    appended: Vec<Symbol>,
+}
+
+impl Reassembler {
+    /// Add a symbol, returning it or the message it completes unless more parts are expected
+    pub fn add(&mut self, part: Symbol) -> Result<Option<Symbol>> {
+        let Some(part) = self.append(part) else {
+            return Ok(None);
+        };
+        let Some(header) = part.structured_append else {
+            return Ok(Some(part));
+        };
+        let pos = match self
+            .groups
+            .iter()
+            .position(|group| group.matches(&part.symbol_type, header))
+        {
+            Some(pos) => pos,
+            None => {
+                self.groups.push(Group::new(&part.symbol_type, header));
+                self.groups.len() - 1
+            }
+        };
+        let group = &mut self.groups[pos];
+
+        // The same part may show up again, e.g. on another copy of the page, even once the
+        // message is complete
+        match group.parts.get_mut(header.index as usize) {
+            Some(slot @ None) => *slot = Some(part.clone()),
+            _ => return Ok(None),
+        }
+
+        if !group.is_complete() {
+            return Ok(None);
+        }
+
+        group.message(part).map(Some)
+    }
+
+    /// Keep the data of a symbol to be appended to the next one of its type, or prepend the data
+    /// kept so far to the symbol
+    fn append(&mut self, symbol: Symbol) -> Option<Symbol> {
+        let symbol = match self
+            .appended
+            .iter()
+            .position(|other| other.symbol_type == symbol.symbol_type)
+        {
+            Some(pos) => {
+                let mut data = self.appended.swap_remove(pos).data;
+                data.extend(&symbol.data);
+
+                Symbol { data, ..symbol }
+            }
+            None => symbol,
+        };
+
+        if symbol.is_appended() {
+            self.appended.push(symbol);
+
+            return None;
+        }
+
+        Some(symbol)
+    }
+
+    /// Warn about the messages still missing parts
+    pub fn report_incomplete(&self) {
+        self.groups
+            .iter()
+            .filter(|group| !group.is_complete())
+            .for_each(|group| {
+                log::warn!(
+                    "{} is incomplete: missing part(s) {} of {}",
+                    group.describe(),
+                    group.missing().join(", "),
+                    group.size
+                )
+            });
+
+        self.appended.iter().for_each(|symbol| {
+            log::warn!(
+                "{} data to append to the next symbol is incomplete: no symbol followed it",
+                symbol.symbol_type
+            )
+        });
+    }
+}
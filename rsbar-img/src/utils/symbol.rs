@@ -0,0 +1,297 @@
+use std::ffi::CStr;
+
+use crate::ffi::{self, ZbarConfig, ZbarSymbolType};
+
+use super::{structured_append::StructuredAppend, transform::Transform, try_harder::Strategy};
+
+/// Number of symbol modifiers known to zbar (`ZBAR_MOD_NUM`)
+const MODIFIER_COUNT: libc::c_int = 5;
+
+/// A bar code symbol decoded from an image
+#[derive(Clone, Debug, PartialEq)]
+pub struct Symbol {
+    /// Name of the symbology, e.g. `QR-Code`
+    pub symbol_type: String,
+    /// The decoded data
+    pub data: Vec<u8>,
+    /// Points delimiting the code zone, in image coordinates
+    pub polygon: Vec<(i32, i32)>,
+    /// Relative confidence of the decode (the higher, the better)
+    pub quality: i32,
+    /// Orientation of the symbol in the image, e.g. `UP`
+    pub orientation: String,
+    /// Names of the modifiers reported by the decoder, e.g. `GS1`
+    pub modifiers: Vec<String>,
+    /// Names of the (non-default) configs the symbol was decoded with, e.g. `ADD_CHECK`
+    pub configs: Vec<String>,
+    /// Number of times the symbol was seen before (for scanners with caching enabled)
+    pub count: i32,
+    /// Number of the PDF page (starting at 1) the symbol was found on, if scanning a PDF file
+    pub page: Option<u32>,
+    /// Index (starting at 0) of the raster image embedded in a PDF file the symbol was found in
+    pub image: Option<usize>,
+    /// Fallback the symbol was found with, if it was only found when trying harder
+    pub strategy: Option<Strategy>,
+    /// Structured append header, if the symbol is one part of a message split across
+    /// several symbols that could not be reassembled from the same image
+    pub structured_append: Option<StructuredAppend>,
+}
+
+unsafe fn name_to_string(name: *const libc::c_char) -> String {
+    CStr::from_ptr(name).to_string_lossy().into_owned()
+}
+
+unsafe fn flag_names(
+    flags: libc::c_uint,
+    count: libc::c_int,
+    name: unsafe extern "C" fn(libc::c_int) -> *const libc::c_char,
+) -> Vec<String> {
+    (0..count)
+        .filter(|&idx| flags & (1 << idx) != 0)
+        .map(|idx| name_to_string(name(idx)))
+        .collect()
+}
+
+impl Symbol {
+    /// Copy the results of a zbar symbol
+    ///
+    /// # Safety
+    ///
+    /// `symbol` must point to a valid zbar symbol
+    pub(crate) unsafe fn from_zbar(symbol: *const libc::c_void) -> Self {
+        let data = std::slice::from_raw_parts(
+            ffi::zbar_symbol_get_data(symbol).cast::<u8>(),
+            ffi::zbar_symbol_get_data_length(symbol) as usize,
+        )
+        .to_vec();
+        let polygon = (0..ffi::zbar_symbol_get_loc_size(symbol))
+            .map(|idx| {
+                (
+                    ffi::zbar_symbol_get_loc_x(symbol, idx),
+                    ffi::zbar_symbol_get_loc_y(symbol, idx),
+                )
+            })
+            .collect();
+        let configs =
+            ffi::zbar_symbol_get_configs(symbol) & !(1 << ZbarConfig::Enable as libc::c_uint);
+
+        Self {
+            symbol_type: name_to_string(ffi::zbar_get_symbol_name(ffi::zbar_symbol_get_type(
+                symbol,
+            ))),
+            data,
+            polygon,
+            quality: ffi::zbar_symbol_get_quality(symbol),
+            orientation: name_to_string(ffi::zbar_get_orientation_name(
+                ffi::zbar_symbol_get_orientation(symbol),
+            )),
+            modifiers: flag_names(
+                ffi::zbar_symbol_get_modifiers(symbol),
+                MODIFIER_COUNT,
+                ffi::zbar_get_modifier_name,
+            ),
+            configs: flag_names(configs, ZbarConfig::Num as _, ffi::zbar_get_config_name),
+            count: ffi::zbar_symbol_get_count(symbol),
+            page: None,
+            image: None,
+            strategy: None,
+            structured_append: StructuredAppend::from_zbar(symbol),
+        }
+    }
+
+    /// Collect the symbols decoded from a zbar image, skipping intermediate results
+    ///
+    /// The parts of incomplete structured append messages are collected separately, to be
+    /// reassembled with the parts found in other images.
+    ///
+    /// # Safety
+    ///
+    /// `zimage` must point to a valid zbar image
+    pub(crate) unsafe fn collect_from_image(zimage: *const libc::c_void) -> Vec<Self> {
+        let mut symbols = Vec::new();
+        let mut symbol = ffi::zbar_image_first_symbol(zimage);
+
+        while !symbol.is_null() {
+            if ffi::zbar_symbol_get_type(symbol) != ZbarSymbolType::ZbarPartial {
+                symbols.push(Self::from_zbar(symbol));
+            } else {
+                symbols.extend(Self::collect_parts(symbol));
+            }
+
+            symbol = ffi::zbar_symbol_next(symbol);
+        }
+
+        symbols
+    }
+
+    /// Collect the structured append parts of an incomplete composite result
+    unsafe fn collect_parts(symbol: *const libc::c_void) -> Vec<Self> {
+        let mut parts = Vec::new();
+        let mut part = ffi::zbar_symbol_first_component(symbol);
+
+        while !part.is_null() {
+            if ffi::zbar_symbol_get_type(part) != ZbarSymbolType::ZbarPartial {
+                let part = Self::from_zbar(part);
+
+                if part.structured_append.is_some() {
+                    parts.push(part);
+                }
+            }
+
+            part = ffi::zbar_symbol_next(part);
+        }
+
+        parts
+    }
+
+    pub(crate) fn transform(&mut self, transform: &Transform) {
+        self.polygon
+            .iter_mut()
+            .for_each(|point| *point = transform.apply(*point));
+    }
+
+    /// Bounding box of the polygon as `(left, top, right, bottom)`
+    fn bounds(&self) -> Option<(i32, i32, i32, i32)> {
+        self.polygon.iter().fold(None, |bounds, &(x, y)| {
+            Some(match bounds {
+                Some((left, top, right, bottom)) => {
+                    (x.min(left), y.min(top), x.max(right), y.max(bottom))
+                }
+                None => (x, y, x, y),
+            })
+        })
+    }
+
+    /// Whether both symbols carry the same data at overlapping positions, i.e. they are
+    /// the same code found by different scans of an image
+    pub(crate) fn is_duplicate_of(&self, other: &Self) -> bool {
+        if self.symbol_type != other.symbol_type || self.data != other.data {
+            return false;
+        }
+
+        match (self.bounds(), other.bounds()) {
+            (Some(a), Some(b)) => a.0 <= b.2 && b.0 <= a.2 && a.1 <= b.3 && b.1 <= a.3,
+            _ => true,
+        }
+    }
+
+    /// Add the symbols of another scan of the same image, skipping ones already found
+    pub(crate) fn merge(symbols: &mut Vec<Self>, found: Vec<Self>) {
+        let known = symbols.len();
+
+        for symbol in found {
+            if !symbols[..known]
+                .iter()
+                .any(|other| symbol.is_duplicate_of(other))
+            {
+                symbols.push(symbol);
+            }
+        }
+    }
+
+    /// The data, if it is valid UTF-8
+    pub fn text(&self) -> Option<&str> {
+        std::str::from_utf8(&self.data).ok()
+    }
+
+    /// Whether the symbol was decoded from its mirror image (`MIRRORED` modifier)
+    pub fn is_mirrored(&self) -> bool {
+        self.modifiers.iter().any(|modifier| modifier == "MIRRORED")
+    }
+
+    /// Whether the data is to be prepended to the next symbol (`APPEND` modifier)
+    pub fn is_appended(&self) -> bool {
+        self.modifiers.iter().any(|modifier| modifier == "APPEND")
+    }
+
+    /// Same heuristic as zbar to decide whether the data can't be embedded in XML as text
+    fn is_binary(&self) -> bool {
+        let data = &self.data;
+
+        data.starts_with(&[0xff, 0xfe])
+            || data.starts_with(&[0xfe, 0xff])
+            || data.starts_with(b"<?xml")
+            || data.iter().enumerate().any(|(idx, &c)| {
+                (c < 0x20 && (!0x0000_2600u32 >> c) & 1 != 0)
+                    || (0x7f..0xa0).contains(&c)
+                    || data[idx..].starts_with(b"]]>")
+            })
+    }
+
+    /// Serialize the symbol in the XML format used by zbar, as bytes since text data is copied
+    /// as is, whatever its encoding
+    pub fn to_xml(&self) -> Vec<u8> {
+        let mut xml = format!(
+            "<symbol type='{}' quality='{}' orientation='{}'",
+            self.symbol_type, self.quality, self.orientation
+        );
+
+        if !self.modifiers.is_empty() {
+            xml.push_str(&format!(" modifiers='{}'", self.modifiers.join(" ")));
+        }
+
+        if !self.configs.is_empty() {
+            xml.push_str(&format!(" configs='{}'", self.configs.join(" ")));
+        }
+
+        if self.count != 0 {
+            xml.push_str(&format!(" count='{}'", self.count));
+        }
+
+        let points = self
+            .polygon
+            .iter()
+            .map(|(x, y)| format!("{x:+},{y:+}"))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        xml.push_str(&format!("><polygon points='{points}'/><data"));
+
+        let mut xml = xml.into_bytes();
+
+        if self.is_binary() {
+            xml.extend(
+                format!(
+                    " format='base64' length='{}'><![CDATA[\n{}",
+                    self.data.len(),
+                    base64_encode(&self.data)
+                )
+                .bytes(),
+            );
+        } else {
+            xml.extend(b"><![CDATA[");
+            xml.extend(&self.data);
+        }
+
+        xml.extend(b"]]></data></symbol>");
+
+        xml
+    }
+}
+
+/// Base64 encoding with line breaks after every 76 characters and a trailing one, like zbar
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::new();
+
+    for (idx, chunk) in data.chunks(3).enumerate() {
+        let buf = chunk.iter().enumerate().fold(0u32, |buf, (i, &byte)| {
+            buf | u32::from(byte) << (16 - 8 * i)
+        });
+        let sextet = |shift: u32| char::from(ALPHABET[(buf >> shift & 0x3f) as usize]);
+
+        encoded.push(sextet(18));
+        encoded.push(sextet(12));
+        encoded.push(if chunk.len() > 1 { sextet(6) } else { '=' });
+        encoded.push(if chunk.len() > 2 { sextet(0) } else { '=' });
+
+        if chunk.len() == 3 && (idx + 1) % 19 == 0 {
+            encoded.push('\n');
+        }
+    }
+
+    encoded.push('\n');
+
+    encoded
+}
@@ -0,0 +1,89 @@
+/// Projective mapping of points from a processed image back to the image it was derived from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Transform([[f64; 3]; 3]);
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+
+    /// Map `(x, y)` to `(a * x + b * y + c, d * x + e * y + f)`
+    pub const fn affine(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Self([[a, b, c], [d, e, f], [0.0, 0.0, 1.0]])
+    }
+
+    /// Map the corners (0, 0), (1, 0), (1, 1) and (0, 1) of the unit square onto the corners of
+    /// the given quadrilateral, in the same order
+    pub fn square_to_quad(quad: [(f64, f64); 4]) -> Self {
+        let [(x0, y0), (x1, y1), (x2, y2), (x3, y3)] = quad;
+        let (dx1, dy1) = (x1 - x2, y1 - y2);
+        let (dx2, dy2) = (x3 - x2, y3 - y2);
+        let (dx3, dy3) = (x0 - x1 + x2 - x3, y0 - y1 + y2 - y3);
+        let determinant = dx1 * dy2 - dx2 * dy1;
+        let g = (dx3 * dy2 - dx2 * dy3) / determinant;
+        let h = (dx1 * dy3 - dx3 * dy1) / determinant;
+
+        Self([
+            [x1 - x0 + g * x1, x3 - x0 + h * x3, x0],
+            [y1 - y0 + g * y1, y3 - y0 + h * y3, y0],
+            [g, h, 1.0],
+        ])
+    }
+
+    /// Apply this mapping, followed by `next`
+    pub fn then(&self, next: &Self) -> Self {
+        let (a, b) = (&next.0, &self.0);
+
+        Self(std::array::from_fn(|row| {
+            std::array::from_fn(|col| (0..3).map(|idx| a[row][idx] * b[idx][col]).sum())
+        }))
+    }
+
+    /// The mapping in the opposite direction
+    pub fn inverse(&self) -> Self {
+        let m = &self.0;
+        let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| {
+            m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+        };
+        let adjugate = [
+            [
+                cofactor(1, 2, 1, 2),
+                -cofactor(0, 2, 1, 2),
+                cofactor(0, 1, 1, 2),
+            ],
+            [
+                -cofactor(1, 2, 0, 2),
+                cofactor(0, 2, 0, 2),
+                -cofactor(0, 1, 0, 2),
+            ],
+            [
+                cofactor(1, 2, 0, 1),
+                -cofactor(0, 2, 0, 1),
+                cofactor(0, 1, 0, 1),
+            ],
+        ];
+        let determinant = (0..3).map(|col| m[0][col] * adjugate[col][0]).sum::<f64>();
+
+        Self(adjugate.map(|row| row.map(|value| value / determinant)))
+    }
+
+    pub fn apply(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        let (x, y) = self.apply_f64(f64::from(x), f64::from(y));
+
+        (x.round() as i32, y.round() as i32)
+    }
+
+    pub fn apply_f64(&self, x: f64, y: f64) -> (f64, f64) {
+        let [r0, r1, r2] = &self.0;
+        let w = r2[0] * x + r2[1] * y + r2[2];
+
+        (
+            (r0[0] * x + r0[1] * y + r0[2]) / w,
+            (r1[0] * x + r1[1] * y + r1[2]) / w,
+        )
+    }
+}
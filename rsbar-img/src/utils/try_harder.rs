@@ -0,0 +1,64 @@
+use std::fmt;
+
+use super::preprocess::{Preprocess, Threshold, ADAPTIVE_THRESHOLD_RADIUS};
+
+/// Images whose longer side is shorter than this are upscaled
+const SMALL_IMAGE_SIZE: u32 = 640;
+
+/// Images whose longer side is longer than this are downscaled
+const HUGE_IMAGE_SIZE: u32 = 2048;
+
+/// Size of the longer side of downscaled huge images
+const DOWNSCALED_IMAGE_SIZE: u32 = 1024;
+
+/// Factor small images are upscaled by
+const UPSCALE_FACTOR: f64 = 2.0;
+
+/// Angle images are rotated by, so codes at odd angles get scanned along their bars
+const ROTATION_DEGREES: f64 = 45.0;
+
+/// A fallback used to rescan an image when nothing was found
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Strategy {
+    /// Append a step to the preprocessing chain
+    Preprocess(Preprocess),
+    /// Scan every row and column of the image (`x-density=1` and `y-density=1`)
+    FullDensity,
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Preprocess(step) => write!(f, "{step}"),
+            Self::FullDensity => write!(f, "density:1"),
+        }
+    }
+}
+
+/// Fallbacks tried (in order) for an image of the given size scanned with the given densities
+pub(crate) fn fallback_strategies(width: u32, height: u32, density: (i32, i32)) -> Vec<Strategy> {
+    let size = width.max(height);
+    let mut strategies = Vec::new();
+
+    if size < SMALL_IMAGE_SIZE {
+        strategies.push(Strategy::Preprocess(Preprocess::Scale(UPSCALE_FACTOR)));
+    } else if size > HUGE_IMAGE_SIZE {
+        strategies.push(Strategy::Preprocess(Preprocess::Scale(
+            f64::from(DOWNSCALED_IMAGE_SIZE) / f64::from(size),
+        )));
+    }
+
+    strategies.extend([
+        Strategy::Preprocess(Preprocess::Rotate(ROTATION_DEGREES)),
+        Strategy::Preprocess(Preprocess::Invert),
+        Strategy::Preprocess(Preprocess::Threshold(Threshold::Adaptive(
+            ADAPTIVE_THRESHOLD_RADIUS,
+        ))),
+    ]);
+
+    if density != (1, 1) {
+        strategies.push(Strategy::FullDensity);
+    }
+
+    strategies
+}
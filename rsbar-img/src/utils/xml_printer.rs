@@ -1,4 +1,9 @@
-use std::path::Path;
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use super::Candidate;
 
 const INDENT_WIDTH: usize = 4;
 const INDENT_CHARACTER: char = ' ';
@@ -29,15 +34,41 @@ impl XmlPrinter {
         Self::print_xml("</source>".to_string(), 1);
     }
 
-    pub fn print_index_head(num: u8) {
-        Self::print_xml(format!("<index num=\"{num}\">"), 2);
+    pub fn print_index_head(num: usize, page: Option<u32>) {
+        let page = page.map_or(String::new(), |page| format!(" page=\"{page}\""));
+
+        Self::print_xml(format!("<index num=\"{num}\"{page}>"), 2);
     }
 
     pub fn print_index_foot() {
         Self::print_xml("</index>".to_string(), 2);
     }
 
-    pub fn print_symbol(symbol_xml: &str) {
-        Self::print_xml(symbol_xml.to_string(), 3);
+    /// Print the XML of a symbol, whose text data may not be valid UTF-8
+    pub fn print_symbol(symbol_xml: &[u8]) {
+        let mut stdout = io::stdout().lock();
+
+        write!(
+            stdout,
+            "{INDENT_CHARACTER:>indent$}",
+            indent = 3 * INDENT_WIDTH
+        )
+        .and_then(|_| stdout.write_all(symbol_xml))
+        .and_then(|_| writeln!(stdout))
+        .expect("failed printing to stdout");
+    }
+
+    pub fn print_candidate(candidate: &Candidate) {
+        let page = candidate
+            .page
+            .map_or(String::new(), |page| format!(" page='{page}'"));
+
+        Self::print_xml(
+            format!(
+                "<candidate kind='{}' region='{}' confidence='{:.2}'{page}/>",
+                candidate.kind, candidate.region, candidate.confidence
+            ),
+            2,
+        );
     }
 }
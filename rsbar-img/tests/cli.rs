@@ -7,9 +7,79 @@ use predicates::prelude::*;
 
 const TEST_BAR_CODE_PATH: &str = "tests/images/qr-code.png";
 const TEST_BAR_CODE_CONTENT: &str = "QR-Code:https://github.com/mchehab/zbar\n";
+const TEST_BINARY_BAR_CODE_PATH: &str = "tests/images/qr-code-binary.png";
+const TEST_HIGH_BYTES_BAR_CODE_PATH: &str = "tests/images/qr-code-high-bytes.png";
+const TEST_CDATA_END_BAR_CODE_PATH: &str = "tests/images/qr-code-cdata.png";
+const TEST_CONTROL_CHARS_BAR_CODE_PATH: &str = "tests/images/qr-code-control-chars.png";
 const TEST_MULTIPLE_BAR_CODE_PATH: &str = "tests/images/multiple.png";
 const TEST_MULTIPLE_BAR_CODE_CONTENT: &str = "EAN-13:9789876543217\nCodabar:A9876543210B\n";
 const TEST_NO_BAR_CODE_PATH: &str = "tests/images/no-code.png";
+const TEST_TRANSPARENT_BAR_CODE_PATH: &str = "tests/images/qr-code-transparent.png";
+const TEST_16_BIT_BAR_CODE_PATH: &str = "tests/images/qr-code-16bit.png";
+const TEST_EXIF_ROTATED_BAR_CODE_PATH: &str = "tests/images/qr-code-exif-rotated.jpg";
+const TEST_INVERTED_BAR_CODE_PATH: &str = "tests/images/qr-code-inverted.png";
+const TEST_HALFTONE_BAR_CODE_PATH: &str = "tests/images/qr-code-halftone.png";
+const TEST_COLORED_BAR_CODE_PATH: &str = "tests/images/qr-code-blue-on-green.png";
+const TEST_COLORED_PDF_BAR_CODE_PATH: &str = "tests/images/qr-code-blue-on-green.pdf";
+const TEST_MIXED_INVERTED_BAR_CODE_PATH: &str = "tests/images/mixed-inverted.png";
+const TEST_MIRRORED_BAR_CODE_PATH: &str = "tests/images/qr-code-mirrored.png";
+const TEST_ROTATED_BAR_CODE_PATH: &str = "tests/images/bar-code-rotated.png";
+const TEST_LOW_CONTRAST_BAR_CODE_PATH: &str = "tests/images/bar-code-low-contrast.png";
+const TEST_DAMAGED_BAR_CODE_PATH: &str = "tests/images/qr-code-damaged.png";
+const TEST_PERSPECTIVE_BAR_CODE_PATH: &str = "tests/images/bar-code-perspective.png";
+const TEST_PDF417_BAR_CODE_PATH: &str = "tests/images/pdf417.png";
+const TEST_PDF417_BAR_CODE_CONTENT: &str = "PDF417:PDF417 Test4006381333931~rsbar~\n";
+const TEST_DAMAGED_PDF417_BAR_CODE_PATH: &str = "tests/images/pdf417-damaged.png";
+const TEST_DATAMATRIX_BAR_CODE_PATH: &str = "tests/images/datamatrix.png";
+const TEST_DATAMATRIX_BAR_CODE_CONTENT: &str = "DataMatrix:rsbar DATA MATRIX 4006381333931\n";
+const TEST_DAMAGED_DATAMATRIX_BAR_CODE_PATH: &str = "tests/images/datamatrix-damaged.png";
+const TEST_ROTATED_DATAMATRIX_BAR_CODE_PATH: &str = "tests/images/datamatrix-rotated.png";
+const TEST_GS1_DATAMATRIX_BAR_CODE_PATH: &str = "tests/images/datamatrix-gs1.png";
+const TEST_AZTEC_BAR_CODE_PATH: &str = "tests/images/aztec.png";
+const TEST_AZTEC_BAR_CODE_CONTENT: &str = "Aztec:rsbar Aztec Code 4006381333931\n";
+const TEST_DAMAGED_AZTEC_BAR_CODE_PATH: &str = "tests/images/aztec-damaged.png";
+const TEST_ROTATED_AZTEC_BAR_CODE_PATH: &str = "tests/images/aztec-rotated.png";
+const TEST_GS1_AZTEC_BAR_CODE_PATH: &str = "tests/images/aztec-gs1.png";
+const TEST_MICRO_QR_CODE_PATH: &str = "tests/images/micro-qr-code.png";
+const TEST_MICRO_QR_CODE_CONTENT: &str = "Micro-QR-Code:rsbar Micro QR\n";
+const TEST_DAMAGED_MICRO_QR_CODE_PATH: &str = "tests/images/micro-qr-code-damaged.png";
+const TEST_ROTATED_MICRO_QR_CODE_PATH: &str = "tests/images/micro-qr-code-rotated.png";
+const TEST_RMQR_CODE_PATH: &str = "tests/images/rmqr.png";
+const TEST_RMQR_CODE_CONTENT: &str = "rMQR-Code:rsbar rMQR Code 4006381333931\n";
+const TEST_DAMAGED_RMQR_CODE_PATH: &str = "tests/images/rmqr-damaged.png";
+const TEST_ROTATED_RMQR_CODE_PATH: &str = "tests/images/rmqr-rotated.png";
+const TEST_GS1_RMQR_CODE_PATH: &str = "tests/images/rmqr-gs1.png";
+const TEST_CODE128_GS1_CODE_SET_SWITCH_PATH: &str = "tests/images/code128-gs1-code-set-switch.png";
+const TEST_CODE128_AIM_PATH: &str = "tests/images/code128-aim.png";
+const TEST_CODE128_EXTENDED_PATH: &str = "tests/images/code128-extended.png";
+const TEST_CODE128_EXTENDED_LATCH_PATH: &str = "tests/images/code128-extended-latch.png";
+const TEST_CODE128_APPEND_FIRST_PATH: &str = "tests/images/code128-append-1.png";
+const TEST_CODE128_APPEND_LAST_PATH: &str = "tests/images/code128-append-2.png";
+const TEST_CODE128_READER_INIT_PATH: &str = "tests/images/code128-reader-init.png";
+const TEST_CODE39_PATH: &str = "tests/images/code39.png";
+const TEST_CODE39_BAD_CHECK_PATH: &str = "tests/images/code39-bad-check.png";
+const TEST_CODE39_FULL_ASCII_PATH: &str = "tests/images/code39-full-ascii.png";
+const TEST_I25_PATH: &str = "tests/images/i25.png";
+const TEST_I25_BAD_CHECK_PATH: &str = "tests/images/i25-bad-check.png";
+const TEST_ITF14_PATH: &str = "tests/images/itf14.png";
+const TEST_ITF14_BEARER_PATH: &str = "tests/images/itf14-bearer.png";
+const TEST_DATABAR_PATH: &str = "tests/images/databar.png";
+const TEST_DATABAR_STACKED_PATH: &str = "tests/images/databar-stacked.png";
+const TEST_DATABAR_STACKED_OMNI_PATH: &str = "tests/images/databar-stacked-omni.png";
+const TEST_DATABAR_LIMITED_PATH: &str = "tests/images/databar-limited.png";
+const TEST_ROTATED_DATABAR_LIMITED_PATH: &str = "tests/images/databar-limited-rotated.png";
+const TEST_DATABAR_EXPANDED_PATH: &str = "tests/images/databar-expanded.png";
+const TEST_DATABAR_EXPANDED_STACKED_PATH: &str = "tests/images/databar-expanded-stacked.png";
+const TEST_SA_FIRST_PART_PATH: &str = "tests/images/qr-code-sa-1.png";
+const TEST_SA_LAST_PARTS_PATH: &str = "tests/images/qr-code-sa-2-3.png";
+const TEST_SA_OUTER_PARTS_PATH: &str = "tests/images/qr-code-sa-1-3.png";
+const TEST_SA_MIDDLE_PART_PATH: &str = "tests/images/qr-code-sa-2.png";
+const TEST_SA_BAD_PARITY_PART_PATH: &str = "tests/images/qr-code-sa-2-bad-parity.png";
+const TEST_SA_CONTENT: &str = "QR-Code:rsbar reassembles structured append across pages\n";
+const TEST_PDF_BAR_CODE_PATH: &str = "tests/images/qr-codes.pdf";
+const TEST_PDF_JPEG_BAR_CODE_PATH: &str = "tests/images/qr-codes-dct.pdf";
+const TEST_RAW_NV12_BAR_CODE_PATH: &str = "tests/images/qr-code.nv12";
+const TEST_RAW_YUYV_PADDED_BAR_CODE_PATH: &str = "tests/images/qr-code.yuyv";
 
 #[test]
 fn should_fail_if_no_image_provided() -> Result<()> {
@@ -135,6 +205,49 @@ fn should_return_xml_for_scanned_image_when_passing_xml_flag() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn should_return_same_xml_symbol_as_zbar_for_binary_and_text_data() -> Result<()> {
+    // Expected output of zbar_symbol_xml() for the same images
+    for (path, symbol) in [
+        (
+            TEST_BINARY_BAR_CODE_PATH,
+            "<symbol type='QR-Code' quality='1' orientation='UP'><polygon points='+16,+16 +16,+147 +148,+148 +147,+16'/>\
+                <data format='base64' length='64'><![CDATA[\n\
+                AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8gISIjJCUmJygpKissLS4vMDEyMzQ1Njc4\n\
+                OTo7PD0+Pw==\n\
+                ]]></data></symbol>\n",
+        ),
+        (
+            TEST_HIGH_BYTES_BAR_CODE_PATH,
+            "<symbol type='QR-Code' quality='1' orientation='UP'><polygon points='+16,+16 +16,+100 +100,+100 +100,+16'/>\
+                <data format='base64' length='7'><![CDATA[\nAMKAw6nDvw==\n]]></data></symbol>\n",
+        ),
+        (
+            TEST_CDATA_END_BAR_CODE_PATH,
+            "<symbol type='QR-Code' quality='1' orientation='UP'><polygon points='+17,+17 +16,+115 +116,+116 +115,+16'/>\
+                <data format='base64' length='15'><![CDATA[\ncnNiYXIgXV0+IENEQVRB\n]]></data></symbol>\n",
+        ),
+        (
+            TEST_CONTROL_CHARS_BAR_CODE_PATH,
+            "<symbol type='QR-Code' quality='1' orientation='UP'><polygon points='+17,+17 +16,+115 +116,+116 +115,+16'/>\
+                <data><![CDATA[rsbar\tXML\r\ntext]]></data></symbol>\n",
+        ),
+    ] {
+        let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+        file.write_file(Path::new(path))?;
+
+        let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+        cmd.arg(file.path()).arg("--xml");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains(format!("            {symbol}")));
+    }
+
+    Ok(())
+}
+
 #[test]
 fn should_return_only_data_for_scanned_image_when_passing_raw_flag() -> Result<()> {
     let file = assert_fs::NamedTempFile::new("barcode.png")?;
@@ -191,9 +304,9 @@ fn should_fail_to_parse_invalid_config() -> Result<()> {
 
     let mut cmd = Command::cargo_bin("rsbar-img")?;
 
-    cmd.arg(file.path()).arg("--set=test");
+    cmd.arg(file.path()).arg("--set=bogus");
     cmd.assert().failure().stderr(predicate::str::contains(
-        "Failed to parse the config `test`",
+        "Failed to parse the config `bogus`",
     ));
 
     Ok(())
@@ -214,3 +327,2037 @@ fn should_fail_when_diabling_code_with_config_flag() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn should_return_type_and_data_for_scanned_raw_image() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.nv12")?;
+
+    file.write_file(Path::new(TEST_RAW_NV12_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .arg("--raw-format=NV12")
+        .arg("--size=100x100");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_type_and_data_for_scanned_raw_image_with_stride() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.yuyv")?;
+
+    file.write_file(Path::new(TEST_RAW_YUYV_PADDED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .arg("--raw-format=YUYV")
+        .arg("--size=100x100")
+        .arg("--stride=256");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_when_raw_image_is_smaller_than_its_size() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.nv12")?;
+
+    file.write_file(Path::new(TEST_RAW_NV12_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .arg("--raw-format=NV12")
+        .arg("--size=200x100");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("is too small"));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_unsupported_raw_format() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.nv12")?;
+
+    file.write_file(Path::new(TEST_RAW_NV12_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .arg("--raw-format=ABCD")
+        .arg("--size=100x100");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unsupported pixel format `ABCD`"));
+
+    Ok(())
+}
+
+#[test]
+fn should_composite_transparent_image_onto_white_background() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_TRANSPARENT_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_when_compositing_dark_code_onto_black_background() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_TRANSPARENT_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--background=black");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_type_and_data_for_low_contrast_16_bit_image() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_16_BIT_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_polygon_in_stored_coordinates_for_exif_rotated_image() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.jpg")?;
+
+    file.write_file(Path::new(TEST_EXIF_ROTATED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--xml");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "<symbol type='QR-Code' quality='1' orientation='UP'><polygon points='+1,+99 +98,+100 +100,+0 +0,+2'/>",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn should_only_scan_inside_crop() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MULTIPLE_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--crop=0,0,100%,30%");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("Codabar:A9876543210B\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_scan_each_region_of_interest() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MULTIPLE_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .arg("--roi=0,60%,100%,40%")
+        .arg("--roi=0,0,226,50")
+        .arg("--roi=0,0,100%,100%");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_MULTIPLE_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_when_regions_of_interest_are_outside_of_crop() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MULTIPLE_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .arg("--crop=0,60%,100%,40%")
+        .arg("--roi=0,0,100%,30%");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_only_locate_datamatrix_code_inside_crop_and_regions_of_interest() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATAMATRIX_BAR_CODE_PATH))?;
+
+    for region in ["--crop=0,0,5,5", "--roi=0,0,5,5"] {
+        let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+        cmd.arg(file.path()).arg(region);
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("No symbol detected"));
+    }
+    for region in ["--crop=4,4,96,96", "--roi=4,4,96,96"] {
+        let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+        cmd.arg(file.path()).arg("--xml").arg(region);
+        cmd.assert().success().stdout(predicate::str::contains(
+            "<polygon points='+8,+8 +8,+96 +96,+96 +96,+8'/>",
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn should_only_locate_aztec_code_inside_crop_and_regions_of_interest() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_AZTEC_BAR_CODE_PATH))?;
+
+    for region in ["--crop=0,0,5,5", "--roi=0,0,5,5"] {
+        let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+        cmd.arg(file.path()).arg(region);
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("No symbol detected"));
+    }
+    for region in ["--crop=4,4,96,96", "--roi=4,4,96,96"] {
+        let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+        cmd.arg(file.path()).arg("--xml").arg(region);
+        cmd.assert().success().stdout(predicate::str::contains(
+            "<polygon points='+8,+8 +8,+100 +100,+100 +100,+8'/>",
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_to_parse_invalid_region() -> Result<()> {
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(TEST_BAR_CODE_PATH).arg("--crop=10,10,50%");
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Expected a region like `x,y,width,height`",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_page_numbers_for_images_embedded_in_pdf() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.pdf")?;
+
+    file.write_file(Path::new(TEST_PDF_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert().success().stdout(predicate::eq(
+        "Page 1:QR-Code:https://github.com/mchehab/zbar\n\
+         Page 3:EAN-13:9789876543217\n\
+         Page 3:Codabar:A9876543210B\n\
+         Page 4:QR-Code:CCITT\n",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_xml_index_and_page_per_pdf_image() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.pdf")?;
+
+    file.write_file(Path::new(TEST_PDF_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--xml");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("<index num=\"0\" page=\"1\">"))
+        .stdout(predicate::str::contains("<index num=\"1\" page=\"3\">"))
+        .stdout(predicate::str::contains(
+            "<index num=\"2\" page=\"4\">\n            <symbol type='QR-Code' quality='1' orientation='UP'><polygon points='+16,+16 +16,+100 +100,+100 +100,+16'/><data><![CDATA[CCITT]]></data></symbol>",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn should_apply_decode_array_and_cmyk_color_space_of_jpeg_images_in_pdf() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.pdf")?;
+
+    file.write_file(Path::new(TEST_PDF_JPEG_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert().success().stdout(predicate::eq(
+        "Page 1:QR-Code:https://github.com/mchehab/zbar\n\
+         Page 1:QR-Code:https://github.com/mchehab/zbar\n",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_xml_index_per_image_on_same_pdf_page() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.pdf")?;
+
+    file.write_file(Path::new(TEST_PDF_JPEG_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--xml");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("<index num=\"0\" page=\"1\">"))
+        .stdout(predicate::str::contains("<index num=\"1\" page=\"1\">"));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_invalid_pdf() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.pdf")?;
+
+    file.write_str("%PDF-1.5\nnot really a PDF")?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to open PDF"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_type_and_data_for_inverted_image_when_preprocessing_with_invert() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_INVERTED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .arg("--preprocess=contrast:auto,sharpen,threshold:otsu,invert");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_polygon_in_original_coordinates_for_rotated_and_scaled_image() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .arg("--polygon")
+        .arg("--preprocess=rotate:90,scale:0.7");
+    cmd.assert().success().stdout(predicate::eq(
+        "QR-Code:2,1 0,98 100,100 99,1:https://github.com/mchehab/zbar\n",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_preprocess_raw_image() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.nv12")?;
+
+    file.write_file(Path::new(TEST_RAW_NV12_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .arg("--raw-format=NV12")
+        .arg("--size=100x100")
+        .arg("--preprocess=rotate:45");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_to_parse_unknown_preprocessing_step() -> Result<()> {
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(TEST_BAR_CODE_PATH)
+        .arg("--preprocess=rotate:90,blur");
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Unknown preprocessing step `blur`",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_report_fallback_when_trying_harder_on_inverted_image() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_INVERTED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--try-harder").arg("-vv");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_BAR_CODE_CONTENT))
+        .stderr(predicate::str::contains("found using fallback `invert`"));
+
+    Ok(())
+}
+
+#[test]
+fn should_scan_with_full_density_when_trying_harder() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .args(["-Sx-density=100", "-Sy-density=100", "--try-harder", "-vv"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_BAR_CODE_CONTENT))
+        .stderr(predicate::str::contains("found using fallback `density:1`"));
+
+    Ok(())
+}
+
+#[test]
+fn should_not_try_harder_by_default() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_INVERTED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_halftone_image_without_pyramid() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_HALFTONE_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_polygon_in_full_resolution_when_scanning_pyramid() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_HALFTONE_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--polygon").arg("--pyramid");
+    cmd.assert().success().stdout(predicate::eq(
+        "QR-Code:903,651 903,849 1099,849 1099,651:https://github.com/mchehab/zbar\n",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_not_duplicate_symbols_found_on_several_pyramid_levels() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MULTIPLE_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--pyramid=3");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_MULTIPLE_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_colored_image_with_low_luma_contrast() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_COLORED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_type_and_data_for_colored_image_when_scanning_rgb_channels() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_COLORED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--channels=rgb");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_type_and_data_for_colored_pdf_image_when_scanning_rgb_channels() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.pdf")?;
+
+    file.write_file(Path::new(TEST_COLORED_PDF_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--channels=rgb");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(format!("Page 1:{TEST_BAR_CODE_CONTENT}")));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_type_and_data_for_colored_image_when_scanning_color_projection() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_COLORED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--channels=projection");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_merge_symbols_found_in_several_channels() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MULTIPLE_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--channels=all");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_MULTIPLE_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_to_parse_unknown_channels() -> Result<()> {
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(TEST_BAR_CODE_PATH).arg("--channels=cmyk");
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Expected `luma`, `rgb`, `projection` or `all`, got `cmyk`",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_only_return_dark_on_light_symbols_by_default() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MIXED_INVERTED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--set=test-inverted");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_retry_inverted_for_configured_linear_symbologies() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MIXED_INVERTED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--set=ean13.test-inverted");
+    cmd.assert().success().stdout(predicate::eq(
+        "QR-Code:https://github.com/mchehab/zbar\nEAN-13:9789876543217\n",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_to_retry_inverted_for_2d_symbology() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--set=qrcode.test-inverted");
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Failed to set the config `qrcode.test-inverted` for the processor",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_mark_mirrored_qr_code_in_xml_output() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MIRRORED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--xml");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "<symbol type='QR-Code' quality='1' orientation='UP' modifiers='MIRRORED'>",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_not_mark_regular_qr_code_as_mirrored() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--xml");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("MIRRORED").not());
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_mirrored_qr_code_when_not_testing_mirrored() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MIRRORED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--set=test-mirrored=0");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_diagonal_bar_code_without_diagonal_scan_lines() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_ROTATED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_diagonal_bar_code_when_scanning_diagonals() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_ROTATED_BAR_CODE_PATH))?;
+
+    for angle in ["-Sdiagonal", "-Sdiagonal=15", "-Sdiagonal=45"] {
+        let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+        cmd.arg(file.path()).arg(angle);
+        cmd.assert().success().stdout("EAN-13:9789876543217\n");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn should_return_image_coordinates_of_diagonal_bar_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_ROTATED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Sdiagonal").arg("--xml");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "<polygon points='+241,+224 +175,+159 ",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_invalid_diagonal_angle() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_ROTATED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Sdiagonal=90");
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Failed to set the config `diagonal=90` for the processor",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_low_contrast_bar_code_with_default_edge_threshold() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_LOW_CONTRAST_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_low_contrast_bar_code_with_lowered_edge_threshold() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_LOW_CONTRAST_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Sedge-threshold=1");
+    cmd.assert()
+        .success()
+        .stdout(TEST_MULTIPLE_BAR_CODE_CONTENT);
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_out_of_range_edge_detection_parameters() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MULTIPLE_BAR_CODE_PATH))?;
+
+    for config in ["edge-threshold=0", "ewma-weight=101", "threshold-decay=0"] {
+        let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+        cmd.arg(file.path()).arg(format!("-S{config}"));
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "Failed to set the config `{config}` for the processor"
+            )));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_tilted_bar_code_without_rectification() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_PERSPECTIVE_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_tilted_bar_code_when_rectifying() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_PERSPECTIVE_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--rectify");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("EAN-13:9789876543217\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_image_coordinates_of_rectified_bar_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_PERSPECTIVE_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--rectify").arg("--polygon");
+    cmd.assert().success().stdout(predicate::str::starts_with(
+        "EAN-13:74,106 267,111 376,112 257,111 74,108 ",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_not_duplicate_symbols_found_before_rectifying() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MULTIPLE_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--rectify");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_MULTIPLE_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_damaged_qr_code_without_locating() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DAMAGED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_locate_damaged_qr_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DAMAGED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--locate");
+    cmd.assert()
+        .failure()
+        .stdout("Candidate:qrcode:1,0,99,100:1.00\n")
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_locate_undecodable_bars() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_NO_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--locate");
+    cmd.assert()
+        .failure()
+        .stdout("Candidate:linear:0,0,156,48:0.89\n");
+
+    Ok(())
+}
+
+#[test]
+fn should_report_located_regions_after_decoded_symbols() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MULTIPLE_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--locate");
+    cmd.assert().success().stdout(format!(
+        "{TEST_MULTIPLE_BAR_CODE_CONTENT}Candidate:linear:16,80,208,80:1.00\n\
+        Candidate:linear:32,0,160,32:0.98\n"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_print_located_regions_in_xml_output() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DAMAGED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--locate").arg("--xml");
+    cmd.assert().failure().stdout(predicate::str::contains(
+        "<candidate kind='qrcode' region='1,0,99,100' confidence='1.00'/>",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_only_locate_regions_inside_crop() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MULTIPLE_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .arg("--locate")
+        .arg("--crop=0,0,100%,30%");
+    cmd.assert()
+        .success()
+        .stdout("Codabar:A9876543210B\nCandidate:linear:32,0,160,32:0.98\n");
+
+    Ok(())
+}
+
+#[test]
+fn should_locate_regions_in_color_planes_when_scanning_channels() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_COLORED_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--locate").arg("--channels=rgb");
+    cmd.assert().success().stdout(format!(
+        "{TEST_BAR_CODE_CONTENT}Candidate:qrcode:1,0,99,100:1.00\n"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_type_and_data_for_pdf417_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_PDF417_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_PDF417_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_correct_errors_of_damaged_pdf417_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DAMAGED_PDF417_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_PDF417_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_polygon_of_pdf417_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_PDF417_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--xml");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "orientation='UP'><polygon points='+8,+9 +8,+43 +314,+43 +314,+9'/>",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_pdf417_code_when_disabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_PDF417_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--set=pdf417.disable");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_type_and_data_for_datamatrix_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATAMATRIX_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_DATAMATRIX_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_correct_errors_of_damaged_datamatrix_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DAMAGED_DATAMATRIX_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_DATAMATRIX_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_read_rotated_datamatrix_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_ROTATED_DATAMATRIX_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_DATAMATRIX_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_gs1_modifier_of_datamatrix_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_GS1_DATAMATRIX_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--xml");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "modifiers='GS1'><polygon points='+8,+8 +8,+80 +80,+80 +80,+8'/><data><![CDATA[01040063813339311012AB]]>",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_datamatrix_code_when_disabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATAMATRIX_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--set=datamatrix.disable");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_type_and_data_for_aztec_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_AZTEC_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_AZTEC_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_correct_errors_of_damaged_aztec_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DAMAGED_AZTEC_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_AZTEC_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_read_rotated_aztec_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_ROTATED_AZTEC_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_AZTEC_BAR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_gs1_modifier_of_aztec_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_GS1_AZTEC_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--xml");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "modifiers='GS1'><polygon points='+8,+8 +8,+84 +84,+84 +84,+8'/><data><![CDATA[01040063813339311012AB]]>",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_aztec_code_when_disabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_AZTEC_BAR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--set=aztec.disable");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_type_and_data_for_micro_qr_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MICRO_QR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_MICRO_QR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_correct_errors_of_damaged_micro_qr_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DAMAGED_MICRO_QR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_MICRO_QR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_read_rotated_micro_qr_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_ROTATED_MICRO_QR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_MICRO_QR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_polygon_of_micro_qr_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MICRO_QR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--xml");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "<symbol type='Micro-QR-Code' quality='1' orientation='UP'><polygon points='+8,+8 +8,+76 +76,+76 +76,+8'/>",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_micro_qr_code_when_disabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_MICRO_QR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--set=microqr.disable");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_type_and_data_for_rmqr_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_RMQR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_RMQR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_correct_errors_of_damaged_rmqr_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DAMAGED_RMQR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_RMQR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_read_rotated_rmqr_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_ROTATED_RMQR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_RMQR_CODE_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_gs1_modifier_of_rmqr_code() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_GS1_RMQR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--xml");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "modifiers='GS1'><polygon points='+8,+8 +8,+52 +316,+52 +316,+8'/><data><![CDATA[01040063813339311012AB]]>",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_rmqr_code_when_disabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_RMQR_CODE_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--set=rmqr.disable");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_reassemble_structured_append_across_images() -> Result<()> {
+    let first_file = assert_fs::NamedTempFile::new("first.png")?;
+    let last_file = assert_fs::NamedTempFile::new("last.png")?;
+
+    first_file.write_file(Path::new(TEST_SA_FIRST_PART_PATH))?;
+    last_file.write_file(Path::new(TEST_SA_LAST_PARTS_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(first_file.path()).arg(last_file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_SA_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_reassemble_structured_append_with_gap_in_image() -> Result<()> {
+    let outer_file = assert_fs::NamedTempFile::new("outer.png")?;
+    let middle_file = assert_fs::NamedTempFile::new("middle.png")?;
+
+    outer_file.write_file(Path::new(TEST_SA_OUTER_PARTS_PATH))?;
+    middle_file.write_file(Path::new(TEST_SA_MIDDLE_PART_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(middle_file.path())
+        .arg(outer_file.path())
+        .arg(middle_file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq(TEST_SA_CONTENT));
+
+    Ok(())
+}
+
+#[test]
+fn should_report_missing_structured_append_parts() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_SA_FIRST_PART_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-v");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(
+            "structured append QR-Code message with parity 0x56 is incomplete: missing part(s) 2, 3 of 3",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn should_drop_structured_append_with_parity_mismatch() -> Result<()> {
+    let outer_file = assert_fs::NamedTempFile::new("outer.png")?;
+    let middle_file = assert_fs::NamedTempFile::new("middle.png")?;
+
+    outer_file.write_file(Path::new(TEST_SA_OUTER_PARTS_PATH))?;
+    middle_file.write_file(Path::new(TEST_SA_BAD_PARITY_PART_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(outer_file.path()).arg(middle_file.path()).arg("-v");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("failed the parity check"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_gs1_fields_of_code128_after_code_set_switch() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_CODE128_GS1_CODE_SET_SWITCH_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("CODE-128:010400638133393110LOT42\x1d21S1\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_gs1_modifier_of_code128_after_code_set_switch() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_CODE128_GS1_CODE_SET_SWITCH_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--xml");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("modifiers='GS1'>"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_reader_init_modifier_of_code128() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_CODE128_READER_INIT_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--xml");
+    cmd.assert().success().stdout(
+        predicate::str::contains("modifiers='READER-INIT'>")
+            .and(predicate::str::contains("<data><![CDATA[INIT]]></data>")),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn should_return_aim_modifier_of_code128_with_fnc1_in_second_position() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_CODE128_AIM_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("--xml");
+    cmd.assert().success().stdout(
+        predicate::str::contains("modifiers='AIM'")
+            .and(predicate::str::contains("<data><![CDATA[011234]]></data>")),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn should_decode_extended_ascii_of_code128() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_CODE128_EXTENDED_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("CODE-128:Straße\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_decode_extended_ascii_latch_of_code128() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_CODE128_EXTENDED_LATCH_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("CODE-128:ÀÉÎ-ok\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_keep_code128_extended_ascii_as_latin1_when_passing_binary_config() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_CODE128_EXTENDED_LATCH_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Scode128.binary").arg("--xml");
+    cmd.assert()
+        .success()
+        .stdout(predicate::function(|stdout: &[u8]| {
+            stdout
+                .windows(18)
+                .any(|window| window == b"<![CDATA[\xc0\xc9\xce-ok]]>")
+        }));
+
+    Ok(())
+}
+
+#[test]
+fn should_append_code128_data_to_next_symbol() -> Result<()> {
+    let first_file = assert_fs::NamedTempFile::new("first.png")?;
+    let last_file = assert_fs::NamedTempFile::new("last.png")?;
+
+    first_file.write_file(Path::new(TEST_CODE128_APPEND_FIRST_PATH))?;
+    last_file.write_file(Path::new(TEST_CODE128_APPEND_LAST_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(first_file.path()).arg(last_file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("CODE-128:rsbar appends\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_report_code128_data_to_append_without_next_symbol() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_CODE128_APPEND_FIRST_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-v");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(
+            "CODE-128 data to append to the next symbol is incomplete: no symbol followed it",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_check_character_of_code39_as_data_by_default() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_CODE39_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("CODE-39:RSBAR-39M\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_strip_check_character_of_code39_when_enabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_CODE39_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Scode39.add-check");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("CODE-39:RSBAR-39\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_emit_check_character_of_code39_when_enabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_CODE39_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .arg("-Scode39.add-check")
+        .arg("-Scode39.emit-check");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("CODE-39:RSBAR-39M\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_code39_with_invalid_check_character() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_CODE39_BAD_CHECK_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Scode39.add-check");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_decode_full_ascii_code39_when_enabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_CODE39_FULL_ASCII_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .arg("-Scode39.ascii")
+        .arg("-Scode39.add-check");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("CODE-39:rsbar Code39!\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_full_ascii_escapes_of_code39_by_default() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_CODE39_FULL_ASCII_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("CODE-39:+R+S+B+A+R C+O+D+E39/AZ\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_return_check_digit_of_i25_as_data_by_default() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_I25_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("I2/5:12345670\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_strip_check_digit_of_i25_when_enabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_I25_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Si25.add-check");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("I2/5:1234567\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_emit_check_digit_of_i25_when_enabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_I25_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .arg("-Si25.add-check")
+        .arg("-Si25.emit-check");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("I2/5:12345670\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_i25_with_invalid_check_digit() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_I25_BAD_CHECK_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Si25.add-check");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_decode_itf14_when_enabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_ITF14_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Sitf14.enable");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("ITF-14:15400141288763\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_i25_of_other_length_in_itf14_mode() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_I25_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path())
+        .arg("-Si25.disable")
+        .arg("-Sitf14.enable");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_decode_itf14_with_bearer_bars_and_short_quiet_zone() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_ITF14_BEARER_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Sitf14.enable");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("ITF-14:15400141288763\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_decode_databar() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATABAR_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("DataBar:0109501101530003\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_decode_databar_stacked() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATABAR_STACKED_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("DataBar-Stk:0109501101530003\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_decode_databar_stacked_omni() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATABAR_STACKED_OMNI_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("DataBar-Stk-Omni:0109501101530003\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_databar_stacked_when_disabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATABAR_STACKED_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Sdatabar-stk.disable");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_databar_stacked_omni_when_disabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATABAR_STACKED_OMNI_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Sdatabar-stk-omni.disable");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_databar_stacked_omni_when_disabled_by_short_name() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATABAR_STACKED_OMNI_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Sdb-stk-omni.disable");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_databar_limited_when_disabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATABAR_LIMITED_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Sdatabar-ltd.disable");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_fail_for_databar_expanded_stacked_when_disabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATABAR_EXPANDED_STACKED_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Sdatabar-exp-stk.disable");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No symbol detected"));
+
+    Ok(())
+}
+
+#[test]
+fn should_decode_databar_limited() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATABAR_LIMITED_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("DataBar-Ltd:0115012345678907\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_decode_rotated_databar_limited() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_ROTATED_DATABAR_LIMITED_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("DataBar-Ltd:0115012345678907\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_strip_check_digit_of_databar_limited_when_disabled() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATABAR_LIMITED_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path()).arg("-Sdatabar-ltd.emit-check=0");
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("DataBar-Ltd:011501234567890\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_decode_databar_expanded() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATABAR_EXPANDED_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("DataBar-Exp:0109501101530003\n"));
+
+    Ok(())
+}
+
+#[test]
+fn should_decode_databar_expanded_stacked() -> Result<()> {
+    let file = assert_fs::NamedTempFile::new("barcode.png")?;
+
+    file.write_file(Path::new(TEST_DATABAR_EXPANDED_STACKED_PATH))?;
+
+    let mut cmd = Command::cargo_bin("rsbar-img")?;
+
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("DataBar-Exp-Stk:0109501101530003\n"));
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+#ifndef _AZTEC_H_
+#define _AZTEC_H_
+
+#include <zbar.h>
+
+typedef struct az_reader az_reader;
+
+az_reader *_zbar_az_create(void);
+void _zbar_az_destroy(az_reader *reader);
+void _zbar_az_reset(az_reader *reader);
+
+int _zbar_az_new_config(az_reader *reader, unsigned config);
+int _zbar_az_decode(az_reader *reader, zbar_image_scanner_t *iscn,
+		    zbar_image_t *img);
+
+#endif
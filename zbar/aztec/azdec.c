@@ -0,0 +1,609 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+
+/* Aztec symbol detection: the bullseye is found by scanning the rows of the
+ * binarized image for its alternating rings, the light rings around the
+ * center give its orientation and module size, then the orientation
+ * patterns and the mode message are read, and the layers sampled
+ */
+
+#include "config.h"
+
+#include <math.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "aztec.h"
+#include "binimage.h"
+#include "azdec.h"
+#include "image.h"
+#include "img_scanner.h"
+#include "symbol.h"
+
+/* smallest module of a bullseye candidate, in pixels */
+#define AZ_MIN_MODULE 1
+
+/* tolerance of the bullseye run widths, relative to their mean */
+#define AZ_RUN_TOLERANCE 0.5
+
+/* tolerance of the size of a bullseye ring, relative to its side */
+#define AZ_RING_TOLERANCE 0.15
+
+/* most bullseye modules sampled wrong */
+#define AZ_MAX_CORE_ERRORS 0.1
+
+/* most orientation pattern modules sampled wrong */
+#define AZ_MAX_ORIENT_ERRORS 1
+
+/* radius of the mode message ring of compact and full range symbols */
+#define AZ_COMPACT_RING 5
+#define AZ_FULL_RING	7
+
+struct az_reader {
+    int enabled;
+};
+
+/* a located bullseye: its center and one module along each symbol axis,
+ * x rightwards and y downwards for a symbol read upright
+ */
+typedef struct az_core {
+    bin_point c, u, v;
+    int compact;
+} az_core;
+
+/* a rectangle bounding a ring */
+typedef struct az_rect {
+    bin_point c, u;	 /* center and unit vector along the width */
+    double width, height;
+} az_rect;
+
+/* a ring of the bullseye */
+typedef struct az_ring {
+    az_rect rect;
+    bin_point centroid;
+    int area;
+} az_ring;
+
+az_reader *_zbar_az_create(void)
+{
+    az_reader *reader = malloc(sizeof(az_reader));
+    if (reader)
+	reader->enabled = 1;
+    return (reader);
+}
+
+void _zbar_az_destroy(az_reader *reader)
+{
+    free(reader);
+}
+
+void _zbar_az_reset(az_reader *reader)
+{
+    reader->enabled = 1;
+}
+
+int _zbar_az_new_config(az_reader *reader, unsigned config)
+{
+    reader->enabled = (config >> ZBAR_CFG_ENABLE) & 1;
+    return (0);
+}
+
+/* check runs of similar widths, returns their mean or 0 */
+static double check_runs(const int *runs, int n)
+{
+    double mean = 0;
+    int i;
+    for (i = 0; i < n; i++)
+	mean += runs[i];
+    mean /= n;
+    if (mean < AZ_MIN_MODULE)
+	return (0);
+    for (i = 0; i < n; i++)
+	if (fabs(runs[i] - mean) > AZ_RUN_TOLERANCE * mean)
+	    return (0);
+    return (mean);
+}
+
+/* measure the three innermost rings through a dark center pixel along a
+ * direction, returns the module size and updates the center or returns 0
+ */
+static double cross_check(const bin_image *img, int *x, int *y, int dx,
+			  int dy)
+{
+    int runs[5], ends[2], side, i;
+    double module;
+    for (side = 0; side < 2; side++) {
+	int sx = (side) ? dx : -dx, sy = (side) ? dy : -dy;
+	int px = *x, py = *y;
+	/* the center, then a light and a dark ring */
+	for (i = 0; i < 3; i++) {
+	    int n = 0;
+	    while (px >= 0 && py >= 0 && px < img->width && py < img->height &&
+		   pixel(img, px, py) == !(i & 1)) {
+		px += sx;
+		py += sy;
+		n++;
+	    }
+	    if (!n)
+		return (0);
+	    if (!i)
+		ends[side] = n;
+	    else
+		runs[(side) ? 2 + i : 2 - i] = n;
+	}
+    }
+    runs[2] = ends[0] + ends[1] - 1;
+    module  = check_runs(runs, 5);
+    if (module) {
+	*x += dx * (ends[1] - ends[0]) / 2;
+	*y += dy * (ends[1] - ends[0]) / 2;
+    }
+    return (module);
+}
+
+/* the smallest rectangle bounding a convex hull, along one of its edges */
+static int fit_rect(const bin_point *hull, int nhull, az_rect *rect)
+{
+    double best = HUGE_VAL;
+    int i, j;
+    for (i = 0; i < nhull; i++) {
+	bin_point e = pt_sub(hull[(i + 1) % nhull], hull[i]), u;
+	double umin = HUGE_VAL, umax = -HUGE_VAL, vmin = HUGE_VAL,
+	       vmax = -HUGE_VAL, l = pt_len(e);
+	if (l < 1)
+	    continue;
+	u.x = e.x / l;
+	u.y = e.y / l;
+	for (j = 0; j < nhull; j++) {
+	    double su = hull[j].x * u.x + hull[j].y * u.y;
+	    double sv = hull[j].y * u.x - hull[j].x * u.y;
+	    umin      = (su < umin) ? su : umin;
+	    umax      = (su > umax) ? su : umax;
+	    vmin      = (sv < vmin) ? sv : vmin;
+	    vmax      = (sv > vmax) ? sv : vmax;
+	}
+	if ((umax - umin) * (vmax - vmin) >= best)
+	    continue;
+	best	     = (umax - umin) * (vmax - vmin);
+	rect->u	     = u;
+	rect->width  = umax - umin;
+	rect->height = vmax - vmin;
+	/* back from the rotated frame, v being (-u.y, u.x) */
+	rect->c.x = (umin + umax) / 2 * u.x - (vmin + vmax) / 2 * u.y;
+	rect->c.y = (umin + umax) / 2 * u.y + (vmin + vmax) / 2 * u.x;
+    }
+    return (best < HUGE_VAL) ? 0 : -1;
+}
+
+/* fit the square ring of the bullseye through a pixel, of the given side
+ * in modules around a center
+ */
+static int fit_ring(bin_image *img, bin_point seed, bin_point c, int side,
+		    double mmin, double mmax, az_ring *ring)
+{
+    int x = (int)floor(seed.x), y = (int)floor(seed.y), n;
+    az_rect *rect = &ring->rect;
+    double s;
+
+    if (x < 0 || y < 0 || x >= img->width || y >= img->height)
+	return (-1);
+    n = _zbar_bin_trace(img, x, y, 0, (size_t)(side * side * mmax * mmax));
+    /* components may be traced again for other candidates */
+    _zbar_bin_unmark(img);
+    if (n || img->border)
+	return (-1);
+    ring->area	     = (int)img->ncomp;
+    ring->centroid.x = img->sumx / ring->area;
+    ring->centroid.y = img->sumy / ring->area;
+
+    n = _zbar_bin_hull(img);
+    if (n < 3 || fit_rect(img->hull, n, rect))
+	return (-1);
+
+    s = (rect->width + rect->height) / 2;
+    if (fabs(rect->width - rect->height) > AZ_RING_TOLERANCE * s ||
+	s < (1 - AZ_RING_TOLERANCE) * side * mmin ||
+	s > (1 + AZ_RING_TOLERANCE) * side * mmax ||
+	pt_len(pt_sub(ring->centroid, c)) > mmax)
+	return (-1);
+    return (0);
+}
+
+/* fit a dark ring of the bullseye and the light one around it.
+ * their areas give the module size, the binarization shrinking one as
+ * much as it grows the other, and their hulls the orientation
+ */
+static int fit_rings(bin_image *img, bin_point dark, bin_point light,
+		     bin_point c, int side, double mmin, double mmax,
+		     az_core *core)
+{
+    az_ring in = { { { 0, 0 } } }, out = { { { 0, 0 } } };
+    double s;
+
+    if (fit_ring(img, dark, c, side, mmin, mmax, &in) ||
+	fit_ring(img, light, c, side + 2, mmin, mmax, &out))
+	return (-1);
+    s = sqrt((in.area / (4. * side - 4) + out.area / (4. * side + 4)) / 2);
+    core->c   = pt_add(in.centroid, pt_sub(out.centroid, in.centroid), 0.5);
+    core->u.x = out.rect.u.x * s;
+    core->u.y = out.rect.u.y * s;
+    core->v.x = -core->u.y;
+    core->v.y = core->u.x;
+    return (0);
+}
+
+/* count the bullseye modules sampled wrong, out to the given radius */
+static int core_errors(const bin_image *img, const az_core *core, int radius)
+{
+    int i, j, errors = 0;
+    for (i = -radius; i <= radius; i++)
+	for (j = -radius; j <= radius; j++) {
+	    int d = (abs(i) > abs(j)) ? abs(i) : abs(j);
+	    bin_point p = pt_add(pt_add(core->c, core->u, i), core->v, j);
+	    errors += is_dark(img, p) != !(d & 1);
+	}
+    return (errors);
+}
+
+/* locate the bullseye of a candidate center */
+static int find_core(bin_image *img, bin_point c, double module, az_core *core)
+{
+    bin_point dark = c, light = c;
+    int x = (int)floor(c.x), y = (int)floor(c.y), i;
+
+    /* the second dark ring and the light one around it */
+    for (i = 0; i < 3; i++) {
+	while (x < img->width && pixel(img, x, y) == !(i & 1))
+	    x++;
+	if (i == 1)
+	    dark.x = x;
+    }
+    light.x = x;
+    /* the runs are longer across the diagonal of the rings */
+    if (x >= img->width ||
+	fit_rings(img, dark, light, c, 5, module / M_SQRT2, module, core))
+	return (-1);
+
+    /* full range symbols have a third of each */
+    dark	  = pt_add(core->c, core->u, 4);
+    light	  = pt_add(core->c, core->u, 5);
+    core->compact = 1;
+    module	  = pt_len(core->u);
+    if (!fit_rings(img, dark, light, core->c, 9, module, module, core))
+	core->compact = 0;
+
+    i = (core->compact) ? 4 : 6;
+    if (core_errors(img, core, i) > AZ_MAX_CORE_ERRORS * (2 * i + 1) * (2 * i + 1))
+	return (-1);
+    return (0);
+}
+
+/* image position of a module, in symbol coordinates from the center, the
+ * symbol being rotated by rot quarter turns and maybe mirrored
+ */
+static bin_point module_pos(const az_core *core, int rot, int mirror,
+			    double x, double y)
+{
+    double t;
+    if (mirror) {
+	t = x;
+	x = y;
+	y = t;
+    }
+    for (; rot > 0; rot--) {
+	t = x;
+	x = -y;
+	y = t;
+    }
+    return (pt_add(pt_add(core->c, core->u, x), core->v, y));
+}
+
+static inline int sample(const bin_image *img, const az_core *core, int rot,
+			 int mirror, int x, int y)
+{
+    return (is_dark(img, module_pos(core, rot, mirror, x, y)));
+}
+
+/* count the orientation pattern modules at the corners of the mode message
+ * ring sampled wrong
+ */
+static int orient_errors(const bin_image *img, const az_core *core, int rot,
+			 int mirror, int r)
+{
+    /* the sides of the corner, then an offset from it and the color */
+    static const signed char pattern[12][5] = {
+	/* upper left */
+	{ -1, -1, 0, 0, 1 },
+	{ -1, -1, 1, 0, 1 },
+	{ -1, -1, 0, 1, 1 },
+	/* upper right */
+	{ 1, -1, 0, 0, 1 },
+	{ 1, -1, 0, 1, 1 },
+	{ 1, -1, -1, 0, 0 },
+	/* lower right */
+	{ 1, 1, 0, 0, 0 },
+	{ 1, 1, 0, -1, 1 },
+	{ 1, 1, -1, 0, 0 },
+	/* lower left */
+	{ -1, 1, 0, 0, 0 },
+	{ -1, 1, 1, 0, 0 },
+	{ -1, 1, 0, -1, 0 },
+    };
+    int i, errors = 0;
+    for (i = 0; i < 12; i++) {
+	const signed char *p = pattern[i];
+	errors += sample(img, core, rot, mirror, p[0] * r + p[2],
+			 p[1] * r + p[3]) != p[4];
+    }
+    return (errors);
+}
+
+/* read and decode the mode message around the bullseye */
+static int read_mode(const bin_image *img, const az_core *core, int rot,
+		     int mirror, az_mode *mode)
+{
+    unsigned char bits[40];
+    int i;
+    if (core->compact)
+	for (i = 0; i < 7; i++) {
+	    int o = i - 3, r = AZ_COMPACT_RING;
+	    bits[i]	 = sample(img, core, rot, mirror, o, -r);
+	    bits[i + 7]	 = sample(img, core, rot, mirror, r, o);
+	    bits[20 - i] = sample(img, core, rot, mirror, o, r);
+	    bits[27 - i] = sample(img, core, rot, mirror, -r, o);
+	}
+    else
+	for (i = 0; i < 10; i++) {
+	    /* skipping the reference grid line through the center */
+	    int o = i - 5 + i / 5, r = AZ_FULL_RING;
+	    bits[i]	 = sample(img, core, rot, mirror, o, -r);
+	    bits[i + 10] = sample(img, core, rot, mirror, r, o);
+	    bits[29 - i] = sample(img, core, rot, mirror, o, r);
+	    bits[39 - i] = sample(img, core, rot, mirror, -r, o);
+	}
+    return (_zbar_az_decode_mode(bits, core->compact, mode));
+}
+
+/* count the reference grid modules of a full range symbol sampled right */
+static int grid_matches(const bin_image *img, const az_core *core, int rot,
+			int mirror, int size)
+{
+    int half = size / 2, l, k, matches = 0;
+    for (l = -(half / 16) * 16; l <= half; l += 16)
+	for (k = -half; k <= half; k++) {
+	    /* the bullseye and the mode message are not part of the grid */
+	    if (abs(l) <= AZ_FULL_RING && abs(k) <= AZ_FULL_RING)
+		continue;
+	    matches += sample(img, core, rot, mirror, k, l) == !(k & 1);
+	    matches += sample(img, core, rot, mirror, l, k) == !(k & 1);
+	}
+    return (matches);
+}
+
+/* adjust the module size and orientation to the reference grid of a full
+ * range symbol, the bullseye being too small to extrapolate far from it
+ */
+static void refine_grid(const bin_image *img, az_core *core, int rot,
+			int mirror, int size)
+{
+    az_core best = *core;
+    int most = grid_matches(img, core, rot, mirror, size), i, j;
+    for (i = -4; i <= 4; i++)
+	for (j = -6; j <= 6; j++) {
+	    double a = 0.005 * i, s = 1 + 0.005 * j;
+	    az_core t = *core;
+	    int n;
+	    t.u.x = s * (core->u.x * cos(a) - core->u.y * sin(a));
+	    t.u.y = s * (core->u.x * sin(a) + core->u.y * cos(a));
+	    t.v.x = -t.u.y;
+	    t.v.y = t.u.x;
+	    n	  = grid_matches(img, &t, rot, mirror, size);
+	    if (n > most) {
+		most = n;
+		best = t;
+	    }
+	}
+    *core = best;
+}
+
+/* sample and decode the symbol around a bullseye */
+static int decode_core(const bin_image *img, az_core *core, int *rot,
+		       int *mirror, int *size, unsigned char **data,
+		       unsigned *modifiers)
+{
+    int r = (core->compact) ? AZ_COMPACT_RING : AZ_FULL_RING;
+    for (*mirror = 0; *mirror < 2; (*mirror)++)
+	for (*rot = 0; *rot < 4; (*rot)++) {
+	    unsigned char *modules;
+	    az_mode mode;
+	    int x, y, len;
+	    if (orient_errors(img, core, *rot, *mirror, r) >
+		    AZ_MAX_ORIENT_ERRORS ||
+		read_mode(img, core, *rot, *mirror, &mode))
+		continue;
+
+	    *size = _zbar_az_matrix_size(mode.compact, mode.layers);
+	    if (!core->compact)
+		refine_grid(img, core, *rot, *mirror, *size);
+	    modules = malloc(*size * *size);
+	    if (!modules)
+		return (-1);
+	    for (y = 0; y < *size; y++)
+		for (x = 0; x < *size; x++)
+		    modules[y * *size + x] = sample(img, core, *rot, *mirror,
+						    x - *size / 2, y - *size / 2);
+	    len = _zbar_az_decode_matrix(&mode, modules, data, modifiers);
+	    free(modules);
+	    if (len >= 0)
+		return (len);
+	}
+    return (-1);
+}
+
+static void add_symbol(zbar_image_scanner_t *iscn, const bin_image *img,
+		       const az_core *core, int rot, int mirror, int size,
+		       const unsigned char *data, int len, unsigned modifiers)
+{
+    /* the upper left, lower left, lower right and upper right corners */
+    static const signed char corners[4][2] = {
+	{ -1, -1 }, { -1, 1 }, { 1, 1 }, { 1, -1 }
+    };
+    zbar_symbol_t *sym = _zbar_image_scanner_alloc_sym(iscn, ZBAR_AZTEC,
+						       len + 1);
+    bin_point across = pt_sub(module_pos(core, rot, mirror, 1, 0),
+			      module_pos(core, rot, mirror, 0, 0));
+    int i;
+
+    memcpy(sym->data, data, len + 1);
+    sym->modifiers = modifiers;
+    for (i = 0; i < 4; i++) {
+	bin_point p = module_pos(core, rot, mirror, corners[i][0] * size / 2.,
+				 corners[i][1] * size / 2.);
+	_zbar_bin_add_point(img, sym, p);
+    }
+
+    /* symbols read along their upper side */
+    if (fabs(across.x) >= fabs(across.y))
+	sym->orient = (across.x > 0) ? ZBAR_ORIENT_UP : ZBAR_ORIENT_DOWN;
+    else
+	sym->orient = (across.y > 0) ? ZBAR_ORIENT_RIGHT : ZBAR_ORIENT_LEFT;
+
+    _zbar_image_scanner_add_sym(iscn, sym);
+}
+
+int _zbar_az_decode(az_reader *reader, zbar_image_scanner_t *iscn,
+		    zbar_image_t *img)
+{
+    bin_image aimg;
+    int w = img->crop_w, h = img->crop_h, nsyms = 0, ntried = 0, i, y;
+    int *edges = NULL, nalloc = 0, *nfound = NULL;
+    unsigned char **found = NULL;
+    bin_point *tried = NULL;
+
+    if (!reader->enabled || w < 2 * AZ_COMPACT_RING + 1 ||
+	h < 2 * AZ_COMPACT_RING + 1)
+	return (0);
+    if (_zbar_bin_init(&aimg, img))
+	return (0);
+    edges = malloc((w + 1) * sizeof(*edges));
+    if (!edges)
+	goto done;
+
+    for (y = 0; y < h; y++) {
+	int nedges = 0, x, k;
+
+	/* the ends of the runs of the row */
+	edges[nedges++] = 0;
+	for (x = 1; x < w; x++)
+	    if (pixel(&aimg, x, y) != pixel(&aimg, x - 1, y))
+		edges[nedges++] = x;
+	edges[nedges++] = w;
+
+	/* the center of the bullseye and the two rings around it */
+	for (k = 0; k + 5 < nedges; k++) {
+	    int runs[5], cx, cy = y, len, j;
+	    unsigned char *data = NULL;
+	    unsigned modifiers	= 0;
+	    int rot, mirror, size;
+	    double module;
+	    bin_point c;
+	    az_core core;
+
+	    if (!pixel(&aimg, edges[k], y))
+		continue;
+	    for (j = 0; j < 5; j++)
+		runs[j] = edges[k + j + 1] - edges[k + j];
+	    if (!check_runs(runs, 5))
+		continue;
+	    cx = (edges[k + 2] + edges[k + 3]) / 2;
+	    if (!cross_check(&aimg, &cx, &cy, 0, 1))
+		continue;
+	    module = cross_check(&aimg, &cx, &cy, 1, 0);
+	    if (!module)
+		continue;
+	    c.x = cx + 0.5;
+	    c.y = cy + 0.5;
+
+	    /* each bullseye is crossed by several rows, but a rotated one
+	     * also gives false candidates close to its center
+	     */
+	    for (j = 0; j < ntried; j++)
+		if (pt_len(pt_sub(tried[j], c)) < module)
+		    break;
+	    if (j < ntried)
+		continue;
+	    if (!(ntried & (ntried - 1))) {
+		bin_point *t =
+		    realloc(tried, (ntried ? 2 * ntried : 1) * sizeof(*t));
+		if (!t)
+		    goto done;
+		tried = t;
+	    }
+	    tried[ntried++] = c;
+
+	    if (find_core(&aimg, c, module, &core))
+		continue;
+	    len = decode_core(&aimg, &core, &rot, &mirror, &size, &data,
+			      &modifiers);
+	    if (len < 0)
+		continue;
+
+	    for (j = 0; j < nsyms; j++)
+		if (nfound[j] == len && !memcmp(found[j], data, len))
+		    break;
+	    if (j < nsyms) {
+		free(data);
+		continue;
+	    }
+	    if (nsyms >= nalloc) {
+		int n		   = nalloc ? 2 * nalloc : 4;
+		unsigned char **nf = realloc(found, n * sizeof(*found));
+		int *nl;
+		if (!nf) {
+		    free(data);
+		    goto done;
+		}
+		found = nf;
+		nl    = realloc(nfound, n * sizeof(*nfound));
+		if (!nl) {
+		    free(data);
+		    goto done;
+		}
+		nfound = nl;
+		nalloc = n;
+	    }
+	    add_symbol(iscn, &aimg, &core, rot, mirror, size, data, len,
+		       modifiers);
+	    found[nsyms]    = data;
+	    nfound[nsyms++] = len;
+	}
+    }
+
+done:
+    for (i = 0; i < nsyms; i++)
+	free(found[i]);
+    free(found);
+    free(nfound);
+    free(tried);
+    free(edges);
+    _zbar_bin_free(&aimg);
+    return (nsyms);
+}
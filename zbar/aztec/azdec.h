@@ -0,0 +1,63 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+#ifndef _AZTEC_AZDEC_H_
+#define _AZTEC_AZDEC_H_
+
+/* symbol parameters read from the mode message */
+typedef struct az_mode {
+    int compact;	 /* compact or full range symbol */
+    int layers;	 /* data layers around the core */
+    int ndata;	 /* data codewords */
+} az_mode;
+
+/* side of the symbol matrix, in modules */
+int _zbar_az_matrix_size(int compact, int layers);
+
+/* correct and decode the 28 (compact) or 40 (full range) mode message bits,
+ * read clockwise from the upper left corner.
+ * returns 0 on success or -1 if the mode message is invalid
+ */
+int _zbar_az_decode_mode(const unsigned char *bits, int compact,
+			 az_mode *mode);
+
+/* extract, correct and decode the data layers of a sampled symbol.
+ * modules holds the size * size module colors (non-zero for dark), the
+ * upper left one first.
+ * returns the number of bytes written to the newly allocated *data
+ * or -1 if the symbol can't be decoded
+ */
+int _zbar_az_decode_matrix(const az_mode *mode, const unsigned char *modules,
+			   unsigned char **data, unsigned *modifiers);
+
+/* correct the errors of n codewords of wordbits bits in place, the last
+ * necw of which are error correction codewords.
+ * returns the number of corrected codewords or -1 if uncorrectable
+ */
+int _zbar_az_rs_correct(unsigned *cw, int n, int necw, int wordbits);
+
+/* decode the bits of the data codewords, one per byte, into bytes.
+ * returns the number of bytes written to the newly allocated *data
+ * or -1 for invalid data
+ */
+int _zbar_az_decode_data(const unsigned char *bits, int nbits,
+			 unsigned char **data, unsigned *modifiers);
+
+#endif
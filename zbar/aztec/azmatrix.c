@@ -0,0 +1,199 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+
+/* Aztec symbol matrix: the mode message gives the size of the symbol, the
+ * codewords are read from the layers spiraling in from the outermost one,
+ * then error corrected and unstuffed
+ */
+
+#include "config.h"
+
+#include <stdlib.h>
+#include <string.h>
+
+#include "azdec.h"
+
+/* mode message codewords of 4 bits, data then error correction */
+#define AZ_COMPACT_MODE_DATA 2
+#define AZ_COMPACT_MODE_ECW  5
+#define AZ_FULL_MODE_DATA    4
+#define AZ_FULL_MODE_ECW     6
+
+/* most layers of compact and full range symbols */
+#define AZ_COMPACT_MAX_LAYERS 4
+#define AZ_FULL_MAX_LAYERS    32
+
+/* side of the symbol without the reference grid */
+static int base_size(int compact, int layers)
+{
+    return (((compact) ? 11 : 14) + 4 * layers);
+}
+
+int _zbar_az_matrix_size(int compact, int layers)
+{
+    int base = base_size(compact, layers);
+    if (compact)
+	return (base);
+    /* a reference grid line every 16 modules from the center */
+    return (base + 1 + 2 * ((base / 2 - 1) / 15));
+}
+
+/* codeword size of the data layers */
+static int word_bits(int layers)
+{
+    if (layers <= 2)
+	return (6);
+    if (layers <= 8)
+	return (8);
+    if (layers <= 22)
+	return (10);
+    return (12);
+}
+
+/* modules of the data layers */
+static int total_bits(int compact, int layers)
+{
+    return ((((compact) ? 88 : 112) + 16 * layers) * layers);
+}
+
+int _zbar_az_decode_mode(const unsigned char *bits, int compact,
+			 az_mode *mode)
+{
+    unsigned cw[AZ_FULL_MODE_DATA + AZ_FULL_MODE_ECW], v = 0;
+    int ndata = (compact) ? AZ_COMPACT_MODE_DATA : AZ_FULL_MODE_DATA;
+    int necw  = (compact) ? AZ_COMPACT_MODE_ECW : AZ_FULL_MODE_ECW;
+    int i, j, nbits;
+
+    for (i = 0; i < ndata + necw; i++) {
+	cw[i] = 0;
+	for (j = 0; j < 4; j++)
+	    cw[i] = cw[i] << 1 | !!bits[4 * i + j];
+    }
+    if (_zbar_az_rs_correct(cw, ndata + necw, necw, 4) < 0)
+	return (-1);
+    for (i = 0; i < ndata; i++)
+	v = v << 4 | cw[i];
+
+    /* the layers then the data codewords, both less one */
+    nbits	  = (compact) ? 6 : 11;
+    mode->compact = compact;
+    mode->layers  = (v >> nbits) + 1;
+    mode->ndata	  = (v & ((1 << nbits) - 1)) + 1;
+    if (mode->layers >
+	((compact) ? AZ_COMPACT_MAX_LAYERS : AZ_FULL_MAX_LAYERS))
+	return (-1);
+    if (mode->ndata >= total_bits(compact, mode->layers) /
+			   word_bits(mode->layers))
+	return (-1);
+    return (0);
+}
+
+/* read the modules of the data layers, from the outermost one */
+static int read_layers(const az_mode *mode, const unsigned char *modules,
+			unsigned char *bits)
+{
+    int base = base_size(mode->compact, mode->layers);
+    int size = _zbar_az_matrix_size(mode->compact, mode->layers);
+    int *map = malloc(base * sizeof(*map));
+    int i, j, k, n = 0;
+
+    if (!map)
+	return (-1);
+    /* matrix positions of the modules, skipping the reference grid */
+    if (mode->compact)
+	for (i = 0; i < base; i++)
+	    map[i] = i;
+    else
+	for (i = 0; i < base / 2; i++) {
+	    int offset		= i + i / 15;
+	    map[base / 2 - i - 1] = size / 2 - offset - 1;
+	    map[base / 2 + i]	= size / 2 + offset + 1;
+	}
+
+#define MODULE(x, y) (!!modules[map[y] * size + map[x]])
+    for (i = 0; i < mode->layers; i++) {
+	int len = 4 * (mode->layers - i) + ((mode->compact) ? 9 : 12);
+	int lo = 2 * i, hi = base - 1 - 2 * i;
+	/* the left, bottom, right and top sides, two modules wide */
+	for (j = 0; j < len; j++)
+	    for (k = 0; k < 2; k++) {
+		bits[n + 2 * j + k]	      = MODULE(lo + k, lo + j);
+		bits[n + 2 * (len + j) + k] = MODULE(lo + j, hi - k);
+		bits[n + 2 * (2 * len + j) + k] = MODULE(hi - k, hi - j);
+		bits[n + 2 * (3 * len + j) + k] = MODULE(hi - j, lo + k);
+	    }
+	n += 8 * len;
+    }
+#undef MODULE
+    free(map);
+    return (0);
+}
+
+int _zbar_az_decode_matrix(const az_mode *mode, const unsigned char *modules,
+			   unsigned char **data, unsigned *modifiers)
+{
+    int nbits = total_bits(mode->compact, mode->layers);
+    int wbits = word_bits(mode->layers), ncw = nbits / wbits;
+    unsigned mask = (1 << wbits) - 1, *cw;
+    unsigned char *bits, *msg;
+    int i, j, n = 0, len = -1;
+
+    bits = calloc(nbits, 1);
+    msg	 = malloc(mode->ndata * wbits);
+    cw	 = malloc(ncw * sizeof(*cw));
+    if (!bits || !msg || !cw)
+	goto done;
+
+    if (read_layers(mode, modules, bits))
+	goto done;
+    /* the first bits of the outermost layer are padding */
+    for (i = 0; i < ncw; i++) {
+	const unsigned char *b = bits + nbits % wbits + i * wbits;
+	cw[i]		       = 0;
+	for (j = 0; j < wbits; j++)
+	    cw[i] = cw[i] << 1 | b[j];
+    }
+    if (_zbar_az_rs_correct(cw, ncw, ncw - mode->ndata, wbits) < 0)
+	goto done;
+
+    /* a bit complementing the others was stuffed in codewords that would
+     * have been all zeros or all ones
+     */
+    for (i = 0; i < mode->ndata; i++) {
+	if (!cw[i] || cw[i] == mask)
+	    goto done;
+	if (cw[i] == 1 || cw[i] == mask - 1) {
+	    memset(msg + n, cw[i] > 1, wbits - 1);
+	    n += wbits - 1;
+	    continue;
+	}
+	for (j = wbits - 1; j >= 0; j--)
+	    msg[n++] = (cw[i] >> j) & 1;
+    }
+
+    len = _zbar_az_decode_data(msg, n, data, modifiers);
+
+done:
+    free(bits);
+    free(msg);
+    free(cw);
+    return (len);
+}
@@ -0,0 +1,240 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+
+/* Reed-Solomon error correction of Aztec symbols, over GF(16) for the mode
+ * message and GF(64), GF(256), GF(1024) or GF(4096) for the data layers,
+ * depending on the number of layers.
+ * The generator polynomial of a block with k error correction codewords is
+ *  (x - a)(x - a^2)...(x - a^k), the first codeword being the coefficient
+ *  of the highest power.
+ * Byte codewords are corrected by the QR Code decoder's implementation,
+ *  the other sizes by the one below.
+ */
+
+#include "config.h"
+
+#include <stdlib.h>
+#include <string.h>
+
+#include "azdec.h"
+#include "qrcode/rs.h"
+
+/* GF(256) generator polynomial x^8 + x^5 + x^3 + x^2 + 1, without its high
+ * bit, and the power of the first root of the code generator polynomials
+ */
+#define AZ_GF256_PPOLY 0x2D
+#define AZ_M0	       1
+
+typedef struct gf {
+    unsigned *exp; /* twice the multiplicative group, saving a modulo */
+    unsigned *log;
+    int size;	   /* number of non-zero elements */
+} gf;
+
+/* field generator polynomial for the codeword sizes */
+static unsigned gf_poly(int wordbits)
+{
+    switch (wordbits) {
+    case 4:
+	return (0x13);
+    case 6:
+	return (0x43);
+    case 10:
+	return (0x409);
+    case 12:
+	return (0x1069);
+    }
+    return (0);
+}
+
+static int gf_init(gf *f, int wordbits)
+{
+    unsigned poly = gf_poly(wordbits), x = 1;
+    int i;
+    if (!poly)
+	return (-1);
+    f->size = (1 << wordbits) - 1;
+    f->exp  = malloc(2 * f->size * sizeof(*f->exp));
+    f->log  = malloc((f->size + 1) * sizeof(*f->log));
+    if (!f->exp || !f->log)
+	return (-1);
+    for (i = 0; i < f->size; i++) {
+	f->exp[i] = f->exp[i + f->size] = x;
+	f->log[x]			 = i;
+	x <<= 1;
+	if (x >> wordbits)
+	    x ^= poly;
+    }
+    f->log[0] = 0;
+    return (0);
+}
+
+static inline unsigned gf_mul(const gf *f, unsigned a, unsigned b)
+{
+    if (!a || !b)
+	return (0);
+    return (f->exp[f->log[a] + f->log[b]]);
+}
+
+static inline unsigned gf_inv(const gf *f, unsigned a)
+{
+    return (f->exp[f->size - f->log[a]]);
+}
+
+/* generator to the power of e (any sign) */
+static inline unsigned gf_pow(const gf *f, long e)
+{
+    e %= f->size;
+    if (e < 0)
+	e += f->size;
+    return (f->exp[e]);
+}
+
+/* evaluate the polynomial with coefficients p[0] + p[1]x + ... at x */
+static unsigned poly_eval(const gf *f, const unsigned *p, int deg, unsigned x)
+{
+    unsigned y = 0;
+    int i;
+    for (i = deg; i >= 0; i--)
+	y = gf_mul(f, y, x) ^ p[i];
+    return (y);
+}
+
+/* compute the syndromes, returns non-zero if any of them is */
+static int calc_syndromes(const gf *f, const unsigned *cw, int n, int necw,
+			  unsigned *s)
+{
+    int i, j, nonzero = 0;
+    for (j = 0; j < necw; j++) {
+	unsigned x = gf_pow(f, j + 1), y = 0;
+	for (i = 0; i < n; i++)
+	    y = gf_mul(f, y, x) ^ cw[i];
+	s[j] = y;
+	nonzero |= y != 0;
+    }
+    return (nonzero);
+}
+
+/* Berlekamp-Massey, then Chien search and Forney's algorithm */
+static int correct(const gf *f, unsigned *cw, int n, int necw, unsigned *s)
+{
+    unsigned *lambda = s + necw, *b = lambda + necw + 1, *t = b + necw + 1;
+    unsigned *omega = t + necw + 1;
+    int i, j, r, l = 0, nfound = 0;
+
+    memset(lambda, 0, (necw + 1) * sizeof(*lambda));
+    lambda[0] = 1;
+    memcpy(b, lambda, (necw + 1) * sizeof(*b));
+    for (r = 0; r < necw; r++) {
+	unsigned delta = 0;
+	for (i = 0; i <= l && i <= r; i++)
+	    delta ^= gf_mul(f, lambda[i], s[r - i]);
+
+	/* b *= x */
+	memmove(b + 1, b, necw * sizeof(*b));
+	b[0] = 0;
+	if (!delta)
+	    continue;
+
+	for (i = 0; i <= necw; i++)
+	    t[i] = lambda[i] ^ gf_mul(f, delta, b[i]);
+	if (2 * l <= r) {
+	    unsigned inv = gf_inv(f, delta);
+	    l		 = r + 1 - l;
+	    for (i = 0; i <= necw; i++)
+		b[i] = gf_mul(f, lambda[i], inv);
+	}
+	memcpy(lambda, t, (necw + 1) * sizeof(*t));
+    }
+
+    if (2 * l > necw)
+	return (-1);
+
+    /* error evaluator: omega = s * lambda mod x^necw */
+    for (i = 0; i < necw; i++) {
+	omega[i] = 0;
+	for (j = 0; j <= i && j <= l; j++)
+	    omega[i] ^= gf_mul(f, lambda[j], s[i - j]);
+    }
+
+    for (i = 0; i < n; i++) {
+	unsigned xinv = gf_pow(f, -(long)(n - 1 - i)), den = 0;
+	if (poly_eval(f, lambda, l, xinv))
+	    continue;
+	nfound++;
+	/* formal derivative of lambda at xinv, only odd powers remain */
+	for (j = 1; j <= l; j += 2)
+	    den ^= gf_mul(f, lambda[j], gf_pow(f, -(long)(n - 1 - i) * (j - 1)));
+	if (!den)
+	    return (-1);
+	cw[i] ^= gf_mul(f, poly_eval(f, omega, necw - 1, xinv), gf_inv(f, den));
+    }
+
+    if (nfound != l || calc_syndromes(f, cw, n, necw, s))
+	return (-1);
+    return (nfound);
+}
+
+/* correct byte codewords with the GF(256) decoder shared with QR Code */
+static int correct_bytes(unsigned *cw, int n, int necw)
+{
+    unsigned char data[255];
+    rs_gf256 gf;
+    int i, ret;
+
+    for (i = 0; i < n; i++)
+	data[i] = cw[i];
+    rs_gf256_init(&gf, AZ_GF256_PPOLY);
+    ret = rs_correct(&gf, AZ_M0, data, n, necw, NULL, 0);
+    for (i = 0; i < n; i++)
+	cw[i] = data[i];
+    return (ret);
+}
+
+int _zbar_az_rs_correct(unsigned *cw, int n, int necw, int wordbits)
+{
+    gf f = { NULL, NULL, 0 };
+    unsigned *s = NULL;
+    int ret	= -1;
+
+    if (wordbits == 8) {
+	if (necw < 1 || necw >= n || n > 255)
+	    return (-1);
+	return (correct_bytes(cw, n, necw));
+    }
+    if (necw < 1 || necw >= n || gf_init(&f, wordbits) || n > f.size)
+	goto done;
+
+    /* syndromes, lambda, b, t and omega */
+    s = malloc((5 * necw + 3) * sizeof(*s));
+    if (!s)
+	goto done;
+    if (!calc_syndromes(&f, cw, n, necw, s))
+	ret = 0;
+    else
+	ret = correct(&f, cw, n, necw, s);
+
+done:
+    free(s);
+    free(f.exp);
+    free(f.log);
+    return (ret);
+}
@@ -0,0 +1,279 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+
+/* Aztec decoding of the Upper, Lower, Mixed, Punct and Digit character
+ * modes and of the Binary Shift byte runs
+ */
+
+#include "config.h"
+
+#include <stdlib.h>
+#include <string.h>
+
+#include "azdec.h"
+#include "decoder.h"
+
+/* character modes */
+enum
+{
+    AZ_UPPER,
+    AZ_LOWER,
+    AZ_MIXED,
+    AZ_PUNCT,
+    AZ_DIGIT,
+};
+
+/* control codes, besides the characters of each mode */
+enum
+{
+    AZ_CHAR,
+    AZ_PS, /* shift to Punct */
+    AZ_US, /* shift to Upper */
+    AZ_LL, /* latch to Lower */
+    AZ_ML, /* latch to Mixed */
+    AZ_DL, /* latch to Digit */
+    AZ_UL, /* latch to Upper */
+    AZ_PL, /* latch to Punct */
+    AZ_BS, /* binary shift */
+    AZ_FLG, /* FNC1 or ECI */
+};
+
+/* group separator emitted for FNC1 */
+#define AZ_GS 0x1d
+
+static const char az_mixed[9] = "@\\^_`|~\x7f";
+static const char az_punct[26] = "!\"#$%&'()*+,-./:;<=>?[]{}";
+
+typedef struct az_text {
+    unsigned char *buf;
+    int len, alloc;
+    unsigned modifiers; /* symbology modifiers found */
+} az_text;
+
+static int text_push(az_text *txt, unsigned c)
+{
+    if (txt->len >= txt->alloc) {
+	int alloc	   = txt->alloc ? 2 * txt->alloc : 64;
+	unsigned char *buf = realloc(txt->buf, alloc);
+	if (!buf)
+	    return (-1);
+	txt->buf   = buf;
+	txt->alloc = alloc;
+    }
+    txt->buf[txt->len++] = c;
+    return (0);
+}
+
+static int text_push_str(az_text *txt, const char *s)
+{
+    for (; *s; s++)
+	if (text_push(txt, (unsigned char)*s))
+	    return (-1);
+    return (0);
+}
+
+/* read an n bit big endian value, -1 past the end */
+static int read_bits(const unsigned char *bits, int nbits, int *i, int n)
+{
+    int v = 0;
+    if (*i + n > nbits)
+	return (-1);
+    for (; n > 0; n--)
+	v = v << 1 | bits[(*i)++];
+    return (v);
+}
+
+/* the control code of a character code in a mode, AZ_CHAR for characters */
+static int control_code(int mode, int code)
+{
+    if (mode == AZ_DIGIT)
+	switch (code) {
+	case 0:
+	    return (AZ_PS);
+	case 14:
+	    return (AZ_UL);
+	case 15:
+	    return (AZ_US);
+	default:
+	    return (AZ_CHAR);
+	}
+    if (mode == AZ_PUNCT)
+	return ((!code) ? AZ_FLG : (code == 31) ? AZ_UL : AZ_CHAR);
+    switch (code) {
+    case 0:
+	return (AZ_PS);
+    case 28:
+	return ((mode == AZ_LOWER) ? AZ_US : AZ_LL);
+    case 29:
+	return ((mode == AZ_MIXED) ? AZ_UL : AZ_ML);
+    case 30:
+	return ((mode == AZ_MIXED) ? AZ_PL : AZ_DL);
+    case 31:
+	return (AZ_BS);
+    default:
+	return (AZ_CHAR);
+    }
+}
+
+/* append the text of a character code */
+static int push_char(az_text *txt, int mode, int code)
+{
+    if (code == 1 && mode != AZ_PUNCT)
+	return (text_push(txt, ' '));
+    switch (mode) {
+    case AZ_UPPER:
+	return (text_push(txt, 'A' + code - 2));
+    case AZ_LOWER:
+	return (text_push(txt, 'a' + code - 2));
+    case AZ_MIXED:
+	/* control characters, then a few symbols */
+	if (code < 15)
+	    return (text_push(txt, code - 1));
+	if (code < 20)
+	    return (text_push(txt, code + 12));
+	return (text_push(txt, az_mixed[code - 20]));
+    case AZ_DIGIT:
+	return (text_push(txt, (code < 12) ? '0' + code - 2 :
+			       (code == 12) ? ',' : '.'));
+    default:
+	switch (code) {
+	case 1:
+	    return (text_push(txt, '\r'));
+	case 2:
+	    return (text_push_str(txt, "\r\n"));
+	case 3:
+	    return (text_push_str(txt, ". "));
+	case 4:
+	    return (text_push_str(txt, ", "));
+	case 5:
+	    return (text_push_str(txt, ": "));
+	default:
+	    return (text_push(txt, az_punct[code - 6]));
+	}
+    }
+}
+
+/* Binary Shift: a length, then as many bytes */
+static int decode_binary(az_text *txt, const unsigned char *bits, int nbits,
+			 int *i)
+{
+    int len = read_bits(bits, nbits, i, 5);
+    if (!len) {
+	len = read_bits(bits, nbits, i, 11);
+	if (len >= 0)
+	    len += 31;
+    }
+    /* the padding may look like the start of a binary shift */
+    if (len < 0 || *i + 8 * len > nbits) {
+	*i = nbits;
+	return (0);
+    }
+    while (len-- > 0)
+	if (text_push(txt, read_bits(bits, nbits, i, 8)))
+	    return (-1);
+    return (0);
+}
+
+/* FLG(n): FNC1 or an ECI designator of n digits */
+static int decode_flg(az_text *txt, const unsigned char *bits, int nbits,
+		      int *i)
+{
+    int n = read_bits(bits, nbits, i, 3);
+    if (n < 0)
+	return (0);
+    if (!n) {
+	/* FNC1 marks GS1 data in first position, a field separator elsewhere */
+	if (!txt->len) {
+	    txt->modifiers |= MOD(ZBAR_MOD_GS1);
+	    return (0);
+	}
+	return (text_push(txt, AZ_GS));
+    }
+    if (n == 7)
+	return (-1);
+    /* FIXME ECIs are not interpreted, the data is passed through */
+    while (n-- > 0) {
+	int digit = read_bits(bits, nbits, i, 4);
+	if (digit >= 0 && (digit < 2 || digit > 11))
+	    return (-1);
+    }
+    return (0);
+}
+
+int _zbar_az_decode_data(const unsigned char *bits, int nbits,
+			 unsigned char **data, unsigned *modifiers)
+{
+    az_text txt;
+    int latch = AZ_UPPER, mode = AZ_UPPER, i = 0, err = 0;
+
+    memset(&txt, 0, sizeof(txt));
+
+    while (!err) {
+	int code = read_bits(bits, nbits, &i, (mode == AZ_DIGIT) ? 4 : 5);
+	int shift = mode;
+	/* the rest is padding */
+	if (code < 0)
+	    break;
+
+	/* a shifted mode only lasts for one character */
+	mode = latch;
+	switch (control_code(shift, code)) {
+	case AZ_CHAR:
+	    err = push_char(&txt, shift, code);
+	    break;
+	case AZ_PS:
+	    mode = AZ_PUNCT;
+	    break;
+	case AZ_US:
+	    mode = AZ_UPPER;
+	    break;
+	case AZ_LL:
+	    mode = latch = AZ_LOWER;
+	    break;
+	case AZ_ML:
+	    mode = latch = AZ_MIXED;
+	    break;
+	case AZ_DL:
+	    mode = latch = AZ_DIGIT;
+	    break;
+	case AZ_UL:
+	    mode = latch = AZ_UPPER;
+	    break;
+	case AZ_PL:
+	    mode = latch = AZ_PUNCT;
+	    break;
+	case AZ_BS:
+	    err = decode_binary(&txt, bits, nbits, &i);
+	    break;
+	case AZ_FLG:
+	    err = decode_flg(&txt, bits, nbits, &i);
+	    break;
+	}
+    }
+
+    if (err || text_push(&txt, '\0')) {
+	free(txt.buf);
+	return (-1);
+    }
+    *data      = txt.buf;
+    *modifiers = txt.modifiers;
+    return (txt.len - 1);
+}
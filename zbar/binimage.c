@@ -0,0 +1,247 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+
+#include "config.h"
+
+#include <stdlib.h>
+#include <string.h>
+
+#include "binimage.h"
+#include "image.h"
+#include "symbol.h"
+
+/* initial room for the pixels of a component */
+#define BIN_COMP_ALLOC 1024
+
+/* global threshold maximizing the between class variance (Otsu) */
+static int otsu_threshold(const unsigned long *hist, unsigned long n)
+{
+    double sum = 0, sumb = 0, best = -1;
+    unsigned long nb = 0;
+    int i, thresh = 128;
+
+    for (i = 0; i < 256; i++)
+	sum += (double)i * hist[i];
+    for (i = 0; i < 256; i++) {
+	double mb, mf, var;
+	nb += hist[i];
+	if (!nb || nb == n)
+	    continue;
+	sumb += (double)i * hist[i];
+	mb  = sumb / nb;
+	mf  = (sum - sumb) / (n - nb);
+	var = (double)nb * (n - nb) * (mb - mf) * (mb - mf);
+	if (var > best) {
+	    best   = var;
+	    thresh = i;
+	}
+    }
+    return (thresh);
+}
+
+int _zbar_bin_init(bin_image *bimg, const zbar_image_t *img)
+{
+    const unsigned char *data = img->data;
+    unsigned long hist[256] = { 0 };
+    int w = img->crop_w, h = img->crop_h, thresh, x, y;
+
+    memset(bimg, 0, sizeof(*bimg));
+    bimg->width	 = w;
+    bimg->height = h;
+    bimg->x0	 = img->crop_x;
+    bimg->y0	 = img->crop_y;
+    bimg->ymax	 = -1;
+
+    bimg->pix	     = malloc((size_t)w * h);
+    bimg->comp	     = malloc(BIN_COMP_ALLOC * sizeof(*bimg->comp));
+    bimg->comp_alloc = BIN_COMP_ALLOC;
+    bimg->xmin	     = malloc(h * sizeof(*bimg->xmin));
+    bimg->xmax	     = malloc(h * sizeof(*bimg->xmax));
+    bimg->pts	     = malloc(4 * (size_t)h * sizeof(*bimg->pts));
+    bimg->hull	     = malloc((4 * (size_t)h + 1) * sizeof(*bimg->hull));
+    if (!bimg->pix || !bimg->comp || !bimg->xmin || !bimg->xmax ||
+	!bimg->pts || !bimg->hull) {
+	_zbar_bin_free(bimg);
+	return (-1);
+    }
+
+    for (y = 0; y < h; y++) {
+	const unsigned char *row = data + (size_t)(bimg->y0 + y) * img->width;
+	for (x = 0; x < w; x++)
+	    hist[row[bimg->x0 + x]]++;
+	bimg->xmin[y] = w;
+	bimg->xmax[y] = -1;
+    }
+    thresh = otsu_threshold(hist, (unsigned long)w * h);
+    for (y = 0; y < h; y++) {
+	const unsigned char *row = data + (size_t)(bimg->y0 + y) * img->width;
+	unsigned char *pix	 = bimg->pix + (size_t)y * w;
+	for (x = 0; x < w; x++)
+	    pix[x] = (row[bimg->x0 + x] <= thresh) ? BIN_DARK : 0;
+    }
+    return (0);
+}
+
+void _zbar_bin_free(bin_image *bimg)
+{
+    free(bimg->pix);
+    free(bimg->comp);
+    free(bimg->xmin);
+    free(bimg->xmax);
+    free(bimg->pts);
+    free(bimg->hull);
+    memset(bimg, 0, sizeof(*bimg));
+}
+
+/* mark a pixel and append it to the component */
+static inline int add_pixel(bin_image *bimg, size_t i)
+{
+    if (bimg->ncomp >= bimg->comp_alloc) {
+	size_t n     = 2 * bimg->comp_alloc;
+	size_t *comp = realloc(bimg->comp, n * sizeof(*comp));
+	if (!comp)
+	    return (-1);
+	bimg->comp	 = comp;
+	bimg->comp_alloc = n;
+    }
+    bimg->pix[i] |= BIN_MARK;
+    bimg->comp[bimg->ncomp++] = i;
+    return (0);
+}
+
+int _zbar_bin_trace(bin_image *bimg, int x, int y, int conn8, size_t limit)
+{
+    /* the 4-connected neighbors first */
+    static const signed char dirs[8][2] = {
+	{ 1, 0 }, { -1, 0 }, { 0, 1 },	{ 0, -1 },
+	{ 1, 1 }, { -1, 1 }, { 1, -1 }, { -1, -1 },
+    };
+    int w = bimg->width, h = bimg->height, ndirs = (conn8) ? 8 : 4, d;
+    unsigned char color = bimg->pix[(size_t)y * w + x] & BIN_DARK;
+    size_t next;
+
+    /* only the rows of the previous component were changed */
+    for (d = bimg->ymin; d <= bimg->ymax; d++) {
+	bimg->xmin[d] = w;
+	bimg->xmax[d] = -1;
+    }
+    bimg->ymin = bimg->ymax = y;
+    bimg->ncomp		    = 0;
+    bimg->border	    = 0;
+    bimg->sumx = bimg->sumy = 0;
+    if (add_pixel(bimg, (size_t)y * w + x))
+	return (-1);
+
+    /* the component list doubles as the queue of pixels to visit */
+    for (next = 0; next < bimg->ncomp; next++) {
+	size_t i = bimg->comp[next];
+	x	 = i % w;
+	y	 = i / w;
+	if (bimg->ncomp > limit)
+	    return (-1);
+	bimg->sumx += x + 0.5;
+	bimg->sumy += y + 0.5;
+	if (x < bimg->xmin[y])
+	    bimg->xmin[y] = x;
+	if (x > bimg->xmax[y])
+	    bimg->xmax[y] = x;
+	bimg->ymin = (y < bimg->ymin) ? y : bimg->ymin;
+	bimg->ymax = (y > bimg->ymax) ? y : bimg->ymax;
+	if (!x || !y || x == w - 1 || y == h - 1)
+	    bimg->border = 1;
+
+	for (d = 0; d < ndirs; d++) {
+	    int nx = x + dirs[d][0], ny = y + dirs[d][1];
+	    size_t j;
+	    if (nx < 0 || ny < 0 || nx >= w || ny >= h)
+		continue;
+	    j = (size_t)ny * w + nx;
+	    if ((bimg->pix[j] & BIN_DARK) != color || bimg->pix[j] & BIN_MARK)
+		continue;
+	    if (add_pixel(bimg, j))
+		return (-1);
+	}
+    }
+    return (0);
+}
+
+void _zbar_bin_unmark(bin_image *bimg)
+{
+    size_t i;
+    for (i = 0; i < bimg->ncomp; i++)
+	bimg->pix[bimg->comp[i]] &= ~BIN_MARK;
+}
+
+static int cmp_points(const void *a, const void *b)
+{
+    const bin_point *p = a, *q = b;
+    if (p->x != q->x)
+	return ((p->x < q->x) ? -1 : 1);
+    return ((p->y < q->y) ? -1 : (p->y > q->y));
+}
+
+/* convex hull (monotone chain), returns the number of vertices */
+static int convex_hull(bin_point *pts, int n, bin_point *hull)
+{
+    int i, k = 0, lower;
+    qsort(pts, n, sizeof(*pts), cmp_points);
+    for (i = 0; i < n; i++) {
+	while (k >= 2 && cross(hull[k - 2], hull[k - 1], pts[i]) <= 0)
+	    k--;
+	hull[k++] = pts[i];
+    }
+    lower = k + 1;
+    for (i = n - 2; i >= 0; i--) {
+	while (k >= lower && cross(hull[k - 2], hull[k - 1], pts[i]) <= 0)
+	    k--;
+	hull[k++] = pts[i];
+    }
+    return (k - 1);
+}
+
+int _zbar_bin_hull(bin_image *bimg)
+{
+    int y, n = 0;
+    for (y = bimg->ymin; y <= bimg->ymax; y++) {
+	bin_point p;
+	if (bimg->xmax[y] < 0)
+	    continue;
+	p.x	       = bimg->xmin[y];
+	p.y	       = y;
+	bimg->pts[n++] = p;
+	p.y	       = y + 1;
+	bimg->pts[n++] = p;
+	p.x	       = bimg->xmax[y] + 1;
+	bimg->pts[n++] = p;
+	p.y	       = y;
+	bimg->pts[n++] = p;
+    }
+    if (!n)
+	return (0);
+    return (convex_hull(bimg->pts, n, bimg->hull));
+}
+
+void _zbar_bin_add_point(const bin_image *bimg, zbar_symbol_t *sym,
+			 bin_point p)
+{
+    sym_add_point(sym, bimg->x0 + (int)floor(p.x + 0.5),
+		  bimg->y0 + (int)floor(p.y + 0.5));
+}
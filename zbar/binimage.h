@@ -0,0 +1,126 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+#ifndef _BINIMAGE_H_
+#define _BINIMAGE_H_
+
+/* binarized image and connected components, shared by the 2D decoders
+ * locating their symbols from the shapes of their finder patterns
+ */
+
+#include <math.h>
+#include <stddef.h>
+
+#include <zbar.h>
+
+/* flags of the pixels of a binarized image */
+#define BIN_DARK 1 /* darker than the threshold */
+#define BIN_MARK 2 /* part of a traced component */
+
+typedef struct bin_point {
+    double x, y;
+} bin_point;
+
+/* the crop of an image binarized with a global threshold, in coordinates
+ * relative to the crop, and the last component traced in it
+ */
+typedef struct bin_image {
+    unsigned char *pix; /* BIN_DARK and BIN_MARK flags of the pixels */
+    int width, height;
+    int x0, y0;		/* crop offset in the image */
+
+    size_t *comp;	/* pixel indices of the last traced component */
+    size_t ncomp, comp_alloc;
+    int ymin, ymax;	/* its rows */
+    int *xmin, *xmax;	/* ends of its pixel runs in each row */
+    int border;		/* whether it touches the crop borders */
+    double sumx, sumy;	/* sums of its pixel centers */
+
+    bin_point *pts, *hull;
+} bin_image;
+
+/* binarize the crop of a grayscale image, returns 0 or -1 for no memory */
+int _zbar_bin_init(bin_image *bimg, const zbar_image_t *img);
+
+void _zbar_bin_free(bin_image *bimg);
+
+/* trace and mark the unmarked component of the color of a pixel around it,
+ * 8-connected if conn8 is set and 4-connected otherwise.
+ * returns 0, or -1 if there were more than limit pixels or no memory
+ */
+int _zbar_bin_trace(bin_image *bimg, int x, int y, int conn8, size_t limit);
+
+/* clear the marks of the last traced component */
+void _zbar_bin_unmark(bin_image *bimg);
+
+/* convex hull of the pixel areas of the last traced component into hull,
+ * returns the number of its vertices
+ */
+int _zbar_bin_hull(bin_image *bimg);
+
+/* add a point of the crop to the polygon of a symbol, in image coordinates */
+void _zbar_bin_add_point(const bin_image *bimg, zbar_symbol_t *sym,
+			 bin_point p);
+
+static inline bin_point pt_add(bin_point p, bin_point q, double s)
+{
+    bin_point r = { p.x + s * q.x, p.y + s * q.y };
+    return (r);
+}
+
+static inline bin_point pt_sub(bin_point p, bin_point q)
+{
+    bin_point r = { p.x - q.x, p.y - q.y };
+    return (r);
+}
+
+static inline double pt_len(bin_point p)
+{
+    return (sqrt(p.x * p.x + p.y * p.y));
+}
+
+static inline bin_point pt_unit(bin_point p)
+{
+    double l	= pt_len(p);
+    bin_point r = { p.x / l, p.y / l };
+    return (r);
+}
+
+/* z component of the cross product of oa and ob */
+static inline double cross(bin_point o, bin_point a, bin_point b)
+{
+    return ((a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x));
+}
+
+static inline int pixel(const bin_image *bimg, int x, int y)
+{
+    return (bimg->pix[(size_t)y * bimg->width + x] & BIN_DARK);
+}
+
+/* color of the pixel containing a point, light outside of the crop */
+static inline int is_dark(const bin_image *bimg, bin_point p)
+{
+    int x = (int)floor(p.x), y = (int)floor(p.y);
+    if (x < 0 || y < 0 || x >= bimg->width || y >= bimg->height)
+	return (0);
+    return (pixel(bimg, x, y));
+}
+
+#endif
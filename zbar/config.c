@@ -78,10 +78,24 @@ int zbar_parse_config(const char *cfgstr, zbar_symbol_type_t *sym,
 	    *sym = ZBAR_ISBN13;
 	else if (!strncmp(cfgstr, "isbn10", len))
 	    *sym = ZBAR_ISBN10;
+	else if (!strncmp(cfgstr, "itf14", len))
+	    *sym = ZBAR_ITF14;
 	else if (!strncmp(cfgstr, "db-exp", len))
 	    *sym = ZBAR_DATABAR_EXP;
+	else if (!strncmp(cfgstr, "db-ltd", len))
+	    *sym = ZBAR_DATABAR_LTD;
+	else if (!strncmp(cfgstr, "db-stk", len))
+	    *sym = ZBAR_DATABAR_STK;
+	else if (!strncmp(cfgstr, "db-stk-omni", len))
+	    *sym = ZBAR_DATABAR_STK_OMNI;
+	else if (!strncmp(cfgstr, "db-exp-stk", len))
+	    *sym = ZBAR_DATABAR_EXP_STK;
 	else if (!strncmp(cfgstr, "codabar", len))
 	    *sym = ZBAR_CODABAR;
+	else if (!strncmp(cfgstr, "aztec", len))
+	    *sym = ZBAR_AZTEC;
+	else if (!strncmp(cfgstr, "rmqr", len))
+	    *sym = ZBAR_RMQR;
 	else if (len < 6)
 	    return (1);
 	else if (!strncmp(cfgstr, "code93", len))
@@ -96,8 +110,20 @@ int zbar_parse_config(const char *cfgstr, zbar_symbol_type_t *sym,
 	    *sym = ZBAR_CODE128;
 	else if (!strncmp(cfgstr, "databar", len))
 	    *sym = ZBAR_DATABAR;
+	else if (!strncmp(cfgstr, "microqr", len))
+	    *sym = ZBAR_MICROQR;
 	else if (!strncmp(cfgstr, "databar-exp", len))
 	    *sym = ZBAR_DATABAR_EXP;
+	else if (!strncmp(cfgstr, "databar-ltd", len))
+	    *sym = ZBAR_DATABAR_LTD;
+	else if (!strncmp(cfgstr, "databar-stk", len))
+	    *sym = ZBAR_DATABAR_STK;
+	else if (!strncmp(cfgstr, "databar-stk-omni", len))
+	    *sym = ZBAR_DATABAR_STK_OMNI;
+	else if (!strncmp(cfgstr, "databar-exp-stk", len))
+	    *sym = ZBAR_DATABAR_EXP_STK;
+	else if (!strncmp(cfgstr, "datamatrix", len))
+	    *sym = ZBAR_DATAMATRIX;
 	else
 	    return (1);
 	cfgstr = dot + 1;
@@ -133,7 +159,17 @@ int zbar_parse_config(const char *cfgstr, zbar_symbol_type_t *sym,
     else if (!strncmp(cfgstr, "disable", len)) {
 	*cfg   = ZBAR_CFG_ENABLE;
 	negate = !negate; /* no-disable ?!? */
-    } else if (!strncmp(cfgstr, "min-length", len))
+    } else if (!strncmp(cfgstr, "diagonal", len)) {
+	*cfg = ZBAR_CFG_DIAGONAL;
+	if (!eq)
+	    *val = 45; /* scan the two diagonals */
+    } else if (!strncmp(cfgstr, "edge-threshold", len))
+	*cfg = ZBAR_CFG_EDGE_THRESHOLD;
+    else if (!strncmp(cfgstr, "ewma-weight", len))
+	*cfg = ZBAR_CFG_EWMA_WEIGHT;
+    else if (!strncmp(cfgstr, "threshold-decay", len))
+	*cfg = ZBAR_CFG_THRESHOLD_DECAY;
+    else if (!strncmp(cfgstr, "min-length", len))
 	*cfg = ZBAR_CFG_MIN_LEN;
     else if (!strncmp(cfgstr, "max-length", len))
 	*cfg = ZBAR_CFG_MAX_LEN;
@@ -149,6 +185,8 @@ int zbar_parse_config(const char *cfgstr, zbar_symbol_type_t *sym,
 	*cfg = ZBAR_CFG_UNCERTAINTY;
     else if (!strncmp(cfgstr, "test-inverted", len))
 	*cfg = ZBAR_CFG_TEST_INVERTED;
+    else if (!strncmp(cfgstr, "test-mirrored", len))
+	*cfg = ZBAR_CFG_TEST_MIRRORED;
     else if (!strncmp(cfgstr, "position", len))
 	*cfg = ZBAR_CFG_POSITION;
     else
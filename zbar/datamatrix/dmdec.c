@@ -0,0 +1,421 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+
+/* Data Matrix symbol detection: the L shaped finder pattern bounds the
+ * dark connected components of the binarized image, the timing patterns
+ * on the opposite sides give the symbol size, then the module grid is
+ * sampled and decoded
+ */
+
+#include "config.h"
+
+#include <math.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "binimage.h"
+#include "datamatrix.h"
+#include "dmdec.h"
+#include "image.h"
+#include "img_scanner.h"
+#include "symbol.h"
+
+/* smallest symbol candidate, in pixels */
+#define DM_MIN_PIXELS 8
+
+/* samples taken along each side of a candidate */
+#define DM_SIDE_SAMPLES 32
+
+/* least dark fraction of both finder sides */
+#define DM_MIN_FINDER_SCORE 0.85
+
+/* shortest dark run through a pixel seeding a symbol candidate, in pixels */
+#define DM_SEED_RUN 3
+
+/* most finder and timing modules found wrong in a sampled symbol */
+#define DM_MAX_BORDER_ERRORS 0.2
+
+struct dm_reader {
+    int enabled;
+};
+
+/* a located symbol: the corner of the finder pattern and the ends of its
+ * two sides, along the first column (a) and the last row (b)
+ */
+typedef struct dm_finder {
+    bin_point c, a, b;
+} dm_finder;
+
+dm_reader *_zbar_dm_create(void)
+{
+    dm_reader *reader = malloc(sizeof(dm_reader));
+    if (reader)
+	reader->enabled = 1;
+    return (reader);
+}
+
+void _zbar_dm_destroy(dm_reader *reader)
+{
+    free(reader);
+}
+
+void _zbar_dm_reset(dm_reader *reader)
+{
+    reader->enabled = 1;
+}
+
+int _zbar_dm_new_config(dm_reader *reader, unsigned config)
+{
+    reader->enabled = (config >> ZBAR_CFG_ENABLE) & 1;
+    return (0);
+}
+
+/* fraction of dark pixels just inside the side from p to q */
+static double side_score(const bin_image *img, bin_point p, bin_point q,
+			 bin_point inward)
+{
+    bin_point d = pt_sub(q, p);
+    int i, dark = 0;
+    for (i = 0; i < DM_SIDE_SAMPLES; i++) {
+	double t    = 0.1 + 0.8 * (i + 0.5) / DM_SIDE_SAMPLES;
+	bin_point s = pt_add(pt_add(p, d, t), inward, 1.5);
+	dark += is_dark(img, s);
+	if (dark + DM_SIDE_SAMPLES - i - 1 < DM_MIN_FINDER_SCORE * DM_SIDE_SAMPLES)
+	    /* already too light */
+	    return (0);
+    }
+    return ((double)dark / DM_SIDE_SAMPLES);
+}
+
+/* find the finder pattern among the rectangles bounding the hull of a
+ * component along each of its edges
+ */
+static int find_finder(const bin_image *img, const bin_point *hull, int nhull,
+		       dm_finder *f)
+{
+    double best = 0;
+    int i, j, k;
+
+    for (i = 0; i < nhull; i++) {
+	bin_point e = pt_sub(hull[(i + 1) % nhull], hull[i]), u, v, rect[4];
+	bin_point center = { 0, 0 };
+	double umin = HUGE_VAL, umax = -HUGE_VAL, vmin = HUGE_VAL,
+	       vmax = -HUGE_VAL, score[4];
+
+	if (pt_len(e) < 3)
+	    continue;
+	u   = pt_unit(e);
+	v.x = -u.y;
+	v.y = u.x;
+	for (j = 0; j < nhull; j++) {
+	    double su = hull[j].x * u.x + hull[j].y * u.y;
+	    double sv = hull[j].x * v.x + hull[j].y * v.y;
+	    umin      = (su < umin) ? su : umin;
+	    umax      = (su > umax) ? su : umax;
+	    vmin      = (sv < vmin) ? sv : vmin;
+	    vmax      = (sv > vmax) ? sv : vmax;
+	}
+	if (umax - umin < DM_MIN_PIXELS || vmax - vmin < DM_MIN_PIXELS)
+	    continue;
+
+	for (j = 0; j < 4; j++) {
+	    double su = (j == 1 || j == 2) ? umax : umin;
+	    double sv = (j >= 2) ? vmax : vmin;
+	    rect[j].x = su * u.x + sv * v.x;
+	    rect[j].y = su * u.y + sv * v.y;
+	    center    = pt_add(center, rect[j], 0.25);
+	}
+	for (j = 0; j < 4; j++) {
+	    bin_point p = rect[j], q = rect[(j + 1) % 4];
+	    bin_point mid = pt_add(p, pt_sub(q, p), 0.5);
+	    score[j] = side_score(img, p, q, pt_unit(pt_sub(center, mid)));
+	}
+
+	/* two adjacent solid sides, meeting at the finder corner */
+	for (j = 0; j < 4; j++) {
+	    double s = (score[j] < score[(j + 1) % 4]) ? score[j] :
+							   score[(j + 1) % 4];
+	    bin_point c, p, q;
+	    if (s < DM_MIN_FINDER_SCORE || s <= best)
+		continue;
+	    best = s;
+	    c	 = rect[(j + 1) % 4];
+	    p	 = rect[j];
+	    q	 = rect[(j + 2) % 4];
+	    /* the last row runs to the right of the first column */
+	    k = cross(c, q, p) < 0;
+	    f->c = c;
+	    f->a = (k) ? p : q;
+	    f->b = (k) ? q : p;
+	}
+    }
+    return (best > 0) ? 0 : -1;
+}
+
+static int cmp_doubles(const void *a, const void *b)
+{
+    double x = *(const double *)a, y = *(const double *)b;
+    return ((x < y) ? -1 : (x > y));
+}
+
+/* module size, from the thinnest parts of the finder pattern */
+static double module_size(const bin_image *img, const dm_finder *f)
+{
+    double widths[2 * DM_SIDE_SAMPLES];
+    int side, i, n = 0;
+    for (side = 0; side < 2; side++) {
+	bin_point p = (side) ? f->b : f->a, across = (side) ? f->a : f->b;
+	bin_point d = pt_sub(p, f->c), in = pt_unit(pt_sub(across, f->c));
+	double max = pt_len(pt_sub(across, f->c)) / 2, w;
+	for (i = 0; i < DM_SIDE_SAMPLES; i++) {
+	    bin_point s =
+		pt_add(f->c, d, 0.1 + 0.8 * (i + 0.5) / DM_SIDE_SAMPLES);
+	    /* the edge itself may be blurred light */
+	    for (w = 0.5; w < 2 && !is_dark(img, pt_add(s, in, w)); w += 0.5)
+		;
+	    for (; w < max && is_dark(img, pt_add(s, in, w)); w += 0.5)
+		;
+	    widths[n++] = w;
+	}
+    }
+    qsort(widths, n, sizeof(*widths), cmp_doubles);
+    return (widths[n / 4]);
+}
+
+/* count the dark modules of the timing pattern from p to q */
+static int timing_count(const bin_image *img, bin_point p, bin_point q,
+			bin_point inward, double module)
+{
+    bin_point d	 = pt_sub(q, p);
+    double len	 = pt_len(d);
+    int nsamples = (int)(2 * len), minrun = (int)(module / 2), i;
+    int color = 1, run = 0, count = 1;
+
+    if (minrun < 1)
+	minrun = 1;
+    p = pt_add(p, inward, module / 2);
+    for (i = 0; i < nsamples; i++) {
+	int dark = is_dark(img, pt_add(p, d, (i + 0.5) / nsamples));
+	if (dark == color) {
+	    run = 0;
+	    continue;
+	}
+	/* only count changes lasting a fair part of a module */
+	if (++run >= minrun) {
+	    color = dark;
+	    run	  = 0;
+	    count += dark;
+	}
+    }
+    return (count);
+}
+
+/* sample the module colors at the centers of the grid cells */
+static void sample_grid(const bin_image *img, const dm_finder *f,
+			const dm_size *size, unsigned char *modules)
+{
+    bin_point across = pt_sub(f->b, f->c), up = pt_sub(f->a, f->c);
+    int r, c;
+    for (r = 0; r < size->rows; r++)
+	for (c = 0; c < size->cols; c++) {
+	    bin_point s = pt_add(pt_add(f->c, across, (c + 0.5) / size->cols),
+				up, (size->rows - r - 0.5) / size->rows);
+	    modules[r * size->cols + c] = is_dark(img, s);
+	}
+}
+
+/* count the finder and timing pattern modules sampled wrong */
+static int border_errors(const dm_size *size, const unsigned char *modules)
+{
+    int rh = size->region_rows + 2, rw = size->region_cols + 2;
+    int r, c, errors = 0;
+    for (r = 0; r < size->rows; r++)
+	for (c = 0; c < size->cols; c++) {
+	    int br = r % rh, bc = c % rw, dark;
+	    if (br == rh - 1 || !bc)
+		dark = 1;
+	    else if (!br)
+		dark = !(bc & 1);
+	    else if (bc == rw - 1)
+		dark = br & 1;
+	    else
+		continue;
+	    errors += dark != !!modules[r * size->cols + c];
+	}
+    return (errors);
+}
+
+static int decode_finder(const bin_image *img, const dm_finder *f,
+			 unsigned char **data, unsigned *modifiers)
+{
+    bin_point d = pt_add(f->a, pt_sub(f->b, f->c), 1);
+    double module = module_size(img, f);
+    int cols, rows, i, len = -1;
+
+    if (module < 1)
+	return (-1);
+    cols = 2 * timing_count(img, f->a, d, pt_unit(pt_sub(f->c, f->a)), module);
+    rows = 2 * timing_count(img, f->b, d, pt_unit(pt_sub(f->c, f->b)), module);
+
+    /* the sizes counted may be off by a module pair */
+    for (i = 0; i < 9 && len < 0; i++) {
+	static const signed char delta[3] = { 0, -2, 2 };
+	const dm_size *size =
+	    _zbar_dm_find_size(rows + delta[i / 3], cols + delta[i % 3]);
+	unsigned char *modules;
+	int nborder;
+	if (!size)
+	    continue;
+	modules = malloc(size->rows * size->cols);
+	if (!modules)
+	    return (-1);
+	sample_grid(img, f, size, modules);
+	nborder = 2 * (size->rows / (size->region_rows + 2) * size->cols +
+		       size->cols / (size->region_cols + 2) * size->rows);
+	if (border_errors(size, modules) <= DM_MAX_BORDER_ERRORS * nborder)
+	    len = _zbar_dm_decode_matrix(size, modules, data, modifiers);
+	free(modules);
+    }
+    return (len);
+}
+
+static void add_symbol(zbar_image_scanner_t *iscn, const bin_image *img,
+		       const dm_finder *f, const unsigned char *data, int len,
+		       unsigned modifiers)
+{
+    bin_point d	       = pt_add(f->a, pt_sub(f->b, f->c), 1);
+    bin_point across   = pt_sub(f->b, f->c);
+    zbar_symbol_t *sym = _zbar_image_scanner_alloc_sym(iscn, ZBAR_DATAMATRIX,
+						       len + 1);
+    memcpy(sym->data, data, len + 1);
+    sym->modifiers = modifiers;
+    _zbar_bin_add_point(img, sym, f->a);
+    _zbar_bin_add_point(img, sym, f->c);
+    _zbar_bin_add_point(img, sym, f->b);
+    _zbar_bin_add_point(img, sym, d);
+
+    /* symbols read along the finder pattern's last row */
+    if (fabs(across.x) >= fabs(across.y))
+	sym->orient = (across.x > 0) ? ZBAR_ORIENT_UP : ZBAR_ORIENT_DOWN;
+    else
+	sym->orient = (across.y > 0) ? ZBAR_ORIENT_RIGHT : ZBAR_ORIENT_LEFT;
+
+    _zbar_image_scanner_add_sym(iscn, sym);
+}
+
+/* locate and decode the symbol whose finder pattern may be the component
+ * last traced, returns the number of data bytes or -1
+ */
+static int decode_component(bin_image *img, dm_finder *f,
+			    unsigned char **data, unsigned *modifiers)
+{
+    int nhull;
+    if (img->ymax - img->ymin + 1 < DM_MIN_PIXELS)
+	return (-1);
+    nhull = _zbar_bin_hull(img);
+    if (nhull < 3 || find_finder(img, img->hull, nhull, f))
+	return (-1);
+    return (decode_finder(img, f, data, modifiers));
+}
+
+int _zbar_dm_decode(dm_reader *reader, zbar_image_scanner_t *iscn,
+		    zbar_image_t *img)
+{
+    bin_image dimg;
+    int w = img->crop_w, h = img->crop_h, nsyms = 0, i, x, y;
+    int *colrun = NULL;
+    unsigned char **found = NULL;
+    int *nfound = NULL, nalloc = 0;
+
+    if (!reader->enabled || w < DM_MIN_PIXELS || h < DM_MIN_PIXELS)
+	return (0);
+    if (_zbar_bin_init(&dimg, img))
+	return (0);
+    colrun = calloc(w, sizeof(*colrun));
+    if (!colrun)
+	goto done;
+
+    for (y = 0; y < h; y++) {
+	int run = 0;
+	for (x = 0; x < w; x++) {
+	    unsigned char p	= dimg.pix[(size_t)y * w + x];
+	    dm_finder f	= { { 0, 0 } };
+	    unsigned char *data = NULL;
+	    unsigned modifiers	= 0;
+	    int len, j;
+
+	    if (!(p & BIN_DARK)) {
+		run = colrun[x] = 0;
+		continue;
+	    }
+	    run++;
+	    colrun[x]++;
+	    /* the sides of the finder pattern are at least a module thick,
+	     * pixels of thinner components need not be traced
+	     */
+	    if (p & BIN_MARK || (run < DM_SEED_RUN && colrun[x] < DM_SEED_RUN))
+		continue;
+	    if (_zbar_bin_trace(&dimg, x, y, 1, (size_t)-1))
+		goto done;
+	    len = decode_component(&dimg, &f, &data, &modifiers);
+	    if (len < 0)
+		continue;
+
+	    /* parts of a symbol may be traced separately */
+	    for (j = 0; j < nsyms; j++)
+		if (nfound[j] == len && !memcmp(found[j], data, len))
+		    break;
+	    if (j < nsyms) {
+		free(data);
+		continue;
+	    }
+	    if (nsyms >= nalloc) {
+		int n		   = nalloc ? 2 * nalloc : 4;
+		unsigned char **nf = realloc(found, n * sizeof(*found));
+		int *nl;
+		if (!nf) {
+		    free(data);
+		    goto done;
+		}
+		found = nf;
+		nl    = realloc(nfound, n * sizeof(*nfound));
+		if (!nl) {
+		    free(data);
+		    goto done;
+		}
+		nfound = nl;
+		nalloc = n;
+	    }
+	    add_symbol(iscn, &dimg, &f, data, len, modifiers);
+	    found[nsyms]    = data;
+	    nfound[nsyms++] = len;
+	}
+    }
+
+done:
+    for (i = 0; i < nsyms; i++)
+	free(found[i]);
+    free(found);
+    free(nfound);
+    free(colrun);
+    _zbar_bin_free(&dimg);
+    return (nsyms);
+}
@@ -0,0 +1,53 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+#ifndef _DATAMATRIX_DMDEC_H_
+#define _DATAMATRIX_DMDEC_H_
+
+/* largest ECC 200 symbol, in modules */
+#define DM_MAX_SIZE 144
+
+/* ECC 200 symbol attributes */
+typedef struct dm_size {
+    unsigned char rows, cols;		      /* symbol size */
+    unsigned char region_rows, region_cols; /* data area of each region */
+    unsigned short ndata, necw;		      /* data and ECC codewords */
+    unsigned char nblocks;		      /* interleaved blocks */
+} dm_size;
+
+/* attributes of the symbol of the given size, or NULL if there is none */
+const dm_size *_zbar_dm_find_size(int rows, int cols);
+
+/* extract, correct and decode the codewords of a sampled symbol.
+ * modules holds the rows * cols module colors (non-zero for dark).
+ * returns the number of bytes written to the newly allocated *data
+ * or -1 if the symbol can't be decoded
+ */
+int _zbar_dm_decode_matrix(const dm_size *size, const unsigned char *modules,
+			   unsigned char **data, unsigned *modifiers);
+
+/* decode the data codewords into bytes.
+ * returns the number of bytes written to the newly allocated *data
+ * or -1 for invalid data
+ */
+int _zbar_dm_decode_data(const unsigned char *cw, int n, unsigned char **data,
+			 unsigned *modifiers);
+
+#endif
@@ -0,0 +1,249 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+
+/* Data Matrix ECC 200 symbol matrix: the codewords are read from the data
+ * regions following the module placement of the specification, then the
+ * interleaved blocks are error corrected
+ */
+
+#include "config.h"
+
+#include <stdlib.h>
+#include <string.h>
+
+#include "dmdec.h"
+#include "qrcode/rs.h"
+
+/* the Reed-Solomon codes of ECC 200 are over GF(256) generated by
+ * x^8 + x^5 + x^3 + x^2 + 1 (the high bit being implicit), and the
+ * generator polynomial of a block with k error correction codewords is
+ * (x - a)(x - a^2)...(x - a^k)
+ */
+#define DM_PPOLY 0x2D
+#define DM_M0	 1
+
+static const dm_size dm_sizes[] = {
+    /* square symbols */
+    { 10, 10, 8, 8, 3, 5, 1 },
+    { 12, 12, 10, 10, 5, 7, 1 },
+    { 14, 14, 12, 12, 8, 10, 1 },
+    { 16, 16, 14, 14, 12, 12, 1 },
+    { 18, 18, 16, 16, 18, 14, 1 },
+    { 20, 20, 18, 18, 22, 18, 1 },
+    { 22, 22, 20, 20, 30, 20, 1 },
+    { 24, 24, 22, 22, 36, 24, 1 },
+    { 26, 26, 24, 24, 44, 28, 1 },
+    { 32, 32, 14, 14, 62, 36, 1 },
+    { 36, 36, 16, 16, 86, 42, 1 },
+    { 40, 40, 18, 18, 114, 48, 1 },
+    { 44, 44, 20, 20, 144, 56, 1 },
+    { 48, 48, 22, 22, 174, 68, 1 },
+    { 52, 52, 24, 24, 204, 84, 2 },
+    { 64, 64, 14, 14, 280, 112, 2 },
+    { 72, 72, 16, 16, 368, 144, 4 },
+    { 80, 80, 18, 18, 456, 192, 4 },
+    { 88, 88, 20, 20, 576, 224, 4 },
+    { 96, 96, 22, 22, 696, 272, 4 },
+    { 104, 104, 24, 24, 816, 336, 6 },
+    { 120, 120, 18, 18, 1050, 408, 6 },
+    { 132, 132, 20, 20, 1304, 496, 8 },
+    { 144, 144, 22, 22, 1558, 620, 10 },
+    /* rectangular symbols */
+    { 8, 18, 6, 16, 5, 7, 1 },
+    { 8, 32, 6, 14, 10, 11, 1 },
+    { 12, 26, 10, 24, 16, 14, 1 },
+    { 12, 36, 10, 16, 22, 18, 1 },
+    { 16, 36, 14, 16, 32, 24, 1 },
+    { 16, 48, 14, 22, 49, 28, 1 },
+};
+
+const dm_size *_zbar_dm_find_size(int rows, int cols)
+{
+    int i;
+    for (i = 0; i < (int)(sizeof(dm_sizes) / sizeof(*dm_sizes)); i++)
+	if (dm_sizes[i].rows == rows && dm_sizes[i].cols == cols)
+	    return (dm_sizes + i);
+    return (NULL);
+}
+
+/* module placement state over the mapping matrix of the data regions */
+typedef struct dm_placement {
+    int nrow, ncol;
+    const unsigned char *bits; /* mapping matrix module colors */
+    unsigned char *used;       /* modules already placed */
+    unsigned char *cw;	       /* codewords read */
+} dm_placement;
+
+/* read bit (1 is the most significant) of codeword chr from a module,
+ * wrapping around the edges of the mapping matrix
+ */
+static void place_module(dm_placement *p, int row, int col, int chr, int bit)
+{
+    if (row < 0) {
+	row += p->nrow;
+	col += 4 - ((p->nrow + 4) % 8);
+    }
+    if (col < 0) {
+	col += p->ncol;
+	row += 4 - ((p->ncol + 4) % 8);
+    }
+    p->used[row * p->ncol + col] = 1;
+    if (p->bits[row * p->ncol + col])
+	p->cw[chr] |= 0x80 >> (bit - 1);
+}
+
+/* the standard L shaped codeword placement */
+static void place_utah(dm_placement *p, int row, int col, int chr)
+{
+    place_module(p, row - 2, col - 2, chr, 1);
+    place_module(p, row - 2, col - 1, chr, 2);
+    place_module(p, row - 1, col - 2, chr, 3);
+    place_module(p, row - 1, col - 1, chr, 4);
+    place_module(p, row - 1, col, chr, 5);
+    place_module(p, row, col - 2, chr, 6);
+    place_module(p, row, col - 1, chr, 7);
+    place_module(p, row, col, chr, 8);
+}
+
+/* the special placements of codewords split over the corners */
+static void place_corner(dm_placement *p, const signed char pos[8][2],
+			 int chr)
+{
+    int i;
+    for (i = 0; i < 8; i++) {
+	int row = pos[i][0], col = pos[i][1];
+	/* negative positions count from the far edge */
+	if (row < 0)
+	    row += p->nrow;
+	if (col < 0)
+	    col += p->ncol;
+	place_module(p, row, col, chr, i + 1);
+    }
+}
+
+static const signed char dm_corner1[8][2] = {
+    { -1, 0 }, { -1, 1 }, { -1, 2 }, { 0, -2 },
+    { 0, -1 }, { 1, -1 }, { 2, -1 }, { 3, -1 },
+};
+static const signed char dm_corner2[8][2] = {
+    { -3, 0 }, { -2, 0 }, { -1, 0 }, { 0, -4 },
+    { 0, -3 }, { 0, -2 }, { 0, -1 }, { 1, -1 },
+};
+static const signed char dm_corner3[8][2] = {
+    { -3, 0 }, { -2, 0 }, { -1, 0 }, { 0, -2 },
+    { 0, -1 }, { 1, -1 }, { 2, -1 }, { 3, -1 },
+};
+static const signed char dm_corner4[8][2] = {
+    { -1, 0 }, { -1, -1 }, { 0, -3 }, { 0, -2 },
+    { 0, -1 }, { 1, -3 }, { 1, -2 }, { 1, -1 },
+};
+
+/* read the codewords in the diagonal zig-zag order of the placement */
+static void read_codewords(dm_placement *p)
+{
+    int nrow = p->nrow, ncol = p->ncol;
+    int chr = 0, row = 4, col = 0;
+    do {
+	if (row == nrow && !col)
+	    place_corner(p, dm_corner1, chr++);
+	if (row == nrow - 2 && !col && ncol % 4)
+	    place_corner(p, dm_corner2, chr++);
+	if (row == nrow - 2 && !col && ncol % 8 == 4)
+	    place_corner(p, dm_corner3, chr++);
+	if (row == nrow + 4 && col == 2 && !(ncol % 8))
+	    place_corner(p, dm_corner4, chr++);
+
+	/* up and to the right */
+	do {
+	    if (row < nrow && col >= 0 && !p->used[row * ncol + col])
+		place_utah(p, row, col, chr++);
+	    row -= 2;
+	    col += 2;
+	} while (row >= 0 && col < ncol);
+	row += 1;
+	col += 3;
+
+	/* then down and to the left */
+	do {
+	    if (row >= 0 && col < ncol && !p->used[row * ncol + col])
+		place_utah(p, row, col, chr++);
+	    row += 2;
+	    col -= 2;
+	} while (row < nrow && col >= 0);
+	row += 3;
+	col += 1;
+    } while (row < nrow || col < ncol);
+}
+
+int _zbar_dm_decode_matrix(const dm_size *size, const unsigned char *modules,
+			   unsigned char **data, unsigned *modifiers)
+{
+    dm_placement p;
+    rs_gf256 gf;
+    unsigned char *bits, *blk, *cw;
+    int rh = size->region_rows, rw = size->region_cols;
+    int ncw = size->ndata + size->necw, nblocks = size->nblocks;
+    int r, c, b, len = -1;
+
+    p.nrow = size->rows / (rh + 2) * rh;
+    p.ncol = size->cols / (rw + 2) * rw;
+    bits   = malloc(p.nrow * p.ncol);
+    p.used = calloc(p.nrow * p.ncol, 1);
+    /* the placement may address a few more codewords than the symbol holds */
+    cw	   = calloc(ncw + 2, 1);
+    blk	   = malloc(ncw / nblocks + 1);
+    if (!bits || !p.used || !cw || !blk)
+	goto done;
+
+    /* drop the finder and alignment patterns around each data region */
+    for (r = 0; r < p.nrow; r++)
+	for (c = 0; c < p.ncol; c++) {
+	    int sr = r / rh * (rh + 2) + r % rh + 1;
+	    int sc = c / rw * (rw + 2) + c % rw + 1;
+	    bits[r * p.ncol + c] = modules[sr * size->cols + sc];
+	}
+    p.bits = bits;
+    p.cw   = cw;
+    read_codewords(&p);
+
+    /* codewords are interleaved over the blocks, ECC after all the data */
+    rs_gf256_init(&gf, DM_PPOLY);
+    for (b = 0; b < nblocks; b++) {
+	int nd = 0, ne = 0, i;
+	for (i = b; i < size->ndata; i += nblocks)
+	    blk[nd++] = cw[i];
+	for (i = size->ndata + b; i < ncw; i += nblocks)
+	    blk[nd + ne++] = cw[i];
+	if (rs_correct(&gf, DM_M0, blk, nd + ne, ne, NULL, 0) < 0)
+	    goto done;
+	for (i = 0; i < nd; i++)
+	    cw[b + i * nblocks] = blk[i];
+    }
+
+    len = _zbar_dm_decode_data(cw, size->ndata, data, modifiers);
+
+done:
+    free(bits);
+    free(p.used);
+    free(cw);
+    free(blk);
+    return (len);
+}
@@ -0,0 +1,335 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+
+/* Data Matrix ECC 200 decoding of the ASCII, C40, Text, X12, EDIFACT and
+ * Base 256 encodations
+ */
+
+#include "config.h"
+
+#include <stdlib.h>
+#include <string.h>
+
+#include "decoder.h"
+#include "dmdec.h"
+
+/* ASCII encodation codewords */
+#define DM_PAD		   129
+#define DM_DIGITS	   130 /* digit pairs 00-99 from here */
+#define DM_LATCH_C40	   230
+#define DM_LATCH_BASE256   231
+#define DM_FNC1		   232
+#define DM_STRUCTURED_APP  233
+#define DM_READER_PROG	   234
+#define DM_UPPER_SHIFT	   235
+#define DM_MACRO05	   236
+#define DM_MACRO06	   237
+#define DM_LATCH_X12	   238
+#define DM_LATCH_TEXT	   239
+#define DM_LATCH_EDIFACT   240
+#define DM_ECI		   241
+#define DM_UNLATCH	   254 /* back to ASCII from C40, Text and X12 */
+#define DM_EDIFACT_UNLATCH 31
+
+/* group separator emitted for FNC1 */
+#define DM_GS 0x1d
+
+static const char dm_c40_shift2[28] = "!\"#$%&'()*+,-./:;<=>?@[\\]^_";
+
+typedef struct dm_text {
+    unsigned char *buf;
+    int len, alloc;
+    int upper;	       /* add 128 to the next character */
+    unsigned modifiers; /* symbology modifiers found */
+    const char *trailer; /* macro trailer appended at the end */
+} dm_text;
+
+static int text_push(dm_text *txt, unsigned c)
+{
+    if (txt->upper) {
+	c += 128;
+	txt->upper = 0;
+    }
+    if (txt->len >= txt->alloc) {
+	int alloc	   = txt->alloc ? 2 * txt->alloc : 64;
+	unsigned char *buf = realloc(txt->buf, alloc);
+	if (!buf)
+	    return (-1);
+	txt->buf   = buf;
+	txt->alloc = alloc;
+    }
+    txt->buf[txt->len++] = c;
+    return (0);
+}
+
+static int text_push_str(dm_text *txt, const char *s)
+{
+    for (; *s; s++)
+	if (text_push(txt, (unsigned char)*s))
+	    return (-1);
+    return (0);
+}
+
+/* FNC1 marks GS1 data in first position, a field separator elsewhere */
+static int text_fnc1(dm_text *txt, int first)
+{
+    if (first) {
+	txt->modifiers |= MOD(ZBAR_MOD_GS1);
+	return (0);
+    }
+    return (text_push(txt, DM_GS));
+}
+
+/* C40 and Text encodation up to the unlatch, returns the index reached */
+static int decode_c40_text(dm_text *txt, const unsigned char *cw, int n,
+			   int i, int text)
+{
+    int shift = 0;
+    while (i + 1 < n && cw[i] != DM_UNLATCH) {
+	/* three base 40 values in each codeword pair */
+	unsigned v = (cw[i] << 8 | cw[i + 1]) - 1, u[3];
+	int j;
+	u[0] = v / 1600;
+	u[1] = v / 40 % 40;
+	u[2] = v % 40;
+	if (u[0] >= 40)
+	    return (-1);
+	i += 2;
+
+	for (j = 0; j < 3; j++) {
+	    int c = u[j], err = 0;
+	    switch (shift) {
+	    case 0:
+		if (c < 3)
+		    shift = c + 1;
+		else if (c == 3)
+		    err = text_push(txt, ' ');
+		else if (c < 14)
+		    err = text_push(txt, '0' + c - 4);
+		else
+		    err = text_push(txt, ((text) ? 'a' : 'A') + c - 14);
+		continue;
+	    case 1:
+		err = text_push(txt, c);
+		break;
+	    case 2:
+		if (c < 27)
+		    err = text_push(txt, dm_c40_shift2[c]);
+		else if (c == 27)
+		    err = text_fnc1(txt, 0);
+		else if (c == 30)
+		    txt->upper = 1;
+		else
+		    return (-1);
+		break;
+	    default:
+		if (!text)
+		    err = text_push(txt, c + 96);
+		else if (!c)
+		    err = text_push(txt, '`');
+		else if (c < 27)
+		    err = text_push(txt, 'A' + c - 1);
+		else if (c < 32)
+		    err = text_push(txt, "{|}~\x7f"[c - 27]);
+		else
+		    return (-1);
+		break;
+	    }
+	    shift = 0;
+	    if (err)
+		return (-1);
+	}
+    }
+    /* a trailing shift pads the last codeword pair */
+    if (i < n && cw[i] == DM_UNLATCH)
+	i++;
+    return (i);
+}
+
+/* X12 encodation up to the unlatch, returns the index reached */
+static int decode_x12(dm_text *txt, const unsigned char *cw, int n, int i)
+{
+    while (i + 1 < n && cw[i] != DM_UNLATCH) {
+	unsigned v = (cw[i] << 8 | cw[i + 1]) - 1, u[3];
+	int j;
+	u[0] = v / 1600;
+	u[1] = v / 40 % 40;
+	u[2] = v % 40;
+	if (u[0] >= 40)
+	    return (-1);
+	i += 2;
+	for (j = 0; j < 3; j++) {
+	    static const char x12[4] = "\r*> ";
+	    int c = u[j], err;
+	    if (c < 4)
+		err = text_push(txt, x12[c]);
+	    else if (c < 14)
+		err = text_push(txt, '0' + c - 4);
+	    else
+		err = text_push(txt, 'A' + c - 14);
+	    if (err)
+		return (-1);
+	}
+    }
+    if (i < n && cw[i] == DM_UNLATCH)
+	i++;
+    return (i);
+}
+
+/* EDIFACT encodation, four 6 bit values in three codewords */
+static int decode_edifact(dm_text *txt, const unsigned char *cw, int n, int i)
+{
+    while (i < n) {
+	unsigned v = 0;
+	int j, k, avail = (n - i < 3) ? n - i : 3;
+	for (j = 0; j < 3; j++)
+	    v = v << 8 | ((j < avail) ? cw[i + j] : 0);
+	for (k = 0; k < 4; k++) {
+	    unsigned c = (v >> (18 - 6 * k)) & 0x3f;
+	    if (c == DM_EDIFACT_UNLATCH)
+		/* the rest of the codeword is ignored */
+		return (i + (6 * k + 13) / 8);
+	    if (6 * k + 6 > 8 * avail)
+		break;
+	    if (text_push(txt, (c & 0x20) ? c : c | 0x40))
+		return (-1);
+	}
+	i += avail;
+    }
+    return (i);
+}
+
+/* undo the 255 state randomization of Base 256 codeword at position i */
+static inline unsigned unrandomize_255(const unsigned char *cw, int i)
+{
+    int r = (149 * (i + 1)) % 255 + 1;
+    return ((cw[i] - r + 256) & 0xff);
+}
+
+/* Base 256 encodation, a length field then as many bytes */
+static int decode_base256(dm_text *txt, const unsigned char *cw, int n, int i)
+{
+    int len, end;
+    if (i >= n)
+	return (-1);
+    len = unrandomize_255(cw, i++);
+    if (!len)
+	/* up to the end of the symbol */
+	len = n - i;
+    else if (len >= 250) {
+	if (i >= n)
+	    return (-1);
+	len = 250 * (len - 249) + unrandomize_255(cw, i++);
+    }
+    end = i + len;
+    if (end > n)
+	return (-1);
+    for (; i < end; i++)
+	if (text_push(txt, unrandomize_255(cw, i)))
+	    return (-1);
+    return (i);
+}
+
+int _zbar_dm_decode_data(const unsigned char *cw, int n, unsigned char **data,
+			 unsigned *modifiers)
+{
+    dm_text txt;
+    int i = 0;
+
+    memset(&txt, 0, sizeof(txt));
+
+    while (i >= 0 && i < n) {
+	int c = cw[i++];
+	if (!c)
+	    i = -1;
+	else if (c <= 128)
+	    i = (text_push(&txt, c - 1)) ? -1 : i;
+	else if (c == DM_PAD)
+	    break;
+	else if (c < DM_LATCH_C40) {
+	    c -= DM_DIGITS;
+	    if (text_push(&txt, '0' + c / 10) || text_push(&txt, '0' + c % 10))
+		i = -1;
+	} else
+	    switch (c) {
+	    case DM_LATCH_C40:
+	    case DM_LATCH_TEXT:
+		i = decode_c40_text(&txt, cw, n, i, c == DM_LATCH_TEXT);
+		break;
+	    case DM_LATCH_X12:
+		i = decode_x12(&txt, cw, n, i);
+		break;
+	    case DM_LATCH_EDIFACT:
+		i = decode_edifact(&txt, cw, n, i);
+		break;
+	    case DM_LATCH_BASE256:
+		i = decode_base256(&txt, cw, n, i);
+		break;
+	    case DM_FNC1:
+		if (text_fnc1(&txt, i == 1))
+		    i = -1;
+		break;
+	    case DM_UPPER_SHIFT:
+		txt.upper = 1;
+		break;
+	    case DM_MACRO05:
+	    case DM_MACRO06:
+		/* only valid as the first codeword */
+		if (i != 1 ||
+		    text_push_str(&txt, (c == DM_MACRO05) ? "[)>\x1e" "05\x1d" :
+							   "[)>\x1e" "06\x1d"))
+		    i = -1;
+		txt.trailer = "\x1e\x04";
+		break;
+	    case DM_STRUCTURED_APP:
+		/* FIXME structured append symbols are not combined */
+		i += 3;
+		break;
+	    case DM_READER_PROG:
+		break;
+	    case DM_ECI:
+		/* FIXME ECIs are not interpreted, the data is passed through */
+		if (i >= n)
+		    i = -1;
+		else if (cw[i] <= 127)
+		    i += 1;
+		else if (cw[i] <= 191)
+		    i += 2;
+		else
+		    i += 3;
+		break;
+	    default:
+		i = -1;
+		break;
+	    }
+    }
+
+    if (i >= 0 && txt.trailer && text_push_str(&txt, txt.trailer))
+	i = -1;
+    txt.upper = 0;
+    if (i < 0 || text_push(&txt, '\0')) {
+	free(txt.buf);
+	return (-1);
+    }
+    *data = txt.buf;
+    *modifiers = txt.modifiers;
+    return (txt.len - 1);
+}
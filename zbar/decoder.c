@@ -62,6 +62,7 @@ zbar_decoder_t *zbar_decoder_create()
 #endif
 #if ENABLE_I25 == 1
     dcode->i25.config		      = 1 << ZBAR_CFG_ENABLE;
+    dcode->i25.config_itf14	      = 1 << ZBAR_CFG_EMIT_CHECK;
     CFG(dcode->i25, ZBAR_CFG_MIN_LEN) = 6;
 #endif
 #if ENABLE_DATABAR == 1
@@ -69,6 +70,14 @@ zbar_decoder_t *zbar_decoder_create()
 	((1 << ZBAR_CFG_ENABLE) | (1 << ZBAR_CFG_EMIT_CHECK));
     dcode->databar.config_exp =
 	((1 << ZBAR_CFG_ENABLE) | (1 << ZBAR_CFG_EMIT_CHECK));
+    dcode->databar.config_ltd =
+	((1 << ZBAR_CFG_ENABLE) | (1 << ZBAR_CFG_EMIT_CHECK));
+    dcode->databar.config_stk =
+	((1 << ZBAR_CFG_ENABLE) | (1 << ZBAR_CFG_EMIT_CHECK));
+    dcode->databar.config_stk_omni =
+	((1 << ZBAR_CFG_ENABLE) | (1 << ZBAR_CFG_EMIT_CHECK));
+    dcode->databar.config_exp_stk =
+	((1 << ZBAR_CFG_ENABLE) | (1 << ZBAR_CFG_EMIT_CHECK));
     dcode->databar.csegs = 4;
     dcode->databar.segs	 = calloc(4, sizeof(*dcode->databar.segs));
 #endif
@@ -90,11 +99,19 @@ zbar_decoder_t *zbar_decoder_create()
     dcode->pdf417.config = 1 << ZBAR_CFG_ENABLE;
 #endif
 #if ENABLE_QRCODE == 1
-    dcode->qrf.config = 1 << ZBAR_CFG_ENABLE;
+    dcode->qrf.config	    = 1 << ZBAR_CFG_ENABLE;
+    dcode->qrf.config_micro = 1 << ZBAR_CFG_ENABLE;
+    dcode->qrf.config_rmqr  = 1 << ZBAR_CFG_ENABLE;
 #endif
 #if ENABLE_SQCODE == 1
     dcode->sqf.config = 1 << ZBAR_CFG_ENABLE;
 #endif
+#if ENABLE_DATAMATRIX == 1
+    dcode->dmf.config = 1 << ZBAR_CFG_ENABLE;
+#endif
+#if ENABLE_AZTEC == 1
+    dcode->azf.config = 1 << ZBAR_CFG_ENABLE;
+#endif
 
     zbar_decoder_reset(dcode);
     return (dcode);
@@ -240,7 +257,9 @@ zbar_symbol_type_t zbar_decode_width(zbar_decoder_t *dcode, unsigned w)
 
     /* each decoder processes width stream in parallel */
 #if ENABLE_QRCODE == 1
-    if (TEST_CFG(dcode->qrf.config, ZBAR_CFG_ENABLE) &&
+    if (TEST_CFG(dcode->qrf.config | dcode->qrf.config_micro |
+		     dcode->qrf.config_rmqr,
+		 ZBAR_CFG_ENABLE) &&
 	(tmp = _zbar_find_qr(dcode)) > ZBAR_PARTIAL)
 	sym = tmp;
 #endif
@@ -264,7 +283,10 @@ zbar_symbol_type_t zbar_decode_width(zbar_decoder_t *dcode, unsigned w)
 	sym = tmp;
 #endif
 #if ENABLE_DATABAR == 1
-    if (TEST_CFG(dcode->databar.config | dcode->databar.config_exp,
+    if (TEST_CFG(dcode->databar.config | dcode->databar.config_exp |
+		     dcode->databar.config_ltd | dcode->databar.config_stk |
+		     dcode->databar.config_stk_omni |
+		     dcode->databar.config_exp_stk,
 		 ZBAR_CFG_ENABLE) &&
 	(tmp = _zbar_decode_databar(dcode)) > ZBAR_PARTIAL)
 	sym = tmp;
@@ -275,7 +297,8 @@ zbar_symbol_type_t zbar_decode_width(zbar_decoder_t *dcode, unsigned w)
 	sym = tmp;
 #endif
 #if ENABLE_I25 == 1
-    if (TEST_CFG(dcode->i25.config, ZBAR_CFG_ENABLE) &&
+    if (TEST_CFG(dcode->i25.config | dcode->i25.config_itf14,
+		 ZBAR_CFG_ENABLE) &&
 	(tmp = _zbar_decode_i25(dcode)) > ZBAR_PARTIAL)
 	sym = tmp;
 #endif
@@ -339,6 +362,10 @@ decoder_get_configp(const zbar_decoder_t *dcode, zbar_symbol_type_t sym)
     case ZBAR_I25:
 	config = &dcode->i25.config;
 	break;
+
+    case ZBAR_ITF14:
+	config = &dcode->i25.config_itf14;
+	break;
 #endif
 
 #if ENABLE_DATABAR == 1
@@ -348,6 +375,18 @@ decoder_get_configp(const zbar_decoder_t *dcode, zbar_symbol_type_t sym)
     case ZBAR_DATABAR_EXP:
 	config = &dcode->databar.config_exp;
 	break;
+    case ZBAR_DATABAR_LTD:
+	config = &dcode->databar.config_ltd;
+	break;
+    case ZBAR_DATABAR_STK:
+	config = &dcode->databar.config_stk;
+	break;
+    case ZBAR_DATABAR_STK_OMNI:
+	config = &dcode->databar.config_stk_omni;
+	break;
+    case ZBAR_DATABAR_EXP_STK:
+	config = &dcode->databar.config_exp_stk;
+	break;
 #endif
 
 #if ENABLE_CODABAR == 1
@@ -384,6 +423,14 @@ decoder_get_configp(const zbar_decoder_t *dcode, zbar_symbol_type_t sym)
     case ZBAR_QRCODE:
 	config = &dcode->qrf.config;
 	break;
+
+    case ZBAR_MICROQR:
+	config = &dcode->qrf.config_micro;
+	break;
+
+    case ZBAR_RMQR:
+	config = &dcode->qrf.config_rmqr;
+	break;
 #endif
 
 #if ENABLE_SQCODE == 1
@@ -392,6 +439,18 @@ decoder_get_configp(const zbar_decoder_t *dcode, zbar_symbol_type_t sym)
 	break;
 #endif
 
+#if ENABLE_DATAMATRIX == 1
+    case ZBAR_DATAMATRIX:
+	config = &dcode->dmf.config;
+	break;
+#endif
+
+#if ENABLE_AZTEC == 1
+    case ZBAR_AZTEC:
+	config = &dcode->azf.config;
+	break;
+#endif
+
     default:
 	config = NULL;
     }
@@ -544,15 +603,24 @@ int zbar_decoder_set_config(zbar_decoder_t *dcode, zbar_symbol_type_t sym,
 						  ZBAR_ISBN10,
 						  ZBAR_ISBN13,
 						  ZBAR_I25,
+						  ZBAR_ITF14,
 						  ZBAR_DATABAR,
 						  ZBAR_DATABAR_EXP,
+						  ZBAR_DATABAR_LTD,
+						  ZBAR_DATABAR_STK,
+						  ZBAR_DATABAR_STK_OMNI,
+						  ZBAR_DATABAR_EXP_STK,
 						  ZBAR_CODABAR,
 						  ZBAR_CODE39,
 						  ZBAR_CODE93,
 						  ZBAR_CODE128,
 						  ZBAR_QRCODE,
+						  ZBAR_MICROQR,
+						  ZBAR_RMQR,
 						  ZBAR_SQCODE,
 						  ZBAR_PDF417,
+						  ZBAR_DATAMATRIX,
+						  ZBAR_AZTEC,
 						  0 };
 	const zbar_symbol_type_t *symp;
 	for (symp = all; *symp; symp++)
@@ -63,6 +63,12 @@
 #if ENABLE_SQCODE == 1
 #include "decoder/sq_finder.h"
 #endif
+#if ENABLE_DATAMATRIX == 1
+#include "decoder/dm_finder.h"
+#endif
+#if ENABLE_AZTEC == 1
+#include "decoder/az_finder.h"
+#endif
 
 /* size of bar width history (implementation assumes power of two) */
 #ifndef DECODE_WINDOW
@@ -138,6 +144,12 @@ struct zbar_decoder_s {
 #if ENABLE_SQCODE == 1
     sq_finder_t sqf; /* SQ Code finder state */
 #endif
+#if ENABLE_DATAMATRIX == 1
+    dm_finder_t dmf; /* Data Matrix finder state */
+#endif
+#if ENABLE_AZTEC == 1
+    az_finder_t azf; /* Aztec finder state */
+#endif
 };
 
 /* return current element color */
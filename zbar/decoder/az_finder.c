@@ -0,0 +1,7 @@
+#include "az_finder.h"
+#include "decoder.h"
+
+unsigned _zbar_decoder_get_az_finder_config(zbar_decoder_t *dcode)
+{
+    return dcode->azf.config;
+}
@@ -0,0 +1,9 @@
+#ifndef _DECODER_AZ_FINDER_H_
+#define _DECODER_AZ_FINDER_H_
+
+/* Aztec symbol finder state */
+typedef struct az_finder_s {
+    unsigned config;
+} az_finder_t;
+
+#endif
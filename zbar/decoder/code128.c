@@ -354,11 +354,38 @@ static inline unsigned postprocess_c(zbar_decoder_t *dcode, unsigned start,
     return (delta);
 }
 
+/* convert extended ASCII (ISO-8859-1) characters to UTF-8 in place */
+static inline unsigned char postprocess_ext(zbar_decoder_t *dcode,
+					    unsigned *len)
+{
+    unsigned i, j, next = 0;
+    for (i = 0; i < *len; i++)
+	if (dcode->buf[i] & 0x80)
+	    next++;
+    if (!next)
+	return (0);
+    next += *len;
+    if (size_buf(dcode, next + 1)) {
+	dbprintf(2, " [overflow]\n");
+	return (1);
+    }
+    for (i = *len, j = next; i > 0;) {
+	unsigned char c = dcode->buf[--i];
+	if (c & 0x80) {
+	    dcode->buf[--j] = 0x80 | (c & 0x3f);
+	    c		    = 0xc0 | (c >> 6);
+	}
+	dcode->buf[--j] = c;
+    }
+    *len = next;
+    return (0);
+}
+
 /* resolve scan direction and convert to ASCII */
 static inline unsigned char postprocess(zbar_decoder_t *dcode)
 {
-    unsigned i, j, cexp;
-    unsigned char code		= 0, charset;
+    unsigned i, j, n, cexp;
+    unsigned char code		= 0, charset, ext = 0;
     code128_decoder_t *dcode128 = &dcode->code128;
     dbprintf(2, "\n    postproc len=%d", dcode128->character);
     dcode->modifiers = 0;
@@ -388,7 +415,8 @@ static inline unsigned char postprocess(zbar_decoder_t *dcode)
     cexp    = (code == START_C) ? 1 : 0;
     dbprintf(2, " start=%c", 'A' + charset);
 
-    for (i = 1, j = 0; i < dcode128->character - 2; i++) {
+    /* n counts symbol characters, i is shifted by set C expansion */
+    for (i = 1, j = 0, n = 1; i < dcode128->character - 2; i++, n++) {
 	unsigned char code = dcode->buf[i];
 	zassert(!(code & 0x80), 1,
 		"i=%x j=%x code=%02x charset=%x cexp=%x %s\n", i, j, code,
@@ -404,6 +432,10 @@ static inline unsigned char postprocess(zbar_decoder_t *dcode)
 	    if ((!charset || (charset == 0x81)) && (code >= 0x60))
 		/* convert character set A to ASCII */
 		code -= 0x60;
+	    /* FNC4 shift toggles the extended ASCII latch for one character */
+	    if ((ext ^ (ext >> 1)) & 1)
+		code |= 0x80;
+	    ext &= ~2;
 	    dcode->buf[j++] = code;
 	    if (charset & 0x80)
 		charset &= 0x7f;
@@ -424,16 +456,17 @@ static inline unsigned char postprocess(zbar_decoder_t *dcode)
 	    if (code < CODE_C) {
 		if (code == SHIFT)
 		    charset |= 0x80;
-		else if (code == FNC2) {
-		    /* FIXME FNC2 - message append */
-		} else if (code == FNC3) {
-		    /* FIXME FNC3 - initialize */
-		}
+		else if (code == FNC2)
+		    /* FNC2 - append the data to the next symbol */
+		    dcode->modifiers |= MOD(ZBAR_MOD_APPEND);
+		else if (code == FNC3)
+		    /* FNC3 - initialize or reprogram the reader */
+		    dcode->modifiers |= MOD(ZBAR_MOD_READER_INIT);
 	    } else if (code == FNC1) {
 		/* FNC1 - Code 128 subsets or ASCII 0x1d */
-		if (i == 1)
+		if (!j)
 		    dcode->modifiers |= MOD(ZBAR_MOD_GS1);
-		else if (i == 2)
+		else if (n == 2)
 		    dcode->modifiers |= MOD(ZBAR_MOD_AIM);
 		else if (i < dcode->code128.character - 3)
 		    dcode->buf[j++] = 0x1d;
@@ -450,9 +483,12 @@ static inline unsigned char postprocess(zbar_decoder_t *dcode)
 					       dcode->code128.character));
 		if (newset != charset)
 		    charset = newset;
-		else {
-		    /* FIXME FNC4 - extended ASCII */
-		}
+		else if (ext & 2)
+		    /* FNC4 FNC4 - latch/unlatch extended ASCII */
+		    ext = (ext ^ 1) & ~2;
+		else
+		    /* FNC4 - extended ASCII for the next character */
+		    ext |= 2;
 	    }
 	    if (charset & 0x2)
 		cexp = i + 1;
@@ -464,6 +500,10 @@ static inline unsigned char postprocess(zbar_decoder_t *dcode)
 		_zbar_decoder_buf_dump(dcode->buf, dcode->code128.character));
 	j += postprocess_c(dcode, cexp, i, j) * 2;
     }
+    /* keep ISO-8859-1 bytes if configured with ZBAR_CFG_BINARY */
+    if (!TEST_CFG(dcode128->config, ZBAR_CFG_BINARY) &&
+	postprocess_ext(dcode, &j))
+	return (1);
     zassert(j < dcode->buf_alloc, 1, "j=%02x %s\n", j,
 	    _zbar_decoder_buf_dump(dcode->buf, dcode->code128.character));
     dcode->buflen	     = j;
@@ -207,9 +207,72 @@ static inline signed char code39_decode_start(zbar_decoder_t *dcode)
     return (ZBAR_PARTIAL);
 }
 
-static inline int code39_postprocess(zbar_decoder_t *dcode)
+/* validate the mod 43 check character following n data characters */
+static inline int code39_checksum(zbar_decoder_t *dcode, int n)
+{
+    int i;
+    unsigned chk = 0;
+    for (i = 0; i < n; i++)
+	chk += dcode->buf[i];
+    chk %= 43;
+    dbprintf(2, " chk=%02x(%02x)", chk, dcode->buf[n]);
+    return (chk != dcode->buf[n]);
+}
+
+/* decode a Full ASCII shift ($, /, + or %) and letter pair
+ * @returns the ASCII character or -1 if the pair is not valid
+ */
+static inline int code39_ascii(unsigned char shift, unsigned char c)
+{
+    if (c < 10 || c > 35)
+	return (-1);
+    c -= 10;
+    switch (shift) {
+    case 0x27: /* $A-$Z */
+	return (c + 0x01);
+    case 0x28: /* /A-/O, /Z */
+	if (c < 15)
+	    return (c + '!');
+	return ((c == 25) ? ':' : -1);
+    case 0x29: /* +A-+Z */
+	return (c + 'a');
+    case 0x2a: /* %A-%Z */
+	if (c < 5)
+	    return (c + 0x1b);
+	if (c < 10)
+	    return (c - 5 + ';');
+	if (c < 15)
+	    return (c - 10 + '[');
+	if (c < 20)
+	    return (c - 15 + '{');
+	if (c == 20)
+	    return (0x00);
+	if (c == 21)
+	    return ('@');
+	if (c == 22)
+	    return ('`');
+	return (0x7f);
+    }
+    return (-1);
+}
+
+/* check whether n characters are valid Full ASCII data */
+static inline int code39_is_ascii(zbar_decoder_t *dcode, int n)
 {
     int i;
+    for (i = 0; i < n; i++) {
+	unsigned char c = dcode->buf[i];
+	if (c < 0x27 || c > 0x2a)
+	    continue;
+	if (++i >= n || code39_ascii(c, dcode->buf[i]) < 0)
+	    return (0);
+    }
+    return (1);
+}
+
+static inline int code39_postprocess(zbar_decoder_t *dcode)
+{
+    int i, j, n, chk = 0, ascii;
     code39_decoder_t *dcode39 = &dcode->code39;
     dcode->direction	      = 1 - 2 * dcode39->direction;
     if (dcode39->direction) {
@@ -222,14 +285,35 @@ static inline int code39_postprocess(zbar_decoder_t *dcode)
 	    dcode->buf[j] = code;
 	}
     }
-    for (i = 0; i < dcode39->character; i++)
-	dcode->buf[i] = ((dcode->buf[i] < 0x2b) ?
-				   code39_characters[(unsigned)dcode->buf[i]] :
-				   '?');
-    zassert(i < dcode->buf_alloc, -1, "i=%02x %s\n", i,
+
+    n = dcode39->character;
+    if (TEST_CFG(dcode39->config, ZBAR_CFG_ADD_CHECK)) {
+	/* last character is the mod 43 check character */
+	if (n < 2 || code39_checksum(dcode, --n)) {
+	    dbprintf(2, " [checksum error]\n");
+	    return (1);
+	}
+	chk = TEST_CFG(dcode39->config, ZBAR_CFG_EMIT_CHECK);
+    }
+
+    /* data with invalid escapes is not Full ASCII, leave it as is */
+    ascii = TEST_CFG(dcode39->config, ZBAR_CFG_ASCII) &&
+	    code39_is_ascii(dcode, n);
+
+    for (i = j = 0; i < n; i++) {
+	unsigned char c = dcode->buf[i];
+	if (ascii && c >= 0x27 && c <= 0x2a)
+	    c = code39_ascii(c, dcode->buf[++i]);
+	else
+	    c = (c < 0x2b) ? code39_characters[c] : '?';
+	dcode->buf[j++] = c;
+    }
+    if (chk)
+	dcode->buf[j++] = code39_characters[(unsigned)dcode->buf[n]];
+    zassert(j < dcode->buf_alloc, -1, "j=%02x %s\n", j,
 	    _zbar_decoder_buf_dump(dcode->buf, dcode39->character));
-    dcode->buflen    = i;
-    dcode->buf[i]    = '\0';
+    dcode->buflen    = j;
+    dcode->buf[j]    = '\0';
     dcode->modifiers = 0;
     return (0);
 }
@@ -281,7 +365,6 @@ zbar_symbol_type_t _zbar_decode_code39(zbar_decoder_t *dcode)
 		      dcode39->character > CFG(*dcode39, ZBAR_CFG_MAX_LEN)))
 		dbprintf(2, " [invalid len]\n");
 	    else if (!code39_postprocess(dcode)) {
-		/* FIXME checksum */
 		dbprintf(2, " [valid end]\n");
 		sym = ZBAR_CODE39;
 	    }
@@ -417,10 +417,10 @@ static inline int databar_postprocess_exp(zbar_decoder_t *dcode, int *data)
 /* convert from heterogeneous base {1597,2841}
  * to base 10 character representation
  */
-static inline void databar_postprocess(zbar_decoder_t *dcode, unsigned d[4])
+static inline void databar_postprocess(zbar_decoder_t *dcode, unsigned d[4],
+				       unsigned config)
 {
     unsigned long r;
-    databar_decoder_t *db = &dcode->databar;
     int i;
     unsigned c, chk = 0;
     unsigned char *buf = dcode->buf;
@@ -483,7 +483,7 @@ static inline void databar_postprocess(zbar_decoder_t *dcode, unsigned d[4])
     }
 
     /* NB linkage flag not supported */
-    if (TEST_CFG(db->config, ZBAR_CFG_EMIT_CHECK)) {
+    if (TEST_CFG(config, ZBAR_CFG_EMIT_CHECK)) {
 	chk %= 10;
 	if (chk)
 	    chk = 10 - chk;
@@ -503,6 +503,28 @@ static inline int check_width(unsigned wf, unsigned wd, unsigned n)
     return (wf - dwf <= wd && wd <= wf + dwf);
 }
 
+/* compare the scans two segments were encountered on: 0 if they share
+ * some scans or follow each other (same row), 1 if they are on adjacent
+ * rows of a stacked symbol, apart by a separator of at most the height
+ * of a row, -1 otherwise
+ */
+static inline int check_rows(const databar_segment_t *s0,
+			     const databar_segment_t *s1)
+{
+    unsigned gap, height;
+    if (s1->row0 > s0->row1) {
+	gap    = s1->row0 - s0->row1;
+	height = s0->row1 - s0->row0 + 1;
+    } else if (s0->row0 > s1->row1) {
+	gap    = s0->row0 - s1->row1;
+	height = s1->row1 - s1->row0 + 1;
+    } else
+	return (0);
+    if (gap < 2)
+	return (0);
+    return ((gap <= height + 1 || gap <= 3) ? 1 : -1);
+}
+
 static inline void merge_segment(databar_decoder_t *db, databar_segment_t *seg)
 {
     unsigned csegs = db->csegs;
@@ -520,7 +542,10 @@ static inline void merge_segment(databar_decoder_t *db, databar_segment_t *seg)
 	    seg->count = cnt;
 	    seg->partial &= s->partial;
 	    seg->width = (3 * seg->width + s->width + 2) / 4;
-	    s->finder  = -1;
+	    /* the same row continues, a later sighting starts over */
+	    if (check_rows(s, seg) >= 0)
+		seg->row0 = s->row0;
+	    s->finder = -1;
 	    dbprintf(2, " dup@%d(%d,%d)", i, cnt,
 		     (db->epoch - seg->epoch) & 0xff);
 	} else if (s->finder >= 0) {
@@ -536,11 +561,12 @@ static inline zbar_symbol_type_t match_segment(zbar_decoder_t *dcode,
 {
     databar_decoder_t *db = &dcode->databar;
     unsigned csegs = db->csegs, maxage = 0xfff;
-    int i0, i1, i2, maxcnt = 0;
+    int i0, i1, i2, maxcnt = 0, maxstk = 0;
     databar_segment_t *smax[3] = {
 	NULL,
     };
-    unsigned d[4];
+    unsigned d[4], config;
+    zbar_symbol_type_t sym;
 
     if (seg->partial && seg->count < 4)
 	return (ZBAR_PARTIAL);
@@ -588,18 +614,35 @@ static inline zbar_symbol_type_t match_segment(zbar_decoder_t *dcode,
 	    for (i2 = i1 + 1; i2 < csegs; i2++) {
 		databar_segment_t *s2 = db->segs + i2;
 		unsigned cnt, age2, age;
+		int stk, stk2;
 		if (i2 == i0 || s2->finder != s1->finder || s2->exp ||
 		    s2->color != s1->color || s2->side == s1->side ||
 		    s2->check != chk || (s2->partial && s2->count < 4) ||
 		    !check_width(seg->width, s2->width, 14))
 		    continue;
+
+		/* the halves are found on the same scans, or on the two
+		 * adjacent rows of a stacked symbol
+		 */
+		stk  = check_rows(seg, s1);
+		stk2 = check_rows(s0, s2);
+		if (stk < 0 || stk2 < 0)
+		    continue;
+		stk &= stk2;
+		if (!TEST_CFG((stk) ? db->config_stk | db->config_stk_omni :
+				      db->config,
+			      ZBAR_CFG_ENABLE))
+		    continue;
+
 		age2 = (db->epoch - s2->epoch) & 0xff;
 		age  = age1 + age2;
 		cnt  = s0->count + s1->count + s2->count;
-		dbprintf(2, " [%d] MATCH cnt=%d age=%d", i2, cnt, age);
+		dbprintf(2, " [%d] MATCH cnt=%d age=%d stk=%d", i2, cnt, age,
+			 stk);
 		if (maxcnt < cnt || (maxcnt == cnt && maxage > age)) {
 		    maxcnt  = cnt;
 		    maxage  = age;
+		    maxstk  = stk;
 		    smax[0] = s0;
 		    smax[1] = s1;
 		    smax[2] = s2;
@@ -611,6 +654,24 @@ static inline zbar_symbol_type_t match_segment(zbar_decoder_t *dcode,
     if (!smax[0])
 	return (ZBAR_PARTIAL);
 
+    if (maxstk) {
+	/* the row found first is measured to tell the stacked variants
+	 * apart, which depends on the spacing of the scans
+	 */
+	unsigned row0 = smax[1]->row0, row1 = smax[1]->row1;
+	if (row0 > smax[2]->row0)
+	    row0 = smax[2]->row0;
+	if (row1 < smax[2]->row1)
+	    row1 = smax[2]->row1;
+	db->stk_rows  = row1 - row0 + 1;
+	db->stk_width = seg->width;
+	sym	      = ZBAR_DATABAR_STK;
+	config	      = db->config_stk;
+    } else {
+	sym    = ZBAR_DATABAR;
+	config = db->config;
+    }
+
     d[(seg->color << 1) | seg->side] = seg->data;
     for (i0 = 0; i0 < 3; i0++) {
 	d[(smax[i0]->color << 1) | smax[i0]->side] = smax[i0]->data;
@@ -622,13 +683,13 @@ static inline zbar_symbol_type_t match_segment(zbar_decoder_t *dcode,
     if (size_buf(dcode, 18))
 	return (ZBAR_PARTIAL);
 
-    if (acquire_lock(dcode, ZBAR_DATABAR))
+    if (acquire_lock(dcode, sym))
 	return (ZBAR_PARTIAL);
 
-    databar_postprocess(dcode, d);
+    databar_postprocess(dcode, d, config);
     dcode->modifiers = MOD(ZBAR_MOD_GS1);
     dcode->direction = 1 - 2 * (seg->side ^ seg->color ^ 1);
-    return (ZBAR_DATABAR);
+    return (sym);
 }
 
 static inline unsigned lookup_sequence(databar_segment_t *seg, int fixed,
@@ -672,9 +733,10 @@ match_segment_exp(zbar_decoder_t *dcode, databar_segment_t *seg, int dir)
 {
     databar_decoder_t *db = &dcode->databar;
     int bestsegs[22], i = 0, segs[22], seq[22];
-    int ifixed = seg - db->segs, fixed = IDX(seg), maxcnt = 0;
+    int ifixed = seg - db->segs, fixed = IDX(seg), maxcnt = 0, maxstk = 0;
     int iseg[DATABAR_MAX_SEGMENTS];
     unsigned csegs = db->csegs, width = seg->width, maxage = 0x7fff;
+    zbar_symbol_type_t sym;
 
     bestsegs[0] = segs[0] = seq[1] = -1;
     seq[0]			   = 0;
@@ -691,10 +753,10 @@ match_segment_exp(zbar_decoder_t *dcode, databar_segment_t *seg, int dir)
     for (i = 0;; i--) {
 	unsigned cnt, chk, age;
 	unsigned data0, chk0;
+	int j, stk;
 	if (!i)
 	    dbprintf(2, "\n   ");
 	for (; i >= 0 && seq[i] >= 0; i--) {
-	    int j;
 	    dbprintf(2, " [%d]%d", i, seq[i]);
 
 	    if (seq[i] == fixed) {
@@ -751,13 +813,28 @@ match_segment_exp(zbar_decoder_t *dcode, databar_segment_t *seg, int dir)
 	if (chk != chk0)
 	    continue;
 
-	dbprintf(2, " cnt=%d age=%d", cnt, age);
+	/* consecutive segments are found on the same scans (one row),
+	 * or on adjacent rows of a stacked symbol
+	 */
+	for (j = 1, stk = 0; segs[j] >= 0; j++) {
+	    int rows = check_rows(db->segs + segs[j - 1], db->segs + segs[j]);
+	    if (rows < 0)
+		break;
+	    stk |= rows;
+	}
+	if (segs[j] >= 0 ||
+	    !TEST_CFG((stk) ? db->config_exp_stk : db->config_exp,
+		      ZBAR_CFG_ENABLE))
+	    continue;
+
+	dbprintf(2, " cnt=%d age=%d stk=%d", cnt, age, stk);
 	if (maxcnt > cnt || (maxcnt == cnt && maxage <= age))
 	    continue;
 
 	dbprintf(2, " !");
 	maxcnt = cnt;
 	maxage = age;
+	maxstk = stk;
 	for (i = 0; segs[i] >= 0; i++)
 	    bestsegs[i] = segs[i];
 	bestsegs[i] = -1;
@@ -766,14 +843,15 @@ match_segment_exp(zbar_decoder_t *dcode, databar_segment_t *seg, int dir)
     if (bestsegs[0] < 0)
 	return (ZBAR_PARTIAL);
 
-    if (acquire_lock(dcode, ZBAR_DATABAR_EXP))
+    sym = (maxstk) ? ZBAR_DATABAR_EXP_STK : ZBAR_DATABAR_EXP;
+    if (acquire_lock(dcode, sym))
 	return (ZBAR_PARTIAL);
 
     for (i = 0; bestsegs[i] >= 0; i++)
 	segs[i] = db->segs[bestsegs[i]].data;
 
     if (databar_postprocess_exp(dcode, segs)) {
-	release_lock(dcode, ZBAR_DATABAR_EXP);
+	release_lock(dcode, sym);
 	return (ZBAR_PARTIAL);
     }
 
@@ -789,7 +867,7 @@ match_segment_exp(zbar_decoder_t *dcode, databar_segment_t *seg, int dir)
      */
     dcode->direction = (1 - 2 * (seg->side ^ seg->color)) * dir;
     dcode->modifiers = MOD(ZBAR_MOD_GS1);
-    return (ZBAR_DATABAR_EXP);
+    return (sym);
 }
 #undef IDX
 
@@ -1141,7 +1219,10 @@ static inline zbar_symbol_type_t decode_finder(zbar_decoder_t *dcode)
 	     0x1f;
     dbprintf(2, " finder=%d", finder);
     if (finder == 0x1f ||
-	!TEST_CFG((finder < 9) ? db->config : db->config_exp, ZBAR_CFG_ENABLE))
+	!TEST_CFG((finder < 9) ?
+		      db->config | db->config_stk | db->config_stk_omni :
+		      db->config_exp | db->config_exp_stk,
+		  ZBAR_CFG_ENABLE))
 	return (ZBAR_NONE);
 
     zassert(finder >= 0, ZBAR_NONE, "dir=%d sig=%04x f=%d\n", dir, sig & 0xfff,
@@ -1160,6 +1241,8 @@ static inline zbar_symbol_type_t decode_finder(zbar_decoder_t *dcode)
     seg->count	 = 1;
     seg->width	 = s;
     seg->epoch	 = db->epoch;
+    seg->row0	 = db->row;
+    seg->row1	 = db->row;
 
     rc = decode_char(dcode, seg, 12 - dir, -1);
     if (!rc)
@@ -1173,7 +1256,219 @@ static inline zbar_symbol_type_t decode_finder(zbar_decoder_t *dcode)
     return (rc);
 }
 
-zbar_symbol_type_t _zbar_decode_databar(zbar_decoder_t *dcode)
+/* DataBar Limited character groups, by odd (space) modules */
+static const struct ltd_group_s {
+    unsigned char nodd, wodd, weven;
+    unsigned short teven;
+    unsigned sum;
+} ltd_groups[] = {
+    { 17, 6, 3, 28, 0 },	 { 13, 5, 4, 728, 183064 },
+    { 9, 3, 6, 6454, 820064 },	 { 15, 5, 4, 203, 1000776 },
+    { 11, 4, 5, 2408, 1491021 }, { 19, 8, 1, 1, 1979845 },
+    { 7, 1, 8, 16632, 1996939 },
+};
+
+/* DataBar Limited check character patterns, in checksum order */
+static const unsigned short ltd_checks[89] = {
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12,
+    13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+    26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38,
+    39, 40, 41, 42, 43, 45, 52, 57, 63, 64, 65, 66, 73,
+    74, 75, 76, 77, 78, 79, 82, 126, 127, 128, 129, 130, 132,
+    141, 142, 143, 144, 145, 146, 210, 211, 212, 213, 214, 215, 216,
+    217, 220, 316, 317, 318, 319, 320, 322, 323, 326, 337
+};
+
+static inline int combins(int n, int r)
+{
+    unsigned long v = 1;
+    int i;
+    if (r < 0 || r > n)
+	return (0);
+    for (i = 1; i <= r; i++)
+	v = v * (n - r + i) / i;
+    return (v);
+}
+
+/* value of k element widths (of at most wmax modules) among all such
+ * patterns in lexicographic order, which must include a narrow element
+ * unless nonarrow is set
+ */
+static int calc_value(const int *w, int k, int wmax, int nonarrow)
+{
+    int i, n = 0, v = 0, narrow = 0;
+    for (i = 0; i < k; i++)
+	n += w[i];
+    for (i = 0; i < k - 1; i++) {
+	int e;
+	for (e = 1, narrow |= 1 << i; e < w[i]; e++, narrow &= ~(1 << i)) {
+	    int sub = combins(n - e - 1, k - i - 2);
+	    if (!nonarrow && !narrow && n - e - (k - i - 1) >= k - i - 1)
+		sub -= combins(n - e - (k - i), k - i - 2);
+	    if (k - i - 1 > 1) {
+		int less = 0, m;
+		for (m = n - e - (k - i - 2); m > wmax; m--)
+		    less += combins(n - e - m - 1, k - i - 3);
+		sub -= less * (k - 1 - i);
+	    } else if (n - e > wmax)
+		sub--;
+	    v += sub;
+	}
+	n -= e;
+    }
+    return (v);
+}
+
+/* element widths of a 14 element Limited character of n modules,
+ * starting with a space, from the bar+space pair widths
+ */
+static inline int decode_ltd_char(const unsigned *w, int n, int *e)
+{
+    unsigned s = 0;
+    int i, bmin = n;
+    for (i = 0; i < 14; i++)
+	s += w[i];
+    if (!s)
+	return (-1);
+
+    e[0] = 0;
+    for (i = 0; i < 13; i++) {
+	int p = (2 * (w[i] + w[i + 1]) * n + s) / (2 * s);
+	e[i + 1] = p - e[i];
+	if ((i & 1) == 0 && bmin > e[i + 1])
+	    bmin = e[i + 1];
+    }
+
+    /* the bars always include a narrow element */
+    for (i = 0, s = 0; i < 14; i++) {
+	e[i] += (i & 1) ? 1 - bmin : bmin - 1;
+	if (e[i] < 1)
+	    return (-1);
+	s += e[i];
+    }
+    return ((s == n) ? 0 : -1);
+}
+
+/* decode Limited elements in symbol order: leading space, left guard,
+ * left, check and right characters, right guard and trailing space
+ */
+static inline zbar_symbol_type_t decode_ltd(zbar_decoder_t *dcode,
+					    const unsigned *w)
+{
+    databar_decoder_t *db = &dcode->databar;
+    unsigned s = 0, chk = 0, mul = 1;
+    unsigned long long v;
+    int e[14], d[2], i, j;
+
+    for (i = 1; i < 46; i++)
+	s += w[i];
+    /* 73 modules between 1X leading and 5X trailing spaces,
+     * unmeasured at the edges of the image
+     */
+    if (s < 73 || (w[46] && w[46] * 73 < s * 4) || (w[0] && w[0] * 146 < s) ||
+	(2 * (w[44] + w[45]) * 73 + s) / (2 * s) != 2)
+	return (ZBAR_NONE);
+
+    dbprintf(2, "\n        ltd: s=%d", s);
+    for (i = 0; i < 2; i++) {
+	const struct ltd_group_s *g;
+	int odd[7], even[7], nodd = 0;
+	if (decode_ltd_char(w + 2 + 28 * i, 26, e))
+	    return (ZBAR_NONE);
+	for (j = 0; j < 14; j++) {
+	    chk += e[j] * mul;
+	    mul = mul * 3 % 89;
+	    if (j & 1)
+		even[j >> 1] = e[j];
+	    else {
+		odd[j >> 1] = e[j];
+		nodd += e[j];
+	    }
+	}
+	for (g = ltd_groups; g->nodd != nodd; g++)
+	    if (g == ltd_groups + 6)
+		return (ZBAR_NONE);
+	for (j = 0; j < 7; j++)
+	    if (odd[j] > g->wodd || even[j] > g->weven)
+		return (ZBAR_NONE);
+	d[i] = g->sum + calc_value(odd, 7, g->wodd, 1) * g->teven +
+	       calc_value(even, 7, g->weven, 0);
+    }
+    chk %= 89;
+
+    if (decode_ltd_char(w + 16, 18, e) || e[12] != 1 || e[13] != 1)
+	return (ZBAR_NONE);
+    else {
+	int sp[6], br[6], seq;
+	for (j = 0; j < 6; j++) {
+	    sp[j] = e[2 * j];
+	    br[j] = e[2 * j + 1];
+	    if (sp[j] > 3 || br[j] > 3)
+		return (ZBAR_NONE);
+	}
+	seq = calc_value(sp, 6, 3, 1) * 21 + calc_value(br, 6, 3, 1);
+	dbprintf(2, " chk=%d seq=%d", chk, seq);
+	if (ltd_checks[chk] != seq)
+	    return (ZBAR_NONE);
+    }
+
+    v = (unsigned long long)d[0] * 2013571 + d[1];
+    /* NB linkage flag not supported */
+    if (v >= 2015133531096ULL)
+	v -= 2015133531096ULL;
+    if (v >= 2000000000000ULL)
+	return (ZBAR_NONE);
+
+    if (acquire_lock(dcode, ZBAR_DATABAR_LTD))
+	return (ZBAR_PARTIAL);
+
+    dcode->buf[0] = '0';
+    dcode->buf[1] = '1';
+    decode10(dcode->buf + 8, v % 10000000, 7);
+    decode10(dcode->buf + 2, v / 10000000, 6);
+    append_check14(dcode->buf + 2);
+    dcode->buflen = (TEST_CFG(db->config_ltd, ZBAR_CFG_EMIT_CHECK)) ? 16 : 15;
+    dcode->buf[dcode->buflen] = '\0';
+    dcode->modifiers	       = MOD(ZBAR_MOD_GS1);
+    dbprintf(2, " %s", _zbar_decoder_buf_dump(dcode->buf, dcode->buflen));
+    return (ZBAR_DATABAR_LTD);
+}
+
+/* keep the latest element widths, trying a Limited symbol
+ * ending at each space
+ */
+static inline zbar_symbol_type_t decode_ltd_width(zbar_decoder_t *dcode)
+{
+    databar_decoder_t *db = &dcode->databar;
+    unsigned w[DATABAR_LTD_WIDTHS + 1], cur = get_width(dcode, 0);
+    zbar_symbol_type_t sym = ZBAR_NONE;
+    int i, k = db->ltd_idx % DATABAR_LTD_WIDTHS;
+
+    if (get_color(dcode) == ZBAR_SPACE && db->ltd_idx >= DATABAR_LTD_WIDTHS) {
+	for (i = 0; i < DATABAR_LTD_WIDTHS; i++)
+	    w[i] = db->ltd_w[(k + i) % DATABAR_LTD_WIDTHS];
+	w[DATABAR_LTD_WIDTHS] = cur;
+	sym		      = decode_ltd(dcode, w);
+	if (sym == ZBAR_NONE) {
+	    /* scanned right to left */
+	    for (i = 0; i <= DATABAR_LTD_WIDTHS / 2; i++) {
+		unsigned t		   = w[i];
+		w[i]			   = w[DATABAR_LTD_WIDTHS - i];
+		w[DATABAR_LTD_WIDTHS - i] = t;
+	    }
+	    sym = decode_ltd(dcode, w);
+	    if (sym == ZBAR_DATABAR_LTD)
+		dcode->direction = -1;
+	} else if (sym == ZBAR_DATABAR_LTD)
+	    dcode->direction = 1;
+    }
+
+    db->ltd_w[k] = cur;
+    db->ltd_idx++;
+    return (sym);
+}
+
+static inline zbar_symbol_type_t decode_segments(zbar_decoder_t *dcode)
 {
     databar_decoder_t *db = &dcode->databar;
     databar_segment_t *seg, *pair;
@@ -1209,6 +1504,8 @@ zbar_symbol_type_t _zbar_decode_databar(zbar_decoder_t *dcode)
 	seg->count   = 1;
 	seg->width   = pair->width;
 	seg->epoch   = db->epoch;
+	seg->row0    = db->row;
+	seg->row1    = db->row;
     }
 
     sym = decode_char(dcode, seg, 1, 1);
@@ -1222,3 +1519,22 @@ zbar_symbol_type_t _zbar_decode_databar(zbar_decoder_t *dcode)
 
     return (sym);
 }
+
+zbar_symbol_type_t _zbar_decode_databar(zbar_decoder_t *dcode)
+{
+    zbar_symbol_type_t ltd = ZBAR_NONE, sym;
+
+    if (TEST_CFG(dcode->databar.config_ltd, ZBAR_CFG_ENABLE))
+	ltd = decode_ltd_width(dcode);
+
+    /* segments are tracked regardless, a Limited symbol holds the lock */
+    sym = decode_segments(dcode);
+    return ((ltd > ZBAR_PARTIAL) ? ltd : sym);
+}
+
+unsigned _zbar_decoder_get_databar_stack(zbar_decoder_t *dcode,
+					 unsigned *width)
+{
+    *width = dcode->databar.stk_width;
+    return (dcode->databar.stk_rows);
+}
@@ -24,6 +24,7 @@
 #define _DATABAR_H_
 
 #define DATABAR_MAX_SEGMENTS 32
+#define DATABAR_LTD_WIDTHS   46
 
 /* active DataBar (partial) segment entry */
 typedef struct databar_segment_s {
@@ -38,24 +39,38 @@ typedef struct databar_segment_s {
     unsigned check   : 8; /* bar checksum */
     signed short data;	  /* decoded character data */
     unsigned short width; /* measured width of finder (14 modules) */
+    unsigned row0, row1;  /* first and last scan encountered */
 } databar_segment_t;
 
 /* DataBar specific decode state */
 typedef struct databar_decoder_s {
     unsigned config; /* decoder configuration flags */
     unsigned config_exp;
+    unsigned config_ltd;
+    unsigned config_stk;
+    unsigned config_stk_omni;
+    unsigned config_exp_stk;
 
     unsigned csegs : 8; /* allocated segments */
     unsigned epoch : 8; /* current scan */
+    unsigned row;	/* scans started, identifies the rows of segments */
+    unsigned stk_rows;	/* scans of the row found first in a stacked symbol */
+    unsigned stk_width; /* finder width (14 modules) of a stacked symbol */
 
     databar_segment_t *segs; /* active segment list */
     signed char chars[16];   /* outstanding character indices */
+
+    /* DataBar Limited has no finder, the whole symbol is kept instead */
+    unsigned ltd_w[DATABAR_LTD_WIDTHS]; /* latest element widths */
+    unsigned ltd_idx;			/* widths since the scan started */
 } databar_decoder_t;
 
 /* reset DataBar segment decode state */
 static inline void databar_new_scan(databar_decoder_t *db)
 {
     int i;
+    db->row++;
+    db->ltd_idx = 0;
     for (i = 0; i < 16; i++)
 	if (db->chars[i] >= 0) {
 	    databar_segment_t *seg = db->segs + db->chars[i];
@@ -0,0 +1,7 @@
+#include "dm_finder.h"
+#include "decoder.h"
+
+unsigned _zbar_decoder_get_dm_finder_config(zbar_decoder_t *dcode)
+{
+    return dcode->dmf.config;
+}
@@ -0,0 +1,9 @@
+#ifndef _DECODER_DM_FINDER_H_
+#define _DECODER_DM_FINDER_H_
+
+/* Data Matrix symbol finder state */
+typedef struct dm_finder_s {
+    unsigned config;
+} dm_finder_t;
+
+#endif
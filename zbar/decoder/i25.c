@@ -88,6 +88,19 @@ static inline unsigned char i25_decode10(zbar_decoder_t *dcode,
     return (enc);
 }
 
+/* check a quiet zone, which may be shortened down to 1.75n-2.25n when
+ * it is bounded by a bearer bar (wider than any bar of the symbol)
+ */
+static inline unsigned char i25_check_quiet(unsigned quiet, unsigned bearer,
+					    unsigned s10)
+{
+    if (!quiet || quiet >= s10 * 3 / 8)
+	return (0);
+    if (quiet >= s10 / 8 && bearer >= s10 / 5)
+	return (0);
+    return (1);
+}
+
 static inline signed char i25_decode_start(zbar_decoder_t *dcode)
 {
     unsigned char enc = 0;
@@ -114,7 +127,7 @@ static inline signed char i25_decode_start(zbar_decoder_t *dcode)
      * (FIXME should really factor in w:n ratio)
      */
     quiet = get_width(dcode, i);
-    if (quiet && quiet < dcode25->s10 * 3 / 8) {
+    if (i25_check_quiet(quiet, get_width(dcode, i + 1), dcode25->s10)) {
 	dbprintf(3, "      i25: s=%d enc=%x q=%d [invalid qz]\n", dcode25->s10,
 		 enc, quiet);
 	return (ZBAR_NONE);
@@ -141,26 +154,47 @@ static inline int i25_acquire_lock(zbar_decoder_t *dcode)
     return (0);
 }
 
-static inline signed char i25_decode_end(zbar_decoder_t *dcode)
+/* verify the trailing mod 10 check digit (weighted 3-1 like UPC/EAN) */
+static inline signed char i25_check(zbar_decoder_t *dcode, int n)
+{
+    unsigned sum = 0;
+    int i;
+    for (i = n - 2; i >= 0; i -= 2)
+	sum += (dcode->buf[i] - '0') * 3;
+    for (i = n - 3; i >= 0; i -= 2)
+	sum += dcode->buf[i] - '0';
+    sum = (10 - sum % 10) % 10;
+    dbprintf(2, " chk=%d", sum);
+    return (dcode->buf[n - 1] - '0' != sum);
+}
+
+/* check the stop pattern and trailing quiet zone, which is found at
+ * offset q: the end is checked again one element later (q = 1) to
+ * measure the width of a bearer bar following a short quiet zone
+ */
+static inline signed char i25_decode_end(zbar_decoder_t *dcode, unsigned q)
 {
     unsigned char E;
+    zbar_symbol_type_t sym = ZBAR_I25;
     i25_decoder_t *dcode25 = &dcode->i25;
 
     /* check trailing quiet zone */
-    unsigned quiet = get_width(dcode, 0);
-    if ((quiet && quiet < dcode25->width * 3 / 8) ||
-	decode_e(get_width(dcode, 1), dcode25->width, 45) > 2 ||
-	decode_e(get_width(dcode, 2), dcode25->width, 45) > 2) {
+    unsigned quiet  = get_width(dcode, q);
+    unsigned bearer = (q) ? get_width(dcode, 0) : 0;
+    if ((q && !quiet) || i25_check_quiet(quiet, bearer, dcode25->width) ||
+	decode_e(get_width(dcode, q + 1), dcode25->width, 45) > 2 ||
+	decode_e(get_width(dcode, q + 2), dcode25->width, 45) > 2) {
 	dbprintf(3, "      i25: s=%d q=%d [invalid qz]\n", dcode25->width,
 		 quiet);
 	return (ZBAR_NONE);
     }
 
     /* check exit condition */
-    E = decode_e(get_width(dcode, 3), dcode25->width, 45);
+    E = decode_e(get_width(dcode, q + 3), dcode25->width, 45);
     if ((!dcode25->direction) ?
 		  E - 3 > 4 :
-		  (E > 2 || decode_e(get_width(dcode, 4), dcode25->width, 45) > 2))
+		  (E > 2 ||
+		   decode_e(get_width(dcode, q + 4), dcode25->width, 45) > 2))
 	return (ZBAR_NONE);
 
     if (dcode25->character <= 4 && i25_acquire_lock(dcode))
@@ -179,24 +213,48 @@ static inline signed char i25_decode_end(zbar_decoder_t *dcode)
 	}
     }
 
-    if (dcode25->character < CFG(*dcode25, ZBAR_CFG_MIN_LEN) ||
-	(CFG(*dcode25, ZBAR_CFG_MAX_LEN) > 0 &&
-	 dcode25->character > CFG(*dcode25, ZBAR_CFG_MAX_LEN))) {
+    /* ITF-14 is a GTIN-14, reported in place of I2/5 when its check
+     * digit is valid
+     */
+    if (TEST_CFG(dcode25->config_itf14, ZBAR_CFG_ENABLE) &&
+	dcode25->character == 14 && !i25_check(dcode, 14))
+	sym = ZBAR_ITF14;
+    else if (!TEST_CFG(dcode25->config, ZBAR_CFG_ENABLE) ||
+	     dcode25->character < CFG(*dcode25, ZBAR_CFG_MIN_LEN) ||
+	     (CFG(*dcode25, ZBAR_CFG_MAX_LEN) > 0 &&
+	      dcode25->character > CFG(*dcode25, ZBAR_CFG_MAX_LEN))) {
 	dbprintf(2, " [invalid len]\n");
 	release_lock(dcode, ZBAR_I25);
 	dcode25->character = -1;
 	return (ZBAR_NONE);
+    } else if (TEST_CFG(dcode25->config, ZBAR_CFG_ADD_CHECK) &&
+	       i25_check(dcode, dcode25->character)) {
+	dbprintf(2, " [invalid check]\n");
+	release_lock(dcode, ZBAR_I25);
+	dcode25->character = -1;
+	return (ZBAR_NONE);
     }
 
     zassert(dcode25->character < dcode->buf_alloc, ZBAR_NONE, "i=%02x %s\n",
 	    dcode25->character,
 	    _zbar_decoder_buf_dump(dcode->buf, dcode25->character));
-    dcode->buflen		   = dcode25->character;
-    dcode->buf[dcode25->character] = '\0';
-    dcode->modifiers		   = 0;
+    dcode->buflen = dcode25->character;
+    if ((sym == ZBAR_ITF14) ?
+		  !TEST_CFG(dcode25->config_itf14, ZBAR_CFG_EMIT_CHECK) :
+		  (TEST_CFG(dcode25->config, ZBAR_CFG_ADD_CHECK) &&
+		   !TEST_CFG(dcode25->config, ZBAR_CFG_EMIT_CHECK)))
+	dcode->buflen--;
+    dcode->buf[dcode->buflen] = '\0';
+    dcode->modifiers	      = 0;
     dbprintf(2, " [valid end]\n");
     dcode25->character = -1;
-    return (ZBAR_I25);
+
+    /* hand the lock over to the reported symbology */
+    if (sym != ZBAR_I25) {
+	release_lock(dcode, ZBAR_I25);
+	acquire_lock(dcode, sym);
+    }
+    return (sym);
 }
 
 zbar_symbol_type_t _zbar_decode_i25(zbar_decoder_t *dcode)
@@ -214,7 +272,9 @@ zbar_symbol_type_t _zbar_decode_i25(zbar_decoder_t *dcode)
 	return (ZBAR_NONE);
 
     if (--dcode25->element == 6 - dcode25->direction)
-	return (i25_decode_end(dcode));
+	return (i25_decode_end(dcode, 0));
+    else if (dcode25->element == 5 - dcode25->direction)
+	return (i25_decode_end(dcode, 1));
     else if (dcode25->element)
 	return (ZBAR_NONE);
 
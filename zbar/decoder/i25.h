@@ -33,6 +33,7 @@ typedef struct i25_decoder_s {
     unsigned char buf[4];    /* initial scan buffer */
 
     unsigned config;
+    unsigned config_itf14;
     int configs[NUM_CFGS]; /* int valued configurations */
 } i25_decoder_t;
 
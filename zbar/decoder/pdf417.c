@@ -35,6 +35,19 @@
 
 #define PDF417_STOP 0xbff
 
+/* restore the symbol order of codewords scanned in reverse */
+static inline void pdf417_reverse_row(unsigned char *buf, int n)
+{
+    int i;
+    for (i = 0; i < n / 2; i++) {
+	unsigned char lo = buf[2 * i], hi = buf[2 * i + 1];
+	buf[2 * i]		 = buf[2 * (n - 1 - i)];
+	buf[2 * i + 1]		 = buf[2 * (n - 1 - i) + 1];
+	buf[2 * (n - 1 - i)]	 = lo;
+	buf[2 * (n - 1 - i) + 1] = hi;
+    }
+}
+
 static inline signed short pdf417_decode8(zbar_decoder_t *dcode)
 {
     long sig = 0;
@@ -67,6 +80,7 @@ static inline signed short pdf417_decode8(zbar_decoder_t *dcode)
     /* determine cluster number */
     clst =
 	((sig & 7) - ((sig >> 3) & 7) + ((sig >> 12) & 7) - ((sig >> 15) & 7));
+    clst %= 9;
     if (clst < 0)
 	clst += 9;
     dbprintf(2, " k=%d", clst);
@@ -74,6 +88,10 @@ static inline signed short pdf417_decode8(zbar_decoder_t *dcode)
 	    dcode->pdf417.direction, sig, clst,
 	    _zbar_decoder_buf_dump(dcode->buf, dcode->pdf417.character));
 
+    /* stop pattern, without its trailing bar (cluster 6 look-alike) */
+    if (!get_color(dcode) && sig == 0x040486)
+	return (PDF417_STOP);
+
     if (clst != 0 && clst != 3 && clst != 6) {
 	if (get_color(dcode) && clst == 7 && sig == 0x080007)
 	    return (PDF417_STOP);
@@ -163,6 +181,7 @@ static inline signed char pdf417_decode_start(zbar_decoder_t *dcode)
     dcode417->direction = get_color(dcode);
     dcode417->element	= 0;
     dcode417->character = 0;
+    dcode417->len	= s;
 
     dbprintf(2, " [valid start]\n");
     return (ZBAR_PARTIAL);
@@ -182,6 +201,7 @@ zbar_symbol_type_t _zbar_decode_pdf417(zbar_decoder_t *dcode)
 	dbprintf(4, "\n");
 	return (0);
     }
+    dcode417->len += get_width(dcode, 0);
 
     /* process every 8th element of active symbol */
     if (++dcode417->element)
@@ -201,24 +221,44 @@ zbar_symbol_type_t _zbar_decode_pdf417(zbar_decoder_t *dcode)
     }
 
     c = pdf417_decode8(dcode);
-    if (c < 0 || size_buf(dcode, dcode417->character + 1)) {
+    if (c < 0 || size_buf(dcode, 2 * (dcode417->character + 1))) {
 	dbprintf(1, (c < 0) ? " [aborted]\n" : " [overflow]\n");
 	release_lock(dcode, ZBAR_PDF417);
 	dcode417->character = -1;
 	return (0);
     }
 
-    /* FIXME TBD infer dimensions, save codewords */
-
     if (c == PDF417_STOP) {
+	int n = dcode417->character;
 	dbprintf(1, " [valid stop]");
 	/* FIXME check trailing bar and qz */
+	dcode417->character = -1;
+	/* a row holds both row indicators and at least one data codeword */
+	if (n < 3) {
+	    dbprintf(1, " [too short]\n");
+	    release_lock(dcode, ZBAR_PDF417);
+	    return (0);
+	}
+	if (dcode417->direction)
+	    pdf417_reverse_row(dcode->buf, n);
+	dcode->buflen	 = 2 * n;
 	dcode->direction = 1 - 2 * dcode417->direction;
 	dcode->modifiers = 0;
-	release_lock(dcode, ZBAR_PDF417);
-	dcode417->character = -1;
+	dbprintf(2, " [row of %d]\n", n);
+	/* the image scanner collects the rows of a symbol */
+	return (ZBAR_PDF417);
     }
 
+    /* save codeword and cluster, low byte first */
+    dcode->buf[2 * dcode417->character]	    = c & 0xff;
+    dcode->buf[2 * dcode417->character + 1] = c >> 8;
+    dcode417->character++;
+
     dbprintf(2, "\n");
     return (0);
 }
+
+unsigned _zbar_decoder_get_pdf417_row_width(zbar_decoder_t *dcode)
+{
+    return (dcode->pdf417.len);
+}
@@ -29,6 +29,7 @@ typedef struct pdf417_decoder_s {
     unsigned element   : 3;  /* element offset 0-7 */
     int character      : 12; /* character position in symbol */
     unsigned s8;	     /* character width */
+    unsigned len;	     /* width of the row scanned so far */
 
     unsigned config;
     int configs[NUM_CFGS]; /* int valued configurations */
@@ -41,6 +42,7 @@ static inline void pdf417_reset(pdf417_decoder_t *pdf417)
     pdf417->element   = 0;
     pdf417->character = -1;
     pdf417->s8	      = 0;
+    pdf417->len	      = 0;
 }
 
 /* decode PDF417 symbols */
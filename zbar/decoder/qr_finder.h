@@ -9,6 +9,8 @@ typedef struct qr_finder_s {
     qr_finder_line line; /* position info needed by decoder */
 
     unsigned config;
+    unsigned config_micro; /* Micro QR Code config */
+    unsigned config_rmqr;  /* rMQR Code config */
 } qr_finder_t;
 
 /* reset QR finder specific state */
@@ -46,6 +46,15 @@
 #if ENABLE_SQCODE == 1
 #include "sqcode.h"
 #endif
+#if ENABLE_PDF417 == 1
+#include "pdf417.h"
+#endif
+#if ENABLE_DATAMATRIX == 1
+#include "datamatrix.h"
+#endif
+#if ENABLE_AZTEC == 1
+#include "aztec.h"
+#endif
 #include "img_scanner.h"
 #include "svg.h"
 
@@ -70,11 +79,14 @@
  */
 #define CACHE_TIMEOUT (CACHE_HYSTERESIS * 2) /* ms */
 
-#define NUM_SCN_CFGS (ZBAR_CFG_Y_DENSITY - ZBAR_CFG_X_DENSITY + 1)
+#define NUM_SCN_CFGS (ZBAR_CFG_DIAGONAL - ZBAR_CFG_X_DENSITY + 1)
 
 #define CFG(iscn, cfg)	    ((iscn)->configs[(cfg)-ZBAR_CFG_X_DENSITY])
 #define TEST_CFG(iscn, cfg) (((iscn)->config >> ((cfg)-ZBAR_CFG_POSITION)) & 1)
 
+/* fractional bits of diagonal scan line positions */
+#define DIAG_FIXED 16
+
 #ifndef NO_STATS
 #define STAT(x) iscn->stat_##x++
 #else
@@ -99,6 +111,15 @@ struct zbar_image_scanner_s {
 #if ENABLE_SQCODE == 1
     sq_reader *sq; /* SQ Code 2D reader */
 #endif
+#if ENABLE_PDF417 == 1
+    pdf417_reader *pdf417; /* PDF417 row collector */
+#endif
+#if ENABLE_DATAMATRIX == 1
+    dm_reader *dm; /* Data Matrix 2D reader */
+#endif
+#if ENABLE_AZTEC == 1
+    az_reader *az; /* Aztec 2D reader */
+#endif
 
     const void *userdata; /* application data */
     /* user result callback */
@@ -107,6 +128,8 @@ struct zbar_image_scanner_s {
     unsigned long time;	     /* scan start time */
     zbar_image_t *img;	     /* currently scanning image *root* */
     int dx, dy, du, umin, v; /* current scan direction */
+    int64_t vfix, slope;     /* diagonal line minor axis at umin and step */
+    uint8_t invert;	     /* mask applied to scanned pixels */
     zbar_symbol_set_t *syms; /* previous decode results */
     /* recycled symbols in 4^n size buckets */
     recycle_bucket_t recycle[RECYCLE_BUCKETS];
@@ -119,6 +142,7 @@ struct zbar_image_scanner_s {
     unsigned ean_config;
     int configs[NUM_SCN_CFGS];	  /* int valued configurations */
     int sym_configs[1][NUM_SYMS]; /* per-symbology configurations */
+    unsigned inverted_syms;	  /* symbologies retried light-on-dark */
 
 #ifndef NO_STATS
     int stat_syms_new;
@@ -253,6 +277,7 @@ inline zbar_symbol_t *_zbar_image_scanner_alloc_sym(zbar_image_scanner_t *iscn,
     sym->orient	     = ZBAR_ORIENT_UNKNOWN;
     sym->cache_count = 0;
     sym->time	     = iscn->time;
+    sym->sa_size     = 0;
     assert(!sym->syms);
 
     if (datalen > 0) {
@@ -399,6 +424,76 @@ static void sq_handler(zbar_image_scanner_t *iscn)
 }
 #endif
 
+#if ENABLE_PDF417 == 1
+extern unsigned _zbar_decoder_get_pdf417_row_width(zbar_decoder_t *);
+
+static void pdf417_handler(zbar_image_scanner_t *iscn)
+{
+    pdf417_row_line line;
+    unsigned width = _zbar_decoder_get_pdf417_row_width(iscn->dcode);
+    int u1 = iscn->umin + iscn->du * zbar_scanner_get_edge(iscn->scn, 0, 0);
+    int u0 = iscn->umin + iscn->du * zbar_scanner_get_edge(iscn->scn, width, 0);
+    int vert = !iscn->dx;
+
+    /* codewords are in symbol order, the scan may run the other way */
+    if (zbar_decoder_get_direction(iscn->dcode) < 0) {
+	int tmp = u0;
+	u0	= u1;
+	u1	= tmp;
+    }
+    line.vert	      = vert;
+    line.start[vert]  = u0;
+    line.start[!vert] = iscn->v;
+    line.end[vert]    = u1;
+    line.end[!vert]   = iscn->v;
+    line.ncw	      = zbar_decoder_get_data_length(iscn->dcode) / 2;
+    line.cw	      = (const unsigned char *)zbar_decoder_get_data(iscn->dcode);
+    _zbar_pdf417_found_row(iscn->pdf417, &line);
+}
+#endif
+
+#if ENABLE_DATAMATRIX == 1
+extern unsigned _zbar_decoder_get_dm_finder_config(zbar_decoder_t *);
+
+static void dm_handler(zbar_image_scanner_t *iscn)
+{
+    unsigned config = _zbar_decoder_get_dm_finder_config(iscn->dcode);
+    _zbar_dm_new_config(iscn->dm, config);
+}
+#endif
+
+#if ENABLE_AZTEC == 1
+extern unsigned _zbar_decoder_get_az_finder_config(zbar_decoder_t *);
+
+static void az_handler(zbar_image_scanner_t *iscn)
+{
+    unsigned config = _zbar_decoder_get_az_finder_config(iscn->dcode);
+    _zbar_az_new_config(iscn->az, config);
+}
+#endif
+
+#if ENABLE_DATABAR == 1
+extern unsigned _zbar_decoder_get_databar_stack(zbar_decoder_t *, unsigned *);
+
+/* stacked DataBar rows are 5 modules high (33 for omnidirectional),
+ * measured by the scans spanning the row decoded first
+ */
+static zbar_symbol_type_t databar_stk_type(zbar_image_scanner_t *iscn)
+{
+    unsigned width, rows;
+    int density, enable = 0;
+    zbar_symbol_type_t type = ZBAR_DATABAR_STK;
+
+    rows    = _zbar_decoder_get_databar_stack(iscn->dcode, &width);
+    density = CFG(iscn, (iscn->dx) ? ZBAR_CFG_Y_DENSITY : ZBAR_CFG_X_DENSITY);
+    /* the finder width is 14 modules, in 1/32 pixel */
+    if (rows * density * 14 * 32 >= 16 * width)
+	type = ZBAR_DATABAR_STK_OMNI;
+    zbar_decoder_get_config(iscn->dcode, type, ZBAR_CFG_ENABLE, &enable);
+    return ((enable) ? type : ZBAR_NONE);
+}
+#endif
+
 static void symbol_handler(zbar_decoder_t *dcode)
 {
     zbar_image_scanner_t *iscn = zbar_decoder_get_userdata(dcode);
@@ -408,24 +503,47 @@ static void symbol_handler(zbar_decoder_t *dcode)
     unsigned datalen;
     zbar_symbol_t *sym;
 
+    /* only keep results of symbologies retried inverted */
+    if (iscn->invert &&
+	(type <= ZBAR_PARTIAL ||
+	 !(iscn->inverted_syms & (1 << _zbar_get_symbol_hash(type)))))
+	return;
+
 #if ENABLE_QRCODE == 1
     if (type == ZBAR_QRCODE) {
-	qr_handler(iscn);
+	/* finder lines are only located on horizontal and vertical passes */
+	if (!iscn->slope)
+	    qr_handler(iscn);
 	return;
     }
 #else
     assert(type != ZBAR_QRCODE);
 #endif
 
+#if ENABLE_PDF417 == 1
+    if (type == ZBAR_PDF417) {
+	/* rows are assembled into symbols after the whole image is scanned */
+	if (!iscn->slope)
+	    pdf417_handler(iscn);
+	return;
+    }
+#endif
+
     if (TEST_CFG(iscn, ZBAR_CFG_POSITION)) {
 	/* tmp position fixup */
 	int w = zbar_scanner_get_width(iscn->scn);
 	int u = iscn->umin + iscn->du * zbar_scanner_get_edge(iscn->scn, w, 0);
+	int v = iscn->v;
+	if (iscn->slope)
+	    /* diagonal lines move along the minor axis as well */
+	    v = (iscn->vfix + (u - iscn->umin) * iscn->slope +
+		 (1 << (DIAG_FIXED - 1))) >>
+		DIAG_FIXED;
 	if (iscn->dx) {
 	    x = u;
-	    y = iscn->v;
+	    y = v;
 	} else {
-	    x = iscn->v;
+	    x = v;
 	    y = u;
 	}
     }
@@ -436,6 +554,14 @@ static void symbol_handler(zbar_decoder_t *dcode)
 	return;
     }
 
+#if ENABLE_DATABAR == 1
+    if (type == ZBAR_DATABAR_STK) {
+	type = databar_stk_type(iscn);
+	if (type == ZBAR_NONE)
+	    return;
+    }
+#endif
+
     data    = zbar_decoder_get_data(dcode);
     datalen = zbar_decoder_get_data_length(dcode);
 
@@ -495,14 +621,31 @@ zbar_image_scanner_t *zbar_image_scanner_create()
     iscn->sq = _zbar_sq_create();
 #endif
 
+#if ENABLE_PDF417 == 1
+    iscn->pdf417 = _zbar_pdf417_create();
+#endif
+
+#if ENABLE_DATAMATRIX == 1
+    iscn->dm = _zbar_dm_create();
+#endif
+
+#if ENABLE_AZTEC == 1
+    iscn->az = _zbar_az_create();
+#endif
+
     /* apply default configuration */
     CFG(iscn, ZBAR_CFG_X_DENSITY) = 1;
     CFG(iscn, ZBAR_CFG_Y_DENSITY) = 1;
     zbar_image_scanner_set_config(iscn, 0, ZBAR_CFG_POSITION, 1);
     zbar_image_scanner_set_config(iscn, 0, ZBAR_CFG_UNCERTAINTY, 2);
     zbar_image_scanner_set_config(iscn, 0, ZBAR_CFG_TEST_INVERTED, 0);
+    zbar_image_scanner_set_config(iscn, 0, ZBAR_CFG_TEST_MIRRORED, 1);
     zbar_image_scanner_set_config(iscn, ZBAR_QRCODE, ZBAR_CFG_UNCERTAINTY, 0);
     zbar_image_scanner_set_config(iscn, ZBAR_QRCODE, ZBAR_CFG_BINARY, 0);
+    zbar_image_scanner_set_config(iscn, ZBAR_MICROQR, ZBAR_CFG_UNCERTAINTY, 0);
+    zbar_image_scanner_set_config(iscn, ZBAR_MICROQR, ZBAR_CFG_BINARY, 0);
+    zbar_image_scanner_set_config(iscn, ZBAR_RMQR, ZBAR_CFG_UNCERTAINTY, 0);
+    zbar_image_scanner_set_config(iscn, ZBAR_RMQR, ZBAR_CFG_BINARY, 0);
     zbar_image_scanner_set_config(iscn, ZBAR_CODE128, ZBAR_CFG_UNCERTAINTY, 0);
     zbar_image_scanner_set_config(iscn, ZBAR_CODE93, ZBAR_CFG_UNCERTAINTY, 0);
     zbar_image_scanner_set_config(iscn, ZBAR_CODE39, ZBAR_CFG_UNCERTAINTY, 0);
@@ -563,6 +706,24 @@ void zbar_image_scanner_destroy(zbar_image_scanner_t *iscn)
 	_zbar_sq_destroy(iscn->sq);
 	iscn->sq = NULL;
     }
+#endif
+#if ENABLE_PDF417 == 1
+    if (iscn->pdf417) {
+	_zbar_pdf417_destroy(iscn->pdf417);
+	iscn->pdf417 = NULL;
+    }
+#endif
+#if ENABLE_DATAMATRIX == 1
+    if (iscn->dm) {
+	_zbar_dm_destroy(iscn->dm);
+	iscn->dm = NULL;
+    }
+#endif
+#if ENABLE_AZTEC == 1
+    if (iscn->az) {
+	_zbar_az_destroy(iscn->az);
+	iscn->az = NULL;
+    }
 #endif
     free(iscn);
 }
@@ -605,15 +766,43 @@ int zbar_image_scanner_set_config(zbar_image_scanner_t *iscn,
 	return (0);
     }
 
+    if (cfg == ZBAR_CFG_TEST_INVERTED && sym > ZBAR_PARTIAL) {
+	unsigned mask;
+	/* 2D symbologies are only retried by inverting the whole image */
+	if (sym == ZBAR_QRCODE || sym == ZBAR_MICROQR || sym == ZBAR_RMQR ||
+	    sym == ZBAR_SQCODE || sym == ZBAR_DATAMATRIX ||
+	    sym == ZBAR_AZTEC || sym == ZBAR_COMPOSITE)
+	    return (1);
+	mask = 1 << _zbar_get_symbol_hash(sym);
+	if (!val)
+	    iscn->inverted_syms &= ~mask;
+	else if (val == 1)
+	    iscn->inverted_syms |= mask;
+	else
+	    return (1);
+	return (0);
+    }
+
     /* Image scanner parameters apply only to ZBAR_PARTIAL */
     if (sym > ZBAR_PARTIAL)
 	return (1);
 
+    if (cfg >= ZBAR_CFG_EDGE_THRESHOLD)
+	return (zbar_scanner_set_config(iscn->scn, cfg, val));
+
     if (cfg >= ZBAR_CFG_X_DENSITY && cfg <= ZBAR_CFG_Y_DENSITY) {
 	CFG(iscn, cfg) = val;
 	return (0);
     }
 
+    if (cfg == ZBAR_CFG_DIAGONAL) {
+	/* angle step in degrees, 0 disables diagonal scanning */
+	if (val < 0 || val >= 90)
+	    return (1);
+	CFG(iscn, cfg) = val;
+	return (0);
+    }
+
     cfg -= ZBAR_CFG_POSITION;
 
     if (!val)
@@ -648,6 +837,11 @@ int zbar_image_scanner_get_config(zbar_image_scanner_t *iscn,
 	return 0;
     }
 
+    if (cfg == ZBAR_CFG_TEST_INVERTED && sym > ZBAR_PARTIAL) {
+	*val = (iscn->inverted_syms >> _zbar_get_symbol_hash(sym)) & 1;
+	return 0;
+    }
+
     /* Image scanner parameters apply only to ZBAR_PARTIAL */
     if (sym > ZBAR_PARTIAL)
 	return (1);
@@ -657,11 +851,14 @@ int zbar_image_scanner_get_config(zbar_image_scanner_t *iscn,
 	return 0;
     }
 
-    if (cfg <= ZBAR_CFG_Y_DENSITY) {
+    if (cfg <= ZBAR_CFG_DIAGONAL) {
 	*val = CFG(iscn, cfg);
 	return 0;
     }
 
+    if (cfg >= ZBAR_CFG_EDGE_THRESHOLD)
+	return (zbar_scanner_get_config(iscn->scn, cfg, val));
+
     return 1;
 }
 
@@ -865,57 +1062,111 @@ static void zbar_send_code_via_dbus(zbar_image_scanner_t *iscn,
 	p += (dx) + ((uintptr_t)(dy)*w); \
     } while (0);
 
-static void *_zbar_scan_image(zbar_image_scanner_t *iscn, zbar_image_t *img)
+/* tan() of 0..45 degrees in DIAG_FIXED fixed point */
+static const int32_t diag_tan[46] = {
+    0, 1144, 2289, 3435, 4583, 5734, 6888, 8047,
+    9210, 10380, 11556, 12739, 13930, 15130, 16340, 17560,
+    18792, 20036, 21294, 22566, 23853, 25157, 26478, 27818,
+    29179, 30560, 31964, 33392, 34846, 36327, 37837, 39378,
+    40951, 42560, 44205, 45889, 47615, 49385, 51202, 53070,
+    54991, 56970, 59009, 61113, 63287, 65536
+};
+
+/* scan parallel lines at angle degrees (-89..89, nonzero) from the
+ * horizontal, stepping one pixel along the closest axis per sample and
+ * spacing the lines by the density of the other axis
+ */
+static void scan_diagonal(zbar_image_scanner_t *iscn, zbar_image_t *img,
+			  int angle)
 {
-    zbar_symbol_set_t *syms;
-    const uint8_t *data;
     zbar_scanner_t *scn = iscn->scn;
-    unsigned w, h, cx1, cy1;
-    int density;
-    char filter;
-    int nean, naddon;
-
-    /* timestamp image
-     * FIXME prefer video timestamp
-     */
-    iscn->time = _zbar_timer_now();
-
-#if ENABLE_QRCODE == 1
-    _zbar_qr_reset(iscn->qr);
-#endif
-
-#if ENABLE_SQCODE == 1
-    _zbar_sq_reset(iscn->sq);
-#endif
+    unsigned w = img->width;
+    const uint8_t *data = img->data;
+    int deg = (angle < 0) ? -angle : angle;
+    int vert = deg > 45;
+    int64_t slope = diag_tan[(vert) ? 90 - deg : deg];
+    int u0, u1, v0, v1, len, density;
+    int64_t c, cmin, cmax;
+
+    if (angle < 0)
+	slope = -slope;
+    if (vert) {
+	u0	= img->crop_y;
+	u1	= img->crop_y + img->crop_h;
+	v0	= img->crop_x;
+	v1	= img->crop_x + img->crop_w;
+	density = CFG(iscn, ZBAR_CFG_X_DENSITY);
+    } else {
+	u0	= img->crop_x;
+	u1	= img->crop_x + img->crop_w;
+	v0	= img->crop_y;
+	v1	= img->crop_y + img->crop_h;
+	density = CFG(iscn, ZBAR_CFG_Y_DENSITY);
+    }
+    if (density <= 0 || u1 <= u0 || v1 <= v0)
+	return;
+    len = u1 - u0;
 
-    /* image must be in grayscale format */
-    if (img->format != fourcc('Y', '8', '0', '0') &&
-	img->format != fourcc('G', 'R', 'E', 'Y'))
-	return NULL;
-    iscn->img = img;
+    /* line offsets at u0 (in pixels) such that the line crosses the crop */
+    cmin = v0;
+    cmax = v1 - 1;
+    if (slope > 0)
+	cmin -= (slope * (len - 1)) >> DIAG_FIXED;
+    else
+	cmax += (-slope * (len - 1)) >> DIAG_FIXED;
+
+    iscn->dx = !vert;
+    iscn->dy = vert;
+    iscn->du = 1;
+    iscn->slope = slope;
+
+    for (c = cmin; c <= cmax; c += density) {
+	int64_t vfix = c << DIAG_FIXED;
+	int started = 0, u;
+	for (u = u0; u < u1; u++, vfix += slope) {
+	    int v = (vfix + (1 << (DIAG_FIXED - 1))) >> DIAG_FIXED;
+	    uint8_t d;
+	    if (v < v0 || v >= v1) {
+		if (started)
+		    break;
+		continue;
+	    }
+	    if (!started) {
+		started	   = 1;
+		iscn->umin = u;
+		iscn->vfix = vfix;
+	    }
+	    d = (vert) ? data[(uintptr_t)u * w + v] : data[(uintptr_t)v * w + u];
+	    zbar_scan_y(scn, d ^ iscn->invert);
+	}
+	if (started)
+	    quiet_border(iscn);
+    }
 
-    /* recycle previous scanner and image results */
-    zbar_image_scanner_recycle_image(iscn, img);
-    syms = iscn->syms;
-    if (!syms) {
-	syms = iscn->syms = _zbar_symbol_set_create();
-	STAT(syms_new);
-	zbar_symbol_set_ref(syms, 1);
-    } else
-	zbar_symbol_set_ref(syms, 2);
-    img->syms = syms;
+    iscn->slope = 0;
+    iscn->dx = iscn->dy = 0;
+}
 
-    w	= img->width;
-    h	= img->height;
-    cx1 = img->crop_x + img->crop_w;
-    assert(cx1 <= w);
-    cy1 = img->crop_y + img->crop_h;
-    assert(cy1 <= h);
-    data = img->data;
+/* scan diagonal lines at every multiple of the configured angle step */
+static void scan_diagonals(zbar_image_scanner_t *iscn, zbar_image_t *img)
+{
+    int step = CFG(iscn, ZBAR_CFG_DIAGONAL), angle;
+    if (step <= 0)
+	return;
+    for (angle = step; angle < 90; angle += step) {
+	scan_diagonal(iscn, img, angle);
+	scan_diagonal(iscn, img, -angle);
+    }
+}
 
-    zbar_image_write_png(img, "debug.png");
-    svg_open("debug.svg", 0, 0, w, h);
-    svg_image("debug.png", w, h);
+/* scan the rows and columns selected by the densities */
+static void scan_linear(zbar_image_scanner_t *iscn, zbar_image_t *img)
+{
+    zbar_scanner_t *scn = iscn->scn;
+    unsigned w = img->width, h = img->height;
+    unsigned cx1 = img->crop_x + img->crop_w, cy1 = img->crop_y + img->crop_h;
+    const uint8_t *data = img->data;
+    int density;
 
     zbar_scanner_new_scan(scn);
 
@@ -943,7 +1194,7 @@ static void *_zbar_scan_image(zbar_image_scanner_t *iscn, zbar_image_t *img)
 	    iscn->dx = iscn->du = 1;
 	    iscn->umin		= cx0;
 	    while (x < cx1) {
-		uint8_t d = *p;
+		uint8_t d = *p ^ iscn->invert;
 		movedelta(1, 0);
 		zbar_scan_y(scn, d);
 	    }
@@ -961,7 +1212,7 @@ static void *_zbar_scan_image(zbar_image_scanner_t *iscn, zbar_image_t *img)
 	    iscn->dx = iscn->du = -1;
 	    iscn->umin		= cx1;
 	    while (x >= cx0) {
-		uint8_t d = *p;
+		uint8_t d = *p ^ iscn->invert;
 		movedelta(-1, 0);
 		zbar_scan_y(scn, d);
 	    }
@@ -997,7 +1248,7 @@ static void *_zbar_scan_image(zbar_image_scanner_t *iscn, zbar_image_t *img)
 	    iscn->dy = iscn->du = 1;
 	    iscn->umin		= cy0;
 	    while (y < cy1) {
-		uint8_t d = *p;
+		uint8_t d = *p ^ iscn->invert;
 		movedelta(0, 1);
 		zbar_scan_y(scn, d);
 	    }
@@ -1015,7 +1266,7 @@ static void *_zbar_scan_image(zbar_image_scanner_t *iscn, zbar_image_t *img)
 	    iscn->dy = iscn->du = -1;
 	    iscn->umin		= cy1;
 	    while (y >= cy0) {
-		uint8_t d = *p;
+		uint8_t d = *p ^ iscn->invert;
 		movedelta(0, -1);
 		zbar_scan_y(scn, d);
 	    }
@@ -1028,7 +1279,93 @@ static void *_zbar_scan_image(zbar_image_scanner_t *iscn, zbar_image_t *img)
 	}
 	svg_group_end();
     }
-    iscn->dy  = 0;
+    iscn->dy = 0;
+
+    scan_diagonals(iscn, img);
+}
+
+/* symbologies with results in the set, as a mask of symbol hashes */
+static unsigned found_syms(const zbar_symbol_set_t *syms)
+{
+    unsigned found = 0;
+    const zbar_symbol_t *sym;
+    for (sym = syms->head; sym; sym = sym->next)
+	if (sym->type > ZBAR_PARTIAL)
+	    found |= 1 << _zbar_get_symbol_hash(sym->type);
+    return (found);
+}
+
+static void *_zbar_scan_image(zbar_image_scanner_t *iscn, zbar_image_t *img)
+{
+    zbar_symbol_set_t *syms;
+    unsigned w, h, cx1, cy1;
+    int density;
+    char filter;
+    int nean, naddon;
+
+    /* timestamp image
+     * FIXME prefer video timestamp
+     */
+    iscn->time = _zbar_timer_now();
+
+#if ENABLE_QRCODE == 1
+    _zbar_qr_reset(iscn->qr);
+#endif
+
+#if ENABLE_SQCODE == 1
+    _zbar_sq_reset(iscn->sq);
+#endif
+
+#if ENABLE_PDF417 == 1
+    _zbar_pdf417_reset(iscn->pdf417);
+#endif
+
+#if ENABLE_DATAMATRIX == 1
+    _zbar_dm_reset(iscn->dm);
+#endif
+
+#if ENABLE_AZTEC == 1
+    _zbar_az_reset(iscn->az);
+#endif
+
+    /* image must be in grayscale format */
+    if (img->format != fourcc('Y', '8', '0', '0') &&
+	img->format != fourcc('G', 'R', 'E', 'Y'))
+	return NULL;
+    iscn->img = img;
+
+    /* recycle previous scanner and image results */
+    zbar_image_scanner_recycle_image(iscn, img);
+    syms = iscn->syms;
+    if (!syms) {
+	syms = iscn->syms = _zbar_symbol_set_create();
+	STAT(syms_new);
+	zbar_symbol_set_ref(syms, 1);
+    } else
+	zbar_symbol_set_ref(syms, 2);
+    img->syms = syms;
+
+    w	= img->width;
+    h	= img->height;
+    cx1 = img->crop_x + img->crop_w;
+    assert(cx1 <= w);
+    cy1 = img->crop_y + img->crop_h;
+    assert(cy1 <= h);
+
+    zbar_image_write_png(img, "debug.png");
+    svg_open("debug.svg", 0, 0, w, h);
+    svg_image("debug.png", w, h);
+
+    scan_linear(iscn, img);
+
+    /* retry symbologies not found yet on the inverted image */
+    if (iscn->inverted_syms & ~found_syms(syms)) {
+	iscn->invert = 0xff;
+	scan_linear(iscn, img);
+	iscn->invert = 0;
+    }
+
+    density = CFG(iscn, ZBAR_CFG_X_DENSITY);
     iscn->img = NULL;
 
 #if ENABLE_QRCODE == 1
@@ -1040,6 +1377,20 @@ static void *_zbar_scan_image(zbar_image_scanner_t *iscn, zbar_image_t *img)
     _zbar_sq_decode(iscn->sq, iscn, img);
 #endif
 
+#if ENABLE_PDF417 == 1
+    _zbar_pdf417_decode(iscn->pdf417, iscn, img);
+#endif
+
+#if ENABLE_DATAMATRIX == 1
+    dm_handler(iscn);
+    _zbar_dm_decode(iscn->dm, iscn, img);
+#endif
+
+#if ENABLE_AZTEC == 1
+    az_handler(iscn);
+    _zbar_az_decode(iscn->az, iscn, img);
+#endif
+
     /* FIXME tmp hack to filter bad EAN results */
     /* FIXME tmp hack to merge simple case EAN add-ons */
     filter = (!iscn->enable_cache &&
@@ -1052,7 +1403,8 @@ static void *_zbar_scan_image(zbar_image_scanner_t *iscn, zbar_image_t *img)
 	    zbar_symbol_t *sym = *symp;
 	    if (sym->cache_count <= 0 &&
 		((sym->type < ZBAR_COMPOSITE && sym->type > ZBAR_PARTIAL) ||
-		 sym->type == ZBAR_DATABAR || sym->type == ZBAR_DATABAR_EXP ||
+		 (sym->type >= ZBAR_DATABAR_STK &&
+		  sym->type <= ZBAR_DATABAR_LTD) ||
 		 sym->type == ZBAR_CODABAR)) {
 		if ((sym->type == ZBAR_CODABAR || filter) && sym->quality < 4) {
 		    if (iscn->enable_cache) {
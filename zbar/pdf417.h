@@ -0,0 +1,48 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+#ifndef _PDF417_READER_H_
+#define _PDF417_READER_H_
+
+#include <zbar.h>
+
+typedef struct pdf417_reader pdf417_reader;
+
+/* a scan line crossing a complete PDF417 row, from its start pattern to
+ * its stop pattern
+ */
+typedef struct pdf417_row_line {
+    int vert;	   /* scanned by a vertical line */
+    int start[2];  /* image coordinates of the start pattern end */
+    int end[2];	   /* image coordinates of the stop pattern end */
+    int ncw;	   /* number of codewords, including both row indicators */
+    const unsigned char *cw; /* codewords (low 10 bits) and clusters (0-2),
+			      * 2 bytes each, low byte first */
+} pdf417_row_line;
+
+pdf417_reader *_zbar_pdf417_create(void);
+void _zbar_pdf417_destroy(pdf417_reader *reader);
+void _zbar_pdf417_reset(pdf417_reader *reader);
+
+int _zbar_pdf417_found_row(pdf417_reader *reader, const pdf417_row_line *line);
+int _zbar_pdf417_decode(pdf417_reader *reader, zbar_image_scanner_t *iscn,
+			zbar_image_t *img);
+
+#endif
@@ -0,0 +1,408 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+
+/* PDF417 symbol assembly: the rows decoded along the scan lines are
+ * grouped into symbols, their codewords placed in the symbol matrix using
+ * the row indicators, then error corrected and decoded
+ */
+
+#include "config.h"
+
+#include <stdlib.h>
+#include <string.h>
+
+#include "image.h"
+#include "img_scanner.h"
+#include "pdf417.h"
+#include "pdf417dec.h"
+#include "symbol.h"
+
+/* candidate values voted for one codeword of the symbol matrix */
+#define PDF417_NVOTES 4
+
+typedef struct pdf417_line {
+    int vert;
+    int start[2], end[2];
+    int ncw;
+    unsigned short cw[PDF417_MAX_COLS + 2]; /* value | cluster << 10 */
+} pdf417_line;
+
+struct pdf417_reader {
+    pdf417_line *lines;
+    int nlines, clines;
+};
+
+typedef struct pdf417_cell {
+    unsigned short val[PDF417_NVOTES];
+    unsigned short cnt[PDF417_NVOTES];
+} pdf417_cell;
+
+pdf417_reader *_zbar_pdf417_create(void)
+{
+    return (calloc(1, sizeof(pdf417_reader)));
+}
+
+void _zbar_pdf417_destroy(pdf417_reader *reader)
+{
+    free(reader->lines);
+    free(reader);
+}
+
+void _zbar_pdf417_reset(pdf417_reader *reader)
+{
+    reader->nlines = 0;
+}
+
+int _zbar_pdf417_found_row(pdf417_reader *reader, const pdf417_row_line *line)
+{
+    pdf417_line *l;
+    int i;
+
+    if (line->ncw < 3 || line->ncw > PDF417_MAX_COLS + 2)
+	return (-1);
+
+    if (reader->nlines >= reader->clines) {
+	int clines	   = reader->clines ? 2 * reader->clines : 64;
+	pdf417_line *lines = realloc(reader->lines, clines * sizeof(*lines));
+	if (!lines)
+	    return (-1);
+	reader->lines  = lines;
+	reader->clines = clines;
+    }
+
+    l	   = reader->lines + reader->nlines++;
+    l->vert = line->vert;
+    memcpy(l->start, line->start, sizeof(l->start));
+    memcpy(l->end, line->end, sizeof(l->end));
+    l->ncw = line->ncw;
+    for (i = 0; i < line->ncw; i++)
+	l->cw[i] = line->cw[2 * i] | line->cw[2 * i + 1] << 8;
+    return (0);
+}
+
+/* position of a line across (u) and along (v) the scan direction */
+static inline int line_u0(const pdf417_line *l)
+{
+    int a = l->start[l->vert], b = l->end[l->vert];
+    return ((a < b) ? a : b);
+}
+
+static inline int line_u1(const pdf417_line *l)
+{
+    int a = l->start[l->vert], b = l->end[l->vert];
+    return ((a < b) ? b : a);
+}
+
+static inline int line_v(const pdf417_line *l)
+{
+    return (l->start[!l->vert]);
+}
+
+/* whether a line continues the symbol of the lines of a group: parallel,
+ * mostly overlapping and close to them
+ */
+static int line_joins(const pdf417_line *l, const pdf417_line *lines,
+		      const int *group, int ngroup)
+{
+    int u0 = line_u0(l), u1 = line_u1(l), v = line_v(l);
+    int gap = (u1 - u0) / 4;
+    int i;
+    for (i = 0; i < ngroup; i++) {
+	const pdf417_line *m = lines + group[i];
+	int o0, o1;
+	if (m->vert != l->vert || abs(line_v(m) - v) > gap)
+	    continue;
+	o0 = (line_u0(m) > u0) ? line_u0(m) : u0;
+	o1 = (line_u1(m) < u1) ? line_u1(m) : u1;
+	if (2 * (o1 - o0) >= u1 - u0)
+	    return (1);
+    }
+    return (0);
+}
+
+static void vote(pdf417_cell *cell, unsigned val)
+{
+    int i;
+    for (i = 0; i < PDF417_NVOTES; i++)
+	if (cell->cnt[i] && cell->val[i] == val) {
+	    cell->cnt[i]++;
+	    return;
+	}
+    for (i = 0; i < PDF417_NVOTES; i++)
+	if (!cell->cnt[i]) {
+	    cell->val[i] = val;
+	    cell->cnt[i] = 1;
+	    return;
+	}
+}
+
+static int best_vote(const int *votes, int n)
+{
+    int i, best = -1;
+    for (i = 0; i < n; i++)
+	if (votes[i] && (best < 0 || votes[i] > votes[best]))
+	    best = i;
+    return (best);
+}
+
+/* row number given by a row indicator, or -1 */
+static inline int indicator_row(unsigned short cw)
+{
+    int cluster = cw >> 10;
+    if (cluster > 2)
+	return (-1);
+    return (3 * ((cw & 0x3ff) / 30) + cluster);
+}
+
+typedef struct pdf417_params {
+    int rows, cols, ec_level;
+} pdf417_params;
+
+/* vote for the symbol dimensions and error correction level encoded in the
+ * row indicators
+ */
+static int find_params(const pdf417_line *lines, const int *group,
+		       int ngroup, pdf417_params *params)
+{
+    /* (rows - 1) / 3, ec level * 3 + (rows - 1) % 3 and cols - 1 */
+    int votes[3][30];
+    int i, best[3];
+
+    memset(votes, 0, sizeof(votes));
+    for (i = 0; i < ngroup; i++) {
+	const pdf417_line *l = lines + group[i];
+	unsigned short left = l->cw[0], right = l->cw[l->ncw - 1];
+	int cluster;
+
+	/* the left indicator holds the row information, the right one the
+	 * column information, rotated through the clusters
+	 */
+	cluster = left >> 10;
+	if (cluster <= 2)
+	    votes[cluster][(left & 0x3ff) % 30]++;
+	cluster = right >> 10;
+	if (cluster <= 2)
+	    votes[(cluster + 2) % 3][(right & 0x3ff) % 30]++;
+    }
+
+    for (i = 0; i < 3; i++)
+	if ((best[i] = best_vote(votes[i], 30)) < 0)
+	    return (-1);
+
+    params->rows     = 3 * best[0] + best[1] % 3 + 1;
+    params->ec_level = best[1] / 3;
+    params->cols     = best[2] + 1;
+    if (params->rows < PDF417_MIN_ROWS || params->ec_level > PDF417_MAX_EC_LEVEL ||
+	params->rows * params->cols <= (2 << params->ec_level))
+	return (-1);
+    return (0);
+}
+
+/* place the codewords of a line in the matrix, the row of each one given
+ * by its cluster and the nearest row indicator (the line may cross rows)
+ */
+static void place_line(const pdf417_line *l, const pdf417_params *params,
+		       pdf417_cell *matrix)
+{
+    int rows = params->rows, cols = params->cols;
+    int left = indicator_row(l->cw[0]), right = indicator_row(l->cw[cols + 1]);
+    int c;
+
+    if (left >= rows)
+	left = -1;
+    if (right >= rows)
+	right = -1;
+    if (left < 0 && right < 0)
+	return;
+
+    for (c = 0; c < cols; c++) {
+	unsigned short cw = l->cw[c + 1];
+	int cluster = cw >> 10, ref, row, d;
+	if (cluster > 2)
+	    continue;
+	ref = (left >= 0 && (right < 0 || 2 * c < cols)) ? left : right;
+	for (d = -1; d <= 1; d++) {
+	    row = ref + d;
+	    if (row >= 0 && row < rows && row % 3 == cluster)
+		break;
+	}
+	if (d > 1)
+	    continue;
+	vote(matrix + row * cols + c, cw & 0x3ff);
+    }
+}
+
+/* assemble, correct and decode the symbol crossed by a group of lines */
+static int decode_group(const pdf417_line *lines, const int *group, int ngroup,
+			unsigned char **data)
+{
+    pdf417_params params;
+    pdf417_cell *matrix;
+    unsigned short *cw;
+    unsigned char *erased;
+    int i, n, necw, len = -1;
+
+    if (find_params(lines, group, ngroup, &params))
+	return (-1);
+
+    n	   = params.rows * params.cols;
+    necw   = 2 << params.ec_level;
+    matrix = calloc(n, sizeof(*matrix));
+    cw	   = calloc(n, sizeof(*cw));
+    erased = calloc(n, 1);
+    if (!matrix || !cw || !erased)
+	goto done;
+
+    for (i = 0; i < ngroup; i++) {
+	const pdf417_line *l = lines + group[i];
+	if (l->ncw == params.cols + 2)
+	    place_line(l, &params, matrix);
+    }
+
+    for (i = 0; i < n; i++) {
+	int j, best = -1;
+	for (j = 0; j < PDF417_NVOTES; j++)
+	    if (matrix[i].cnt[j] &&
+		(best < 0 || matrix[i].cnt[j] > matrix[i].cnt[best]))
+		best = j;
+	if (best < 0)
+	    erased[i] = 1;
+	else
+	    cw[i] = matrix[i].val[best];
+    }
+
+    if (_zbar_pdf417_rs_correct(cw, n, necw, erased) < 0)
+	goto done;
+
+    /* the length descriptor counts the data codewords, itself included */
+    if (cw[0] < 1 || cw[0] > n - necw)
+	goto done;
+    len = _zbar_pdf417_decode_text(cw + 1, cw[0] - 1, data);
+
+done:
+    free(matrix);
+    free(cw);
+    free(erased);
+    return (len);
+}
+
+/* report a decoded symbol, bounded by the outermost lines of its group */
+static void add_symbol(zbar_image_scanner_t *iscn, const pdf417_line *lines,
+		       const int *group, int ngroup, const unsigned char *data,
+		       int len)
+{
+    const pdf417_line *first = lines + group[0], *last = first;
+    zbar_symbol_t *sym;
+    int i, vert = first->vert;
+
+    for (i = 1; i < ngroup; i++) {
+	const pdf417_line *l = lines + group[i];
+	if (line_v(l) < line_v(first))
+	    first = l;
+	if (line_v(l) > line_v(last))
+	    last = l;
+    }
+
+    sym = _zbar_image_scanner_alloc_sym(iscn, ZBAR_PDF417, len + 1);
+    memcpy(sym->data, data, len + 1);
+    sym->quality = ngroup;
+    sym_add_point(sym, first->start[0], first->start[1]);
+    sym_add_point(sym, last->start[0], last->start[1]);
+    sym_add_point(sym, last->end[0], last->end[1]);
+    sym_add_point(sym, first->end[0], first->end[1]);
+
+    /* rows are read from the start pattern to the stop pattern */
+    if (first->start[vert] < first->end[vert])
+	sym->orient = (vert) ? ZBAR_ORIENT_RIGHT : ZBAR_ORIENT_UP;
+    else
+	sym->orient = (vert) ? ZBAR_ORIENT_LEFT : ZBAR_ORIENT_DOWN;
+
+    _zbar_image_scanner_add_sym(iscn, sym);
+}
+
+int _zbar_pdf417_decode(pdf417_reader *reader, zbar_image_scanner_t *iscn,
+			zbar_image_t *img)
+{
+    int *group, *grouped;
+    unsigned char **found;
+    int *nfound;
+    int i, nsyms = 0;
+
+    if (!reader->nlines)
+	return (0);
+
+    group   = malloc(reader->nlines * sizeof(*group));
+    grouped = calloc(reader->nlines, sizeof(*grouped));
+    found   = calloc(reader->nlines, sizeof(*found));
+    nfound  = calloc(reader->nlines, sizeof(*nfound));
+    if (!group || !grouped || !found || !nfound)
+	goto done;
+
+    for (i = 0; i < reader->nlines; i++) {
+	unsigned char *data = NULL;
+	int ngroup = 0, j, len, added;
+
+	if (grouped[i])
+	    continue;
+
+	/* collect the lines of the symbol, growing the group until no other
+	 * line joins it
+	 */
+	group[ngroup++] = i;
+	grouped[i]	= 1;
+	do {
+	    added = 0;
+	    for (j = i + 1; j < reader->nlines; j++)
+		if (!grouped[j] &&
+		    line_joins(reader->lines + j, reader->lines, group, ngroup)) {
+		    group[ngroup++] = j;
+		    grouped[j]	    = 1;
+		    added	    = 1;
+		}
+	} while (added);
+
+	len = decode_group(reader->lines, group, ngroup, &data);
+	if (len < 0)
+	    continue;
+
+	/* the same symbol may be split into several groups */
+	for (j = 0; j < nsyms; j++)
+	    if (nfound[j] == len && !memcmp(found[j], data, len))
+		break;
+	if (j < nsyms) {
+	    free(data);
+	    continue;
+	}
+
+	add_symbol(iscn, reader->lines, group, ngroup, data, len);
+	found[nsyms]	= data;
+	nfound[nsyms++] = len;
+    }
+
+done:
+    for (i = 0; i < nsyms; i++)
+	free(found[i]);
+    free(group);
+    free(grouped);
+    free(found);
+    free(nfound);
+    return (nsyms);
+}
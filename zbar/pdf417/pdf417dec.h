@@ -0,0 +1,49 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+#ifndef _PDF417_PDF417DEC_H_
+#define _PDF417_PDF417DEC_H_
+
+/* codewords are numbers modulo this prime, the size of the Galois field */
+#define PDF417_GF 929
+
+#define PDF417_MIN_ROWS 3
+#define PDF417_MAX_ROWS 90
+#define PDF417_MAX_COLS 30
+
+/* highest error correction level, with 2^(level+1) codewords */
+#define PDF417_MAX_EC_LEVEL 8
+
+/* correct the errors of a symbol's n codewords in place, the last necw of
+ * which are error correction codewords.
+ * codewords flagged in erased (if not NULL) are known to be missing.
+ * returns the number of corrected codewords or -1 if uncorrectable
+ */
+int _zbar_pdf417_rs_correct(unsigned short *cw, int n, int necw,
+			    const unsigned char *erased);
+
+/* decode the data codewords (following the length descriptor) into bytes.
+ * returns the number of bytes written to the newly allocated *data
+ * or -1 for invalid data
+ */
+int _zbar_pdf417_decode_text(const unsigned short *cw, int n,
+			     unsigned char **data);
+
+#endif
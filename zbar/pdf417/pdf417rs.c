@@ -0,0 +1,200 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+
+/* Reed-Solomon error correction over GF(929).
+ * The generator polynomial of a symbol with k error correction codewords is
+ *  (x - 3)(x - 3^2)...(x - 3^k), the first codeword being the coefficient
+ *  of the highest power.
+ */
+
+#include "config.h"
+
+#include <string.h>
+
+#include "pdf417dec.h"
+
+/* 3 generates the multiplicative group of GF(929) */
+#define PDF417_GF_GENERATOR 3
+
+/* maximum number of error correction codewords */
+#define PDF417_MAX_ECW (2 << PDF417_MAX_EC_LEVEL)
+
+typedef struct gf929 {
+    unsigned short exp[PDF417_GF - 1];
+    unsigned short log[PDF417_GF];
+} gf929;
+
+static void gf_init(gf929 *gf)
+{
+    int i;
+    unsigned x = 1;
+    for (i = 0; i < PDF417_GF - 1; i++) {
+	gf->exp[i] = x;
+	gf->log[x] = i;
+	x	   = x * PDF417_GF_GENERATOR % PDF417_GF;
+    }
+    gf->log[0] = 0;
+}
+
+static inline unsigned gf_mul(unsigned a, unsigned b)
+{
+    return (a * b % PDF417_GF);
+}
+
+static inline unsigned gf_sub(unsigned a, unsigned b)
+{
+    return ((a + PDF417_GF - b) % PDF417_GF);
+}
+
+/* generator to the power of e (any sign) */
+static inline unsigned gf_pow(const gf929 *gf, int e)
+{
+    e %= PDF417_GF - 1;
+    if (e < 0)
+	e += PDF417_GF - 1;
+    return (gf->exp[e]);
+}
+
+static inline unsigned gf_inv(const gf929 *gf, unsigned a)
+{
+    return (gf_pow(gf, -(int)gf->log[a]));
+}
+
+/* evaluate the polynomial with coefficients p[0] + p[1]x + ... at x */
+static unsigned poly_eval(const unsigned *p, int deg, unsigned x)
+{
+    unsigned y = 0;
+    int i;
+    for (i = deg; i >= 0; i--)
+	y = (gf_mul(y, x) + p[i]) % PDF417_GF;
+    return (y);
+}
+
+/* compute the syndromes, returns non-zero if any of them is */
+static int calc_syndromes(const gf929 *gf, const unsigned short *cw, int n,
+			  int necw, unsigned *s)
+{
+    int i, j, nonzero = 0;
+    for (j = 0; j < necw; j++) {
+	unsigned x = gf_pow(gf, j + 1), y = 0;
+	for (i = 0; i < n; i++)
+	    y = (gf_mul(y, x) + cw[i]) % PDF417_GF;
+	s[j] = y;
+	nonzero |= y != 0;
+    }
+    return (nonzero);
+}
+
+int _zbar_pdf417_rs_correct(unsigned short *cw, int n, int necw,
+			    const unsigned char *erased)
+{
+    gf929 gf;
+    unsigned s[PDF417_MAX_ECW];
+    unsigned lambda[PDF417_MAX_ECW + 1], b[PDF417_MAX_ECW + 1];
+    unsigned t[PDF417_MAX_ECW + 1], omega[PDF417_MAX_ECW];
+    int i, j, r, l, ne = 0, nfound = 0;
+
+    if (necw > PDF417_MAX_ECW || necw >= n)
+	return (-1);
+
+    gf_init(&gf);
+
+    /* missing codewords are decoded as errors at known positions */
+    if (erased)
+	for (i = 0; i < n; i++)
+	    if (erased[i])
+		cw[i] = 0;
+
+    if (!calc_syndromes(&gf, cw, n, necw, s))
+	return (0);
+
+    /* start from the erasure locator */
+    memset(lambda, 0, sizeof(lambda));
+    lambda[0] = 1;
+    if (erased)
+	for (i = 0; i < n; i++) {
+	    unsigned x;
+	    if (!erased[i])
+		continue;
+	    if (++ne > necw)
+		return (-1);
+	    /* multiply by (1 - X x) */
+	    x = gf_pow(&gf, n - 1 - i);
+	    for (j = ne; j > 0; j--)
+		lambda[j] = gf_sub(lambda[j], gf_mul(x, lambda[j - 1]));
+	}
+    memcpy(b, lambda, sizeof(b));
+
+    /* Berlekamp-Massey, taking the erasures into account */
+    l = ne;
+    for (r = ne; r < necw; r++) {
+	unsigned delta = 0;
+	for (i = 0; i <= l && i <= r; i++)
+	    delta = (delta + gf_mul(lambda[i], s[r - i])) % PDF417_GF;
+
+	/* b *= x */
+	memmove(b + 1, b, necw * sizeof(*b));
+	b[0] = 0;
+	if (!delta)
+	    continue;
+
+	for (i = 0; i <= necw; i++)
+	    t[i] = gf_sub(lambda[i], gf_mul(delta, b[i]));
+	if (2 * l <= r + ne) {
+	    unsigned inv = gf_inv(&gf, delta);
+	    l		 = r + 1 + ne - l;
+	    for (i = 0; i <= necw; i++)
+		b[i] = gf_mul(lambda[i], inv);
+	}
+	memcpy(lambda, t, sizeof(t));
+    }
+
+    if (2 * l - ne > necw)
+	return (-1);
+
+    /* error evaluator: omega = s * lambda mod x^necw */
+    for (i = 0; i < necw; i++) {
+	omega[i] = 0;
+	for (j = 0; j <= i && j <= l; j++)
+	    omega[i] = (omega[i] + gf_mul(lambda[j], s[i - j])) % PDF417_GF;
+    }
+
+    /* Chien search and Forney's algorithm */
+    for (i = 0; i < n; i++) {
+	unsigned xinv = gf_pow(&gf, -(n - 1 - i)), num, den = 0;
+	if (poly_eval(lambda, l, xinv))
+	    continue;
+	nfound++;
+	/* formal derivative of lambda at xinv */
+	for (j = 1; j <= l; j += 1)
+	    den = (den + gf_mul(gf_mul(j % PDF417_GF, lambda[j]),
+				gf_pow(&gf, -(n - 1 - i) * (j - 1)))) %
+		  PDF417_GF;
+	if (!den)
+	    return (-1);
+	num   = poly_eval(omega, necw - 1, xinv);
+	cw[i] = (cw[i] + gf_mul(num, gf_inv(&gf, den))) % PDF417_GF;
+    }
+
+    if (nfound != l || calc_syndromes(&gf, cw, n, necw, s))
+	return (-1);
+    return (nfound);
+}
@@ -0,0 +1,294 @@
+/*------------------------------------------------------------------------
+ *  This file is part of the ZBar Bar Code Reader.
+ *
+ *  The ZBar Bar Code Reader is free software; you can redistribute it
+ *  and/or modify it under the terms of the GNU Lesser Public License as
+ *  published by the Free Software Foundation; either version 2.1 of
+ *  the License, or (at your option) any later version.
+ *
+ *  The ZBar Bar Code Reader is distributed in the hope that it will be
+ *  useful, but WITHOUT ANY WARRANTY; without even the implied warranty
+ *  of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU Lesser Public License for more details.
+ *
+ *  You should have received a copy of the GNU Lesser Public License
+ *  along with the ZBar Bar Code Reader; if not, write to the Free
+ *  Software Foundation, Inc., 51 Franklin St, Fifth Floor,
+ *  Boston, MA  02110-1301  USA
+ *
+ *  http://sourceforge.net/projects/zbar
+ *------------------------------------------------------------------------*/
+
+/* PDF417 high-level decoding of the text, byte and numeric compaction
+ * modes
+ */
+
+#include "config.h"
+
+#include <stdlib.h>
+#include <string.h>
+
+#include "pdf417dec.h"
+
+/* mode latch and shift codewords */
+#define PDF417_TEXT_LATCH	900
+#define PDF417_BYTE_LATCH	901
+#define PDF417_NUMERIC_LATCH	902
+#define PDF417_BYTE_SHIFT	913
+#define PDF417_READER_INIT	921
+#define PDF417_MACRO_TERMINATOR 922
+#define PDF417_MACRO_OPTIONAL	923
+#define PDF417_BYTE_LATCH_6	924
+#define PDF417_ECI_USER		925
+#define PDF417_ECI_GENERAL	926
+#define PDF417_ECI_CHARSET	927
+#define PDF417_MACRO		928
+
+/* numeric compaction converts groups of up to 15 codewords */
+#define PDF417_NUMERIC_GROUP 15
+
+/* text compaction sub-modes */
+typedef enum pdf417_submode_e
+{
+    PDF417_ALPHA,
+    PDF417_LOWER,
+    PDF417_MIXED,
+    PDF417_PUNCT,
+} pdf417_submode_t;
+
+static const char pdf417_mixed[25] = "0123456789&\r\t,:#-.$/+%*=^";
+static const char pdf417_punct[29] = ";<>@[\\]_`~!\r\t,:\n-.$/\"|*()?{}'";
+
+typedef struct pdf417_text {
+    unsigned char *buf;
+    int len, alloc;
+    pdf417_submode_t submode; /* latched text sub-mode */
+    int shift;		      /* sub-mode of the next value only, or -1 */
+} pdf417_text;
+
+static int text_push(pdf417_text *txt, unsigned char c)
+{
+    if (txt->len >= txt->alloc) {
+	int alloc	   = txt->alloc ? 2 * txt->alloc : 64;
+	unsigned char *buf = realloc(txt->buf, alloc);
+	if (!buf)
+	    return (-1);
+	txt->buf   = buf;
+	txt->alloc = alloc;
+    }
+    txt->buf[txt->len++] = c;
+    return (0);
+}
+
+/* decode one of the two base 30 values of a text compaction codeword */
+static int text_value(pdf417_text *txt, unsigned v)
+{
+    pdf417_submode_t mode = txt->submode;
+    int shifted		  = txt->shift >= 0;
+    if (shifted) {
+	mode	   = txt->shift;
+	txt->shift = -1;
+    }
+
+    if (v == 26 && mode != PDF417_PUNCT)
+	return (text_push(txt, ' '));
+
+    switch (mode) {
+    case PDF417_ALPHA:
+    case PDF417_LOWER:
+	if (v < 26)
+	    return (text_push(txt, ((mode == PDF417_ALPHA) ? 'A' : 'a') + v));
+	if (shifted)
+	    return (0);
+	if (v == 27) {
+	    /* lower latch from alpha, alpha shift from lower */
+	    if (mode == PDF417_ALPHA)
+		txt->submode = PDF417_LOWER;
+	    else
+		txt->shift = PDF417_ALPHA;
+	} else if (v == 28)
+	    txt->submode = PDF417_MIXED;
+	else
+	    txt->shift = PDF417_PUNCT;
+	return (0);
+
+    case PDF417_MIXED:
+	if (v < 25)
+	    return (text_push(txt, pdf417_mixed[v]));
+	if (shifted)
+	    return (0);
+	if (v == 25)
+	    txt->submode = PDF417_PUNCT;
+	else if (v == 27)
+	    txt->submode = PDF417_LOWER;
+	else if (v == 28)
+	    txt->submode = PDF417_ALPHA;
+	else
+	    txt->shift = PDF417_PUNCT;
+	return (0);
+
+    default:
+	if (v < 29)
+	    return (text_push(txt, pdf417_punct[v]));
+	/* alpha latch (also ends a punctuation shift) */
+	txt->submode = PDF417_ALPHA;
+	return (0);
+    }
+}
+
+/* text compaction up to the next mode latch, returns the index reached */
+static int decode_text_mode(pdf417_text *txt, const unsigned short *cw,
+			    int n, int i)
+{
+    for (; i < n; i++) {
+	if (cw[i] == PDF417_BYTE_SHIFT) {
+	    /* a single byte, then back to the current sub-mode */
+	    if (++i >= n || cw[i] > 0xff || text_push(txt, cw[i]))
+		return (-1);
+	    continue;
+	}
+	if (cw[i] >= PDF417_TEXT_LATCH)
+	    break;
+	if (text_value(txt, cw[i] / 30) || text_value(txt, cw[i] % 30))
+	    return (-1);
+    }
+    /* a trailing shift pads the last codeword */
+    txt->shift = -1;
+    return (i);
+}
+
+/* byte compaction, 5 codewords encoding 6 bytes */
+static int decode_byte_mode(pdf417_text *txt, const unsigned short *cw, int n,
+			    int i, int mode)
+{
+    int end = i;
+    while (end < n && cw[end] < PDF417_TEXT_LATCH)
+	end++;
+
+    while (i < end) {
+	/* with latch 901, the last group is only complete if more follow */
+	if (end - i >= 5 && (mode == PDF417_BYTE_LATCH_6 || end - i > 5)) {
+	    unsigned long long v = 0;
+	    int j;
+	    for (j = 0; j < 5; j++)
+		v = v * 900 + cw[i + j];
+	    if (v >> 48)
+		return (-1);
+	    for (j = 5; j >= 0; j--)
+		if (text_push(txt, (v >> (8 * j)) & 0xff))
+		    return (-1);
+	    i += 5;
+	} else {
+	    if (cw[i] > 0xff || text_push(txt, cw[i]))
+		return (-1);
+	    i++;
+	}
+    }
+    return (end);
+}
+
+/* numeric compaction, groups of base 900 numbers with a leading 1 digit */
+static int decode_numeric_mode(pdf417_text *txt, const unsigned short *cw,
+			       int n, int i)
+{
+    while (i < n && cw[i] < PDF417_TEXT_LATCH) {
+	/* little endian decimal digits */
+	unsigned char dec[3 * PDF417_NUMERIC_GROUP];
+	int ndec = 0, j, k;
+	for (j = 0; j < PDF417_NUMERIC_GROUP && i < n &&
+		    cw[i] < PDF417_TEXT_LATCH;
+	     j++, i++) {
+	    unsigned carry = cw[i];
+	    for (k = 0; k < ndec; k++) {
+		carry += dec[k] * 900;
+		dec[k] = carry % 10;
+		carry /= 10;
+	    }
+	    for (; carry; carry /= 10) {
+		if (ndec >= (int)sizeof(dec))
+		    return (-1);
+		dec[ndec++] = carry % 10;
+	    }
+	}
+	if (!ndec || dec[ndec - 1] != 1)
+	    return (-1);
+	for (k = ndec - 2; k >= 0; k--)
+	    if (text_push(txt, '0' + dec[k]))
+		return (-1);
+    }
+    return (i);
+}
+
+int _zbar_pdf417_decode_text(const unsigned short *cw, int n,
+			     unsigned char **data)
+{
+    pdf417_text txt;
+    int i = 0, mode = PDF417_TEXT_LATCH;
+
+    memset(&txt, 0, sizeof(txt));
+    txt.submode = PDF417_ALPHA;
+    txt.shift	= -1;
+
+    while (i >= 0 && i < n) {
+	switch (mode) {
+	case PDF417_TEXT_LATCH:
+	    i = decode_text_mode(&txt, cw, n, i);
+	    break;
+	case PDF417_BYTE_LATCH:
+	case PDF417_BYTE_LATCH_6:
+	    i = decode_byte_mode(&txt, cw, n, i, mode);
+	    break;
+	case PDF417_NUMERIC_LATCH:
+	    i = decode_numeric_mode(&txt, cw, n, i);
+	    break;
+	}
+	if (i < 0 || i >= n)
+	    break;
+
+	switch (cw[i]) {
+	case PDF417_TEXT_LATCH:
+	    txt.submode = PDF417_ALPHA;
+	    /* fall through */
+	case PDF417_BYTE_LATCH:
+	case PDF417_BYTE_LATCH_6:
+	case PDF417_NUMERIC_LATCH:
+	    mode = cw[i++];
+	    break;
+	case PDF417_BYTE_SHIFT:
+	    /* only valid in text compaction */
+	    i = -1;
+	    break;
+	case PDF417_READER_INIT:
+	    i++;
+	    break;
+	case PDF417_ECI_CHARSET:
+	case PDF417_ECI_USER:
+	    /* FIXME ECIs are not interpreted, the data is passed through */
+	    i += 2;
+	    break;
+	case PDF417_ECI_GENERAL:
+	    i += 3;
+	    break;
+	case PDF417_MACRO:
+	case PDF417_MACRO_OPTIONAL:
+	case PDF417_MACRO_TERMINATOR:
+	    /* FIXME macro PDF417 control block is not decoded */
+	    n = i;
+	    break;
+	default:
+	    i = -1;
+	    break;
+	}
+    }
+
+    if (i < 0) {
+	free(txt.buf);
+	return (-1);
+    }
+    if (text_push(&txt, '\0')) {
+	free(txt.buf);
+	return (-1);
+    }
+    *data = txt.buf;
+    return (txt.len - 1);
+}
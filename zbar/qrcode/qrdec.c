@@ -7,6 +7,7 @@
 #include "config.h"
 
 #include <limits.h>
+#include <math.h>
 #include <stdlib.h>
 #include <string.h>
 #include <time.h>
@@ -70,6 +71,8 @@ struct qr_reader {
     isaac_ctx isaac;
     /* current finder state, horizontal and vertical lines */
     qr_finder_lines finder_lines[2];
+    /*Whether to try the transposed module grid when decoding fails.*/
+    int test_mirrored;
 };
 
 /*Initializes a client reader handle.*/
@@ -431,7 +434,7 @@ static int qr_finder_find_crossings(qr_finder_center *_centers,
   Return: The number of putative finder centers located.*/
 static int qr_finder_centers_locate(qr_finder_center **_centers,
 				    qr_finder_edge_pt **_edge_pts,
-				    qr_reader *reader, int _width, int _height)
+				    qr_reader *reader, int _min_centers)
 {
     qr_finder_line *hlines = reader->finder_lines[0].lines;
     int nhlines		   = reader->finder_lines[0].nlines;
@@ -464,8 +467,9 @@ static int qr_finder_centers_locate(qr_finder_center **_centers,
 	(qr_finder_cluster *)malloc((nvlines >> 1) * sizeof(*vclusters));
     nvclusters =
 	qr_finder_cluster_lines(vclusters, vneighbors, vlines, nvlines, 1);
-    /*Find line crossings among the clusters.*/
-    if (nhclusters >= 3 && nvclusters >= 3) {
+    /*Find line crossings among the clusters.
+      A QR code needs 3 finders, a Micro QR or rMQR code only one.*/
+    if (nhclusters >= _min_centers && nvclusters >= _min_centers) {
 	qr_finder_edge_pt *edge_pts;
 	qr_finder_center *centers;
 	int nedge_pts;
@@ -2518,12 +2522,10 @@ static int bch18_6_correct(unsigned *_y)
     return -1;
 }
 
-#if 0
 static unsigned bch18_6_encode(unsigned _x){
   return (-(_x&1)&0x01F25)^(-(_x>>1&1)&0x0216F)^(-(_x>>2&1)&0x042DE)^
    (-(_x>>3&1)&0x085BC)^(-(_x>>4&1)&0x10B78)^(-(_x>>5&1)&0x209D5);
 }
-#endif
 
 /*Reads the version bits near a finder module and decodes the version number.*/
 static int qr_finder_version_decode(qr_finder *_f, const qr_hom *_hom,
@@ -3453,13 +3455,48 @@ static const unsigned char QR_ALNUM_TABLE[45] = {
     'U', 'V', 'W', 'X', 'Y', 'Z', ' ', '$', '%', '*', '+', '-', '.', '/', ':'
 };
 
-static int qr_code_data_parse(qr_code_data *_qrdata, int _version,
+/*The number of bits used to encode the character count for each version range
+   and each data mode.*/
+static const unsigned char QR_LEN_BITS[3][4] = { { 10, 9, 8, 8 },
+						 { 12, 11, 16, 10 },
+						 { 14, 13, 16, 12 } };
+
+/*The same for each Micro QR code version, which does not support all modes.*/
+static const unsigned char QR_MICRO_LEN_BITS[4][4] = { { 3, 0, 0, 0 },
+						       { 4, 3, 0, 0 },
+						       { 5, 4, 4, 3 },
+						       { 6, 5, 5, 4 } };
+
+/*The data modes of Micro QR codes, by mode indicator.*/
+static const unsigned char QR_MICRO_MODES[4] = { QR_MODE_NUM, QR_MODE_ALNUM,
+						 QR_MODE_BYTE, QR_MODE_KANJI };
+
+/*The same for each rMQR code version.*/
+static const unsigned char QR_RMQR_LEN_BITS[32][4] = {
+    { 4, 3, 3, 2 }, { 5, 5, 4, 3 }, { 6, 5, 5, 4 }, { 7, 6, 5, 5 },
+    { 7, 6, 6, 5 }, { 5, 5, 4, 3 }, { 6, 5, 5, 4 }, { 7, 6, 5, 5 },
+    { 7, 6, 6, 5 }, { 8, 7, 6, 6 }, { 4, 4, 3, 2 }, { 6, 5, 5, 4 },
+    { 7, 6, 5, 5 }, { 7, 6, 6, 5 }, { 8, 7, 6, 6 }, { 8, 7, 7, 6 },
+    { 5, 5, 4, 3 }, { 6, 6, 5, 5 }, { 7, 6, 6, 5 }, { 7, 6, 6, 5 },
+    { 8, 7, 7, 6 }, { 8, 8, 7, 7 }, { 7, 6, 6, 5 }, { 7, 7, 6, 5 },
+    { 8, 7, 7, 6 }, { 8, 7, 7, 6 }, { 9, 8, 7, 7 }, { 7, 6, 6, 5 },
+    { 8, 7, 6, 6 }, { 8, 7, 7, 6 }, { 8, 8, 7, 6 }, { 9, 8, 8, 7 }
+};
+
+/*The data modes of rMQR codes, by their 3 bit mode indicator.
+  Indicator 0 is the terminator.*/
+static const unsigned char QR_RMQR_MODES[8] = {
+    0,		    QR_MODE_NUM,      QR_MODE_ALNUM,	QR_MODE_BYTE,
+    QR_MODE_KANJI, QR_MODE_FNC1_1ST, QR_MODE_FNC1_2ND, QR_MODE_ECI
+};
+
+static int qr_code_data_parse(qr_code_data *_qrdata, int _version, int _format,
 			      const unsigned char *_data, int _ndata)
 {
     qr_pack_buf qpb;
     unsigned self_parity;
     int centries;
-    int len_bits_idx;
+    const unsigned char *len_bits;
     /*Entries are stored directly in the struct during parsing.
     Caller cleans up any allocated data on failure.*/
     _qrdata->entries  = NULL;
@@ -3469,16 +3506,39 @@ static int qr_code_data_parse(qr_code_data *_qrdata, int _version,
     centries	      = 0;
     /*The versions are divided into 3 ranges that each use a different number of
      bits for length fields.*/
-    len_bits_idx = (_version > 9) + (_version > 26);
+    if (_format == QR_FORMAT_MICRO)
+	len_bits = QR_MICRO_LEN_BITS[_version - 1];
+    else if (_format == QR_FORMAT_RMQR)
+	len_bits = QR_RMQR_LEN_BITS[_version - 1];
+    else
+	len_bits = QR_LEN_BITS[(_version > 9) + (_version > 26)];
     qr_pack_buf_init(&qpb, _data, _ndata);
     /*While we have enough bits to read a mode...*/
-    while (qr_pack_buf_avail(&qpb) >= 4) {
+    while (qr_pack_buf_avail(&qpb) >= (_format == QR_FORMAT_MICRO ?
+					   2 * _version + 1 :
+					   _format == QR_FORMAT_RMQR ? 3 : 4)) {
 	qr_code_data_entry *entry;
 	int mode;
-	mode = qr_pack_buf_read(&qpb, 4);
-	/*Mode 0 is a terminator.*/
-	if (!mode)
-	    break;
+	if (_format == QR_FORMAT_MICRO) {
+	    int indicator;
+	    /*Micro QR codes use 0...3 bits for the mode indicator, depending on
+	       the version, and numeric mode is 0.
+	      Their terminator reads as an empty numeric segment instead.*/
+	    indicator = qr_pack_buf_read(&qpb, _version - 1);
+	    /*The smaller versions do not support all modes.*/
+	    if (!len_bits[indicator])
+		return -1;
+	    mode = QR_MICRO_MODES[indicator];
+	} else if (_format == QR_FORMAT_RMQR) {
+	    mode = QR_RMQR_MODES[qr_pack_buf_read(&qpb, 3)];
+	    if (!mode)
+		break;
+	} else {
+	    mode = qr_pack_buf_read(&qpb, 4);
+	    /*Mode 0 is a terminator.*/
+	    if (!mode)
+		break;
+	}
 	if (_qrdata->nentries >= centries) {
 	    centries	     = centries << 1 | 1;
 	    _qrdata->entries = (qr_code_data_entry *)realloc(
@@ -3490,11 +3550,6 @@ static int qr_code_data_parse(qr_code_data *_qrdata, int _version,
        on clean-up.*/
 	entry->payload.data.buf = NULL;
 	switch (mode) {
-	    /*The number of bits used to encode the character count for each version
-         range and each data mode.*/
-	    static const unsigned char LEN_BITS[3][4] = { { 10, 9, 8, 8 },
-							  { 12, 11, 16, 10 },
-							  { 14, 13, 16, 12 } };
 	case QR_MODE_NUM: {
 	    unsigned char *buf;
 	    unsigned bits;
@@ -3502,9 +3557,13 @@ static int qr_code_data_parse(qr_code_data *_qrdata, int _version,
 	    int len;
 	    int count;
 	    int rem;
-	    len = qr_pack_buf_read(&qpb, LEN_BITS[len_bits_idx][0]);
+	    len = qr_pack_buf_read(&qpb, len_bits[0]);
 	    if (len < 0)
 		return -1;
+	    if (_format == QR_FORMAT_MICRO && !len) {
+		_qrdata->nentries--;
+		goto done;
+	    }
 	    /*Check to see if there are enough bits left now, so we don't have to
            in the decode loop.*/
 	    count = len / 3;
@@ -3560,7 +3619,7 @@ static int qr_code_data_parse(qr_code_data *_qrdata, int _version,
 	    int len;
 	    int count;
 	    int rem;
-	    len = qr_pack_buf_read(&qpb, LEN_BITS[len_bits_idx][1]);
+	    len = qr_pack_buf_read(&qpb, len_bits[1]);
 	    if (len < 0)
 		return -1;
 	    /*Check to see if there are enough bits left now, so we don't have to
@@ -3619,7 +3678,7 @@ static int qr_code_data_parse(qr_code_data *_qrdata, int _version,
 	    unsigned char *buf;
 	    unsigned c;
 	    int len;
-	    len = qr_pack_buf_read(&qpb, LEN_BITS[len_bits_idx][2]);
+	    len = qr_pack_buf_read(&qpb, len_bits[2]);
 	    if (len < 0)
 		return -1;
 	    /*Check to see if there are enough bits left now, so we don't have to
@@ -3677,7 +3736,7 @@ static int qr_code_data_parse(qr_code_data *_qrdata, int _version,
 	    unsigned char *buf;
 	    unsigned bits;
 	    int len;
-	    len = qr_pack_buf_read(&qpb, LEN_BITS[len_bits_idx][3]);
+	    len = qr_pack_buf_read(&qpb, len_bits[3]);
 	    if (len < 0)
 		return -1;
 	    /*Check to see if there are enough bits left now, so we don't have to
@@ -3725,6 +3784,7 @@ static int qr_code_data_parse(qr_code_data *_qrdata, int _version,
 	} break;
 	}
     }
+done:
     /*Store the parity of the data from this code, for S-A.
     The final parity is the 8-bit XOR of all the decoded bytes of literal data.
     We don't combine the 2-byte kanji codes into one byte in the loops above,
@@ -3946,7 +4006,8 @@ static int qr_code_decode(qr_code_data *_qrdata, const rs_gf256 *_gf,
     }
     /*Parse the corrected bitstream.*/
     if (ret >= 0) {
-	ret = qr_code_data_parse(_qrdata, _version, block_data, ndata);
+	ret = qr_code_data_parse(_qrdata, _version, QR_FORMAT_QR, block_data,
+				 ndata);
 	/*We could return any partially decoded data, but then we'd have to have
        API support for that; a mode ignoring ECC errors might also be useful.*/
 	if (ret < 0)
@@ -4181,6 +4242,8 @@ static int qr_reader_try_configuration(qr_reader *_reader,
 	}
 	fmt_info = qr_finder_fmt_info_decode(&ul, &ur, &dl, &hom, _img, _width,
 					     _height);
+	_qrdata->mirrored = 0;
+	_qrdata->format	  = QR_FORMAT_QR;
 	if (fmt_info < 0 ||
 	    qr_code_decode(_qrdata, &_reader->gf, ul.c->pos, ur.c->pos,
 			   dl.c->pos, ur_version, fmt_info, _img, _width,
@@ -4190,6 +4253,8 @@ static int qr_reader_try_configuration(qr_reader *_reader,
         We should get a valid version either way, so it's relatively cheap to
          check this, as we've already filtered out a lot of invalid
          configurations.*/
+	    if (!_reader->test_mirrored)
+		continue;
 	    QR_SWAP2I(hom.inv[0][0], hom.inv[1][0]);
 	    QR_SWAP2I(hom.inv[0][1], hom.inv[1][1]);
 	    QR_SWAP2I(hom.fwd[0][0], hom.fwd[0][1]);
@@ -4217,6 +4282,7 @@ static int qr_reader_try_configuration(qr_reader *_reader,
 			       _height) < 0) {
 		continue;
 	    }
+	    _qrdata->mirrored = 1;
 	}
 	return ur_version;
     }
@@ -4317,6 +4383,867 @@ void qr_reader_match_centers(qr_reader *_reader, qr_code_data_list *_qrlist,
     free(mark);
 }
 
+/*Micro QR codes have a single finder pattern, in their upper-left corner, and
+   timing patterns along their first row and column instead of the other two.
+  The module grid is projected from the finder center, its orientation and
+   module size being fit to the finder edge points, so the finder centers not
+   used by any QR code are tried as Micro QR codes.*/
+
+/*The number of data bits (the last data codeword of M1 and M3 codes has only
+   four), of parity codewords and of errors which may be corrected for each
+   symbol number: M1, M2-L, M2-M, M3-L, M3-M, M4-L, M4-M and M4-Q.
+  The remaining parity codewords are reserved for error detection.*/
+static const unsigned char QR_MICRO_CODES[8][3] = {
+    { 20, 2, 0 },  { 40, 5, 1 },  { 32, 6, 2 },	  { 84, 6, 2 },
+    { 68, 8, 4 },  { 128, 8, 3 }, { 112, 10, 5 }, { 80, 14, 7 }
+};
+
+/*The version and ECC level of each symbol number.*/
+static const unsigned char QR_MICRO_VERSION[8] = { 1, 2, 2, 3, 3, 4, 4, 4 };
+static const unsigned char QR_MICRO_ECC_LEVEL[8] = { 0, 0, 1, 0, 1, 0, 1, 2 };
+
+/*The most modules of the finder, separator and timing patterns of an M1 code
+   which may be sampled wrong.*/
+#define QR_MICRO_MAX_PATTERN_ERRS (7)
+
+/*A module grid projected from the finder center of a Micro QR or rMQR code,
+   in pixels.*/
+typedef struct qr_micro_grid {
+    /*The center of the finder, that is of module (3,3).*/
+    double c[2];
+    /*The steps to the next column and row.*/
+    double u[2];
+    double v[2];
+} qr_micro_grid;
+
+static int qr_micro_get_bit(const qr_micro_grid *_grid,
+			    const unsigned char *_img, int _width, int _height,
+			    int _row, int _col)
+{
+    double x;
+    double y;
+    x = _grid->c[0] + (_col - 3) * _grid->u[0] + (_row - 3) * _grid->v[0];
+    y = _grid->c[1] + (_col - 3) * _grid->u[1] + (_row - 3) * _grid->v[1];
+    return qr_img_get_bit(_img, _width, _height,
+			  (int)floor(x * (1 << QR_FINDER_SUBPREC)),
+			  (int)floor(y * (1 << QR_FINDER_SUBPREC)));
+}
+
+/*Fits a square to the edge points of a finder, which lie in the middle of its
+   outer ring, 3 modules away from its center.
+  Return: 0 on success, with the angle of one of the sides and the module size,
+   or a negative value if there are too few points.*/
+static int qr_micro_fit_finder(const qr_finder_center *_c, double *_theta,
+			       double *_module)
+{
+    double best;
+    double step;
+    double theta0;
+    int i;
+    int k;
+    if (_c->nedge_pts < 8)
+	return -1;
+    *_module = 0;
+    best     = -1;
+    theta0 = 0;
+    /*Search a coarse, then a fine angle minimizing the spread of the distances
+       to the square.*/
+    for (step = M_PI / 180, k = 0; k < 2; k++, step /= 10) {
+	double start;
+	int nsteps;
+	int j;
+	start  = (k) ? *_theta - 10 * step : 0;
+	nsteps = (k) ? 21 : 90;
+	for (j = 0; j < nsteps; j++) {
+	    double theta;
+	    double cs;
+	    double sn;
+	    double sum;
+	    double sum2;
+	    double err;
+	    theta = start + j * step;
+	    cs	  = cos(theta);
+	    sn	  = sin(theta);
+	    sum = sum2 = 0;
+	    for (i = 0; i < _c->nedge_pts; i++) {
+		double x;
+		double y;
+		double r;
+		x = _c->edge_pts[i].pos[0] - _c->pos[0];
+		y = _c->edge_pts[i].pos[1] - _c->pos[1];
+		r = fmax(fabs(x * cs + y * sn),
+					  fabs(y * cs - x * sn));
+		sum += r;
+		sum2 += r * r;
+	    }
+	    err = sum2 - sum * sum / _c->nedge_pts;
+	    if (best < 0 || err < best) {
+		best   = err;
+		theta0 = theta;
+		*_module = sum / (3 * _c->nedge_pts) / (1 << QR_FINDER_SUBPREC);
+	    }
+	}
+	*_theta = theta0;
+    }
+    return *_module >= 1 ? 0 : -1;
+}
+
+/*Counts the modules of the finder, separator and timing patterns sampled wrong,
+   with the timing patterns up to column and row _dim-1.*/
+static int qr_micro_pattern_errors(const qr_micro_grid *_grid,
+				   const unsigned char *_img, int _width,
+				   int _height, int _dim)
+{
+    int nerrs;
+    int i;
+    int j;
+    nerrs = 0;
+    for (i = 0; i < 8; i++)
+	for (j = 0; j < 8; j++) {
+	    int d;
+	    d = QR_MAXI(abs(i - 3), abs(j - 3));
+	    /*The separator is the light ring at d=4.*/
+	    nerrs += qr_micro_get_bit(_grid, _img, _width, _height, i, j) !=
+		     (d != 2 && d != 4);
+	}
+    for (i = 8; i < _dim; i++) {
+	nerrs += qr_micro_get_bit(_grid, _img, _width, _height, 0, i) !=
+		 !(i & 1);
+	nerrs += qr_micro_get_bit(_grid, _img, _width, _height, i, 0) !=
+		 !(i & 1);
+    }
+    return nerrs;
+}
+
+/*Searches small rotations and scales of the grid around the finder center
+   matching the timing patterns best, as they span the whole code.*/
+static void qr_micro_grid_refine(qr_micro_grid *_grid,
+				 const unsigned char *_img, int _width,
+				 int _height, int _dim)
+{
+    qr_micro_grid best;
+    int best_nerrs;
+    int best_dist;
+    int a;
+    int su;
+    int sv;
+    best       = *_grid;
+    best_nerrs = qr_micro_pattern_errors(_grid, _img, _width, _height, _dim);
+    best_dist  = 0;
+    for (a = -4; a <= 4; a++)
+	for (su = -4; su <= 4; su++)
+	    for (sv = -4; sv <= 4; sv++) {
+		qr_micro_grid grid;
+		double cs;
+		double sn;
+		int nerrs;
+		int dist;
+		dist = abs(a) + abs(su) + abs(sv);
+		if (!dist)
+		    continue;
+		cs	  = cos(0.01 * a);
+		sn	  = sin(0.01 * a);
+		grid.c[0] = _grid->c[0];
+		grid.c[1] = _grid->c[1];
+		grid.u[0] = _grid->u[0] * cs - _grid->u[1] * sn;
+		grid.u[1] = _grid->u[0] * sn + _grid->u[1] * cs;
+		grid.v[0] = _grid->v[0] * cs - _grid->v[1] * sn;
+		grid.v[1] = _grid->v[0] * sn + _grid->v[1] * cs;
+		grid.u[0] *= 1 + 0.01 * su;
+		grid.u[1] *= 1 + 0.01 * su;
+		grid.v[0] *= 1 + 0.01 * sv;
+		grid.v[1] *= 1 + 0.01 * sv;
+		nerrs =
+		    qr_micro_pattern_errors(&grid, _img, _width, _height, _dim);
+		if (nerrs < best_nerrs ||
+		    nerrs == best_nerrs && dist < best_dist) {
+		    best       = grid;
+		    best_nerrs = nerrs;
+		    best_dist  = dist;
+		}
+	    }
+    *_grid = best;
+}
+
+/*Reads and corrects the format information next to the finder.
+  Return: The symbol number and the mask pattern, in 3 and 2 bits, or a
+   negative value on error.*/
+static int qr_micro_fmt_info_decode(const qr_micro_grid *_grid,
+				    const unsigned char *_img, int _width,
+				    int _height)
+{
+    unsigned v;
+    int k;
+    v = 0;
+    /*The low bits run down column 8, the high ones from column 1 of row 8.*/
+    for (k = 0; k < 8; k++)
+	v |= qr_micro_get_bit(_grid, _img, _width, _height, k + 1, 8) << k;
+    for (k = 0; k < 7; k++)
+	v |= qr_micro_get_bit(_grid, _img, _width, _height, 8, k + 1) << 14 - k;
+    v ^= 0x4445;
+    if (bch15_5_correct(&v) < 0)
+	return -1;
+    return v >> 10;
+}
+
+/*The Micro QR data masks, the QR patterns 1, 4, 6 and 7.*/
+static int qr_micro_mask(int _pattern, int _row, int _col)
+{
+    switch (_pattern) {
+    case 0:
+	return !(_row & 1);
+    case 1:
+	return !((_row / 2 + _col / 3) & 1);
+    case 2:
+	return !((_row * _col % 2 + _row * _col % 3) & 1);
+    default:
+	return !(((_row + _col) % 2 + _row * _col % 3) & 1);
+    }
+}
+
+static int qr_micro_code_decode(qr_code_data *_qrdata, const rs_gf256 *_gf,
+				const qr_micro_grid *_grid, int _fmt_info,
+				const unsigned char *_img, int _width,
+				int _height)
+{
+    unsigned char codewords[24];
+    int symbol;
+    int version;
+    int dim;
+    int ndata_bits;
+    int ndata;
+    int npar;
+    int nbits;
+    int ret;
+    int i;
+    int j;
+    symbol     = _fmt_info >> 2;
+    version    = QR_MICRO_VERSION[symbol];
+    dim	       = 2 * version + 9;
+    ndata_bits = QR_MICRO_CODES[symbol][0];
+    npar       = QR_MICRO_CODES[symbol][1];
+    ndata      = ndata_bits + 7 >> 3;
+    memset(codewords, 0, sizeof(codewords));
+    /*Read the data modules in pairs of columns from the right, upward and
+       downward in turn, skipping the function patterns.
+      There are no remainder bits.*/
+    nbits = 0;
+    for (j = dim - 1; j > 0; j -= 2)
+	for (i = 0; i < dim; i++) {
+	    int row;
+	    int k;
+	    row = (j - dim + 1 & 2) ? i : dim - 1 - i;
+	    for (k = 0; k < 2; k++) {
+		int col;
+		int bit;
+		int b;
+		col = j - k;
+		if (!row || row < 9 && col < 9)
+		    continue;
+		bit = qr_micro_get_bit(_grid, _img, _width, _height, row, col) ^
+		      qr_micro_mask(_fmt_info & 3, row, col);
+		/*The parity codewords start after the last, maybe short, data
+		   codeword.*/
+		b = nbits < ndata_bits ? nbits : nbits - ndata_bits + 8 * ndata;
+		codewords[b >> 3] |= bit << 7 - (b & 7);
+		nbits++;
+	    }
+	}
+    ret = rs_correct(_gf, QR_M0, codewords, ndata + npar, npar, NULL, 0);
+    zprintf(1, "Number of errors corrected: %i%s\n", ret,
+	    ret < 0 ? " (data irrecoverable)" : "");
+    if (ret < 0 || ret > QR_MICRO_CODES[symbol][2])
+	return -1;
+    ret = qr_code_data_parse(_qrdata, version, QR_FORMAT_MICRO, codewords,
+			     ndata);
+    if (ret < 0)
+	qr_code_data_clear(_qrdata);
+    _qrdata->version   = version;
+    _qrdata->ecc_level = QR_MICRO_ECC_LEVEL[symbol];
+    return ret;
+}
+
+/*Tries to decode a Micro QR code around a finder center.
+  Return: The version of the code, or a negative value on error.*/
+static int qr_reader_try_micro(qr_reader *_reader, qr_code_data *_qrdata,
+			       const unsigned char *_img, int _width,
+			       int _height, const qr_finder_center *_c)
+{
+    qr_micro_grid best;
+    double theta;
+    double module;
+    int best_nerrs;
+    int mirror;
+    int r;
+    if (qr_micro_fit_finder(_c, &theta, &module) < 0)
+	return -1;
+    /*Find the corner of the finder the timing patterns start from.
+      The code and its transposition match equally well, so mirrored codes are
+       told apart by their format information.*/
+    best_nerrs = INT_MAX;
+    for (r = 0; r < 4; r++) {
+	qr_micro_grid grid;
+	double a;
+	int nerrs;
+	a	  = theta + r * M_PI / 2;
+	grid.c[0] = (double)_c->pos[0] / (1 << QR_FINDER_SUBPREC);
+	grid.c[1] = (double)_c->pos[1] / (1 << QR_FINDER_SUBPREC);
+	grid.u[0] = module * cos(a);
+	grid.u[1] = module * sin(a);
+	grid.v[0] = -grid.u[1];
+	grid.v[1] = grid.u[0];
+	/*The smallest code, M1, has 11 modules.*/
+	nerrs = qr_micro_pattern_errors(&grid, _img, _width, _height, 11);
+	if (nerrs < best_nerrs) {
+	    best       = grid;
+	    best_nerrs = nerrs;
+	}
+    }
+    if (best_nerrs > QR_MICRO_MAX_PATTERN_ERRS)
+	return -1;
+    for (mirror = 0; mirror < 1 + !!_reader->test_mirrored; mirror++) {
+	qr_micro_grid grid;
+	int fmt_info;
+	int version;
+	int dim;
+	int k;
+	grid = best;
+	if (mirror) {
+	    grid.u[0] = best.v[0];
+	    grid.u[1] = best.v[1];
+	    grid.v[0] = best.u[0];
+	    grid.v[1] = best.u[1];
+	}
+	fmt_info = qr_micro_fmt_info_decode(&grid, _img, _width, _height);
+	if (fmt_info < 0)
+	    continue;
+	version = QR_MICRO_VERSION[fmt_info >> 2];
+	dim	= 2 * version + 9;
+	qr_micro_grid_refine(&grid, _img, _width, _height, dim);
+	if (qr_micro_code_decode(_qrdata, &_reader->gf, &grid, fmt_info, _img,
+				 _width, _height) < 0)
+	    continue;
+	_qrdata->mirrored = mirror;
+	_qrdata->format	  = QR_FORMAT_MICRO;
+	/*The corners, in the same order as for QR codes.*/
+	for (k = 0; k < 4; k++) {
+	    double du;
+	    double dv;
+	    du = (k & 1) ? dim - 3.5 : -3.5;
+	    dv = (k & 2) ? dim - 3.5 : -3.5;
+	    _qrdata->bbox[k][0] =
+		(int)floor(grid.c[0] + du * grid.u[0] + dv * grid.v[0] + 0.5);
+	    _qrdata->bbox[k][1] =
+		(int)floor(grid.c[1] + du * grid.u[1] + dv * grid.v[1] + 0.5);
+	}
+	return version;
+    }
+    return -1;
+}
+
+/*rMQR codes have a finder pattern in their upper-left corner, like Micro QR
+   codes, and a smaller finder sub pattern in their lower-right one.
+  They are up to 139 modules wide but at most 17 high, with timing patterns
+   along all their edges and alignment patterns on the upper and lower ones,
+   which are joined by further timing patterns.
+  The grid found from the finder alone is far too coarse for their width, so
+   it is fit to these patterns from the finder outwards.*/
+
+/*The heights and widths of each rMQR version, R7x43...R17x139.*/
+static const unsigned char QR_RMQR_SIZES[32][2] = {
+    { 7, 43 },	 { 7, 59 },   { 7, 77 },   { 7, 99 },	{ 7, 139 },
+    { 9, 43 },	 { 9, 59 },   { 9, 77 },   { 9, 99 },	{ 9, 139 },
+    { 11, 27 },	 { 11, 43 },  { 11, 59 },  { 11, 77 },	{ 11, 99 },
+    { 11, 139 }, { 13, 27 },  { 13, 43 },  { 13, 59 },	{ 13, 77 },
+    { 13, 99 },	 { 13, 139 }, { 15, 43 },  { 15, 59 },	{ 15, 77 },
+    { 15, 99 },	 { 15, 139 }, { 17, 43 },  { 17, 59 },	{ 17, 77 },
+    { 17, 99 },	 { 17, 139 }
+};
+
+/*The number of blocks and of parity codewords per block of each rMQR version,
+   for the ECC levels M and H.*/
+static const unsigned char QR_RMQR_BLOCKS[32][2][2] = {
+    { { 1, 7 }, { 1, 10 } },  { { 1, 9 }, { 1, 14 } },
+    { { 1, 12 }, { 1, 22 } }, { { 1, 16 }, { 1, 30 } },
+    { { 2, 12 }, { 2, 23 } }, { { 1, 9 }, { 1, 14 } },
+    { { 1, 12 }, { 1, 22 } }, { { 1, 18 }, { 2, 16 } },
+    { { 2, 11 }, { 2, 22 } }, { { 3, 11 }, { 3, 22 } },
+    { { 1, 8 }, { 1, 10 } },  { { 1, 12 }, { 1, 20 } },
+    { { 1, 16 }, { 2, 16 } }, { { 2, 11 }, { 2, 22 } },
+    { { 2, 14 }, { 2, 30 } }, { { 3, 14 }, { 3, 30 } },
+    { { 1, 9 }, { 1, 14 } },  { { 1, 14 }, { 1, 28 } },
+    { { 1, 22 }, { 2, 16 } }, { { 2, 16 }, { 2, 28 } },
+    { { 2, 20 }, { 3, 24 } }, { { 4, 15 }, { 4, 27 } },
+    { { 1, 18 }, { 2, 14 } }, { { 2, 13 }, { 2, 24 } },
+    { { 2, 17 }, { 3, 19 } }, { { 3, 16 }, { 4, 20 } },
+    { { 4, 18 }, { 6, 22 } }, { { 2, 11 }, { 2, 19 } },
+    { { 2, 16 }, { 2, 28 } }, { { 3, 16 }, { 3, 26 } },
+    { { 4, 14 }, { 4, 26 } }, { { 5, 18 }, { 6, 26 } }
+};
+
+/*The rMQR widths and the columns of their alignment patterns.*/
+static const unsigned char QR_RMQR_WIDTHS[6] = { 27, 43, 59, 77, 99, 139 };
+static const unsigned char QR_RMQR_ALIGNMENT[6][4] = {
+    { 0 }, { 21 }, { 19, 39 }, { 25, 51 }, { 23, 49, 75 }, { 27, 55, 83, 111 }
+};
+
+/*The masks of the two copies of the format information, next to the finder
+   and next to the finder sub pattern.*/
+static const unsigned QR_RMQR_FMT_MASKS[2] = { 0x1FAB2, 0x20A7B };
+
+/*The most modules of the finder and of the first timing pattern, up to column
+   26, which may be sampled wrong.*/
+#define QR_RMQR_MAX_PATTERN_ERRS (8)
+
+/*The kind of a module of a _height by _width rMQR code.
+  Return: 0 or 1 for light or dark function pattern modules, 2 for format
+   information and 3 for data.*/
+static int qr_rmqr_module(int _height, int _width, int _row, int _col)
+{
+    const unsigned char *alignment;
+    int i;
+    /*The finder and its separator.*/
+    if (_row < 8 && _col < 8) {
+	int d;
+	d = QR_MAXI(abs(_row - 3), abs(_col - 3));
+	return d != 2 && d < 4;
+    }
+    /*The corner finder patterns.*/
+    if (_row < 2 && _col >= _width - 2)
+	return _row != 1 || _col != _width - 2;
+    if (_row == _height - 1 && _col < 3 ||
+	_height >= 11 && _row == _height - 2 && _col < 2)
+	return _col == 0 || _row == _height - 1;
+    /*The finder sub pattern.*/
+    if (_row >= _height - 5 && _col >= _width - 5)
+	return QR_MAXI(abs(_row - _height + 3), abs(_col - _width + 3)) != 1;
+    /*The format information.*/
+    if (_row >= 1 && _row <= 5 && _col >= 8 && _col <= 10 ||
+	_row >= 1 && _row <= 3 && _col == 11 ||
+	_row >= _height - 6 && _row <= _height - 2 && _col >= _width - 8 &&
+	    _col <= _width - 6 ||
+	_row == _height - 6 && _col >= _width - 5 && _col <= _width - 3)
+	return 2;
+    /*The alignment patterns and the timing patterns joining them.*/
+    for (i = 0; QR_RMQR_WIDTHS[i] != _width; i++)
+	;
+    alignment = QR_RMQR_ALIGNMENT[i];
+    for (i = 0; i < 4 && alignment[i]; i++)
+	if (abs(_col - alignment[i]) <= 1) {
+	    if (_row < 3 || _row >= _height - 3)
+		return _row == 1 || _row == _height - 2 ? _col != alignment[i] :
+							  1;
+	    if (_col == alignment[i])
+		return !(_row & 1);
+	}
+    /*The timing patterns along the edges.*/
+    if (_row == 0 || _row == _height - 1)
+	return !(_col & 1);
+    if (_col == 0 || _col == _width - 1)
+	return !(_row & 1);
+    return 3;
+}
+
+/*Counts the modules of the finder, its separator and the first timing pattern
+   up to column 26, the narrowest code, sampled wrong.*/
+static int qr_rmqr_orientation_errors(const qr_micro_grid *_grid,
+				      const unsigned char *_img, int _width,
+				      int _height)
+{
+    int nerrs;
+    int i;
+    int j;
+    nerrs = 0;
+    for (i = 0; i < 7; i++)
+	for (j = 0; j < 8; j++) {
+	    int d;
+	    d = QR_MAXI(abs(i - 3), abs(j - 3));
+	    nerrs += qr_micro_get_bit(_grid, _img, _width, _height, i, j) !=
+		     (d != 2 && d < 4);
+	}
+    for (j = 8; j < 25; j++)
+	nerrs += qr_micro_get_bit(_grid, _img, _width, _height, 0, j) !=
+		 !(j & 1);
+    return nerrs;
+}
+
+/*A function pattern module of an rMQR code.*/
+typedef struct qr_rmqr_fn_module {
+    unsigned char row;
+    unsigned char col;
+    unsigned char dark;
+} qr_rmqr_fn_module;
+
+/*Counts the function pattern modules sampled wrong.*/
+static int qr_rmqr_pattern_errors(const qr_micro_grid *_grid,
+				  const unsigned char *_img, int _width,
+				  int _height, const qr_rmqr_fn_module *_fn,
+				  int _nfn)
+{
+    int nerrs;
+    int i;
+    nerrs = 0;
+    for (i = 0; i < _nfn; i++)
+	nerrs += qr_micro_get_bit(_grid, _img, _width, _height, _fn[i].row,
+				  _fn[i].col) != _fn[i].dark;
+    return nerrs;
+}
+
+/*Moves the end of the first row by _du modules along it and by _dv across
+   it, over _ncols columns, and scales the height by _sv percent.*/
+static void qr_rmqr_grid_adjust(qr_micro_grid *_dst, const qr_micro_grid *_src,
+				double _du, double _dv, int _sv, int _ncols)
+{
+    double su;
+    double a;
+    su	       = 1 + _du / (_ncols - 4);
+    a	       = _dv / (_ncols - 4);
+    _dst->c[0] = _src->c[0];
+    _dst->c[1] = _src->c[1];
+    _dst->u[0] = su * (_src->u[0] + a * _src->v[0]);
+    _dst->u[1] = su * (_src->u[1] + a * _src->v[1]);
+    _dst->v[0] = (1 + 0.01 * _sv) * (_src->v[0] - a * _src->u[0]);
+    _dst->v[1] = (1 + 0.01 * _sv) * (_src->v[1] - a * _src->u[1]);
+}
+
+/*Fits the grid to the function patterns of the code, over more and more of
+   its width, by moving the end of the first row by up to 2 modules and then
+   scaling the height by up to 4%.*/
+static void qr_rmqr_grid_refine(qr_micro_grid *_grid,
+				const unsigned char *_img, int _width,
+				int _height, int _version)
+{
+    qr_rmqr_fn_module fn[17 * 139];
+    int nfn;
+    int h;
+    int w;
+    int i;
+    int j;
+    int k;
+    h = QR_RMQR_SIZES[_version][0];
+    w = QR_RMQR_SIZES[_version][1];
+    /*List the function pattern modules by column.*/
+    nfn = 0;
+    for (j = 0; j < w; j++)
+	for (i = 0; i < h; i++) {
+	    int m;
+	    m = qr_rmqr_module(h, w, i, j);
+	    if (m < 2) {
+		fn[nfn].row    = (unsigned char)i;
+		fn[nfn].col    = (unsigned char)j;
+		fn[nfn++].dark = (unsigned char)m;
+	    }
+	}
+    for (k = 0, i = 0; k < 6 && QR_RMQR_WIDTHS[k] <= w; k++) {
+	qr_micro_grid grid;
+	qr_micro_grid best;
+	int ncols;
+	int best_nerrs;
+	int best_dist;
+	int du;
+	int dv;
+	int sv;
+	ncols = QR_RMQR_WIDTHS[k];
+	while (i < nfn && fn[i].col < ncols)
+	    i++;
+	best	   = *_grid;
+	best_nerrs =
+	    qr_rmqr_pattern_errors(_grid, _img, _width, _height, fn, i);
+	best_dist  = 0;
+	for (du = -8; du <= 8; du++)
+	    for (dv = -8; dv <= 8; dv++) {
+		int nerrs;
+		int dist;
+		dist = abs(du) + abs(dv);
+		if (!dist)
+		    continue;
+		qr_rmqr_grid_adjust(&grid, _grid, 0.25 * du, 0.25 * dv, 0,
+				    ncols);
+		nerrs =
+		    qr_rmqr_pattern_errors(&grid, _img, _width, _height, fn, i);
+		if (nerrs < best_nerrs ||
+		    nerrs == best_nerrs && dist < best_dist) {
+		    best       = grid;
+		    best_nerrs = nerrs;
+		    best_dist  = dist;
+		}
+	    }
+	*_grid	  = best;
+	best_dist = 0;
+	for (sv = -4; sv <= 4; sv++) {
+	    int nerrs;
+	    if (!sv)
+		continue;
+	    qr_rmqr_grid_adjust(&grid, _grid, 0, 0, sv, ncols);
+	    nerrs = qr_rmqr_pattern_errors(&grid, _img, _width, _height, fn, i);
+	    if (nerrs < best_nerrs ||
+		nerrs == best_nerrs && abs(sv) < best_dist) {
+		best	   = grid;
+		best_nerrs = nerrs;
+		best_dist  = abs(sv);
+	    }
+	}
+	*_grid = best;
+    }
+}
+
+/*Reads and corrects one copy of the format information.
+  Return: The ECC level bit and the version, in 1 and 5 bits, or a negative
+   value on error.*/
+static int qr_rmqr_fmt_info_decode(const qr_micro_grid *_grid,
+				   const unsigned char *_img, int _width,
+				   int _height, int _copy, int _version)
+{
+    unsigned v;
+    int x;
+    int n;
+    v = 0;
+    for (n = 0; n < 18; n++) {
+	int row;
+	int col;
+	if (!_copy) {
+	    row = 1 + n % 5;
+	    col = 8 + n / 5;
+	} else {
+	    int h;
+	    int w;
+	    h = QR_RMQR_SIZES[_version][0];
+	    w = QR_RMQR_SIZES[_version][1];
+	    /*The last 3 bits lie right of the others, above the finder sub
+	       pattern.*/
+	    row = n < 15 ? h - 6 + n % 5 : h - 6;
+	    col = n < 15 ? w - 8 + n / 5 : w - 20 + n;
+	}
+	v |= qr_micro_get_bit(_grid, _img, _width, _height, row, col) << n;
+    }
+    v ^= QR_RMQR_FMT_MASKS[_copy];
+    /*All 64 values are valid, so search them all.*/
+    for (x = 0; x < 64; x++)
+	if (qr_hamming_dist(v, bch18_6_encode(x), 4) < 4)
+	    return x;
+    return -1;
+}
+
+static int qr_rmqr_code_decode(qr_code_data *_qrdata, const rs_gf256 *_gf,
+			       const qr_micro_grid *_grid, int _fmt_info,
+			       const unsigned char *_img, int _width,
+			       int _height)
+{
+    unsigned char codewords[256];
+    unsigned char block[256];
+    unsigned char data[256];
+    int version;
+    int h;
+    int w;
+    int nblocks;
+    int npar;
+    int ncodewords;
+    int ndata;
+    int nshort;
+    int nlong;
+    int nbits;
+    int ret;
+    int i;
+    int j;
+    int b;
+    version = _fmt_info & 0x1F;
+    h	    = QR_RMQR_SIZES[version][0];
+    w	    = QR_RMQR_SIZES[version][1];
+    nblocks = QR_RMQR_BLOCKS[version][_fmt_info >> 5][0];
+    npar    = QR_RMQR_BLOCKS[version][_fmt_info >> 5][1];
+    memset(codewords, 0, sizeof(codewords));
+    /*Read the data modules in pairs of columns from the right, upward and
+       downward in turn, like in QR codes, and unmask them.*/
+    nbits = 0;
+    for (j = w - 2; j > 0; j -= 2)
+	for (i = 1; i < h - 1; i++) {
+	    int row;
+	    int k;
+	    row = (w - 2 - j & 2) ? i : h - 1 - i;
+	    for (k = 0; k < 2; k++) {
+		int col;
+		int bit;
+		col = j - k;
+		if (qr_rmqr_module(h, w, row, col) != 3)
+		    continue;
+		bit = qr_micro_get_bit(_grid, _img, _width, _height, row, col) ^
+		      !((row / 2 + col / 3) & 1);
+		codewords[nbits >> 3] |= bit << 7 - (nbits & 7);
+		nbits++;
+	    }
+	}
+    /*The remaining bits are remainder bits.*/
+    ncodewords = nbits >> 3;
+    ndata      = ncodewords - nblocks * npar;
+    nshort     = ndata / nblocks;
+    nlong      = ndata % nblocks;
+    /*Deinterleave and correct the blocks, the longer ones coming last.*/
+    ret = 0;
+    for (b = 0, i = 0; b < nblocks; b++) {
+	int nblock_data;
+	int nerrs;
+	int k;
+	nblock_data = nshort + (b >= nblocks - nlong);
+	for (k = 0; k < nshort; k++)
+	    block[k] = codewords[k * nblocks + b];
+	if (nblock_data > nshort)
+	    block[nshort] =
+		codewords[nshort * nblocks + b - (nblocks - nlong)];
+	for (k = 0; k < npar; k++)
+	    block[nblock_data + k] = codewords[ndata + k * nblocks + b];
+	nerrs = rs_correct(_gf, QR_M0, block, nblock_data + npar, npar, NULL,
+			   0);
+	zprintf(1, "Number of errors corrected: %i%s\n", nerrs,
+		nerrs < 0 ? " (data irrecoverable)" : "");
+	if (nerrs < 0)
+	    return -1;
+	ret += nerrs;
+	memcpy(data + i, block, nblock_data);
+	i += nblock_data;
+    }
+    ret = qr_code_data_parse(_qrdata, version + 1, QR_FORMAT_RMQR, data,
+			     ndata);
+    if (ret < 0)
+	qr_code_data_clear(_qrdata);
+    _qrdata->version   = version + 1;
+    _qrdata->ecc_level = _fmt_info >> 5 ? 3 : 1;
+    return ret;
+}
+
+/*Tries to decode an rMQR code around a finder center.
+  Return: The version of the code, or a negative value on error.*/
+static int qr_reader_try_rmqr(qr_reader *_reader, qr_code_data *_qrdata,
+			      const unsigned char *_img, int _width,
+			      int _height, const qr_finder_center *_c)
+{
+    qr_micro_grid grids[8];
+    int nerrs[8];
+    double theta;
+    double module;
+    int r;
+    if (qr_micro_fit_finder(_c, &theta, &module) < 0)
+	return -1;
+    /*The first timing pattern tells the rows and columns apart, as codes are
+       at least 27 modules wide but at most 17 high.
+      Try the orientations in the order of the errors.*/
+    for (r = 0; r < 8; r++) {
+	double a;
+	a	      = theta + (r & 3) * M_PI / 2;
+	grids[r].c[0] = (double)_c->pos[0] / (1 << QR_FINDER_SUBPREC);
+	grids[r].c[1] = (double)_c->pos[1] / (1 << QR_FINDER_SUBPREC);
+	grids[r].u[0] = module * cos(a);
+	grids[r].u[1] = module * sin(a);
+	grids[r].v[0] = -grids[r].u[1];
+	grids[r].v[1] = grids[r].u[0];
+	/*The mirrored code is read from the transposed grid.*/
+	if (r & 4) {
+	    grids[r].v[0] = grids[r].u[0];
+	    grids[r].v[1] = grids[r].u[1];
+	    grids[r].u[0] = -grids[r].u[1];
+	    grids[r].u[1] = grids[r].v[0];
+	}
+	nerrs[r] = r & 4 && !_reader->test_mirrored ?
+		       INT_MAX :
+		       qr_rmqr_orientation_errors(grids + r, _img, _width,
+						  _height);
+    }
+    for (;;) {
+	qr_micro_grid grid;
+	int fmt_info;
+	int version;
+	int h;
+	int w;
+	int best;
+	int k;
+	for (best = 0, r = 1; r < 8; r++)
+	    if (nerrs[r] < nerrs[best])
+		best = r;
+	if (nerrs[best] > QR_RMQR_MAX_PATTERN_ERRS)
+	    return -1;
+	nerrs[best] = INT_MAX;
+	grid	    = grids[best];
+	/*The copy next to the finder can be read before the grid is refined.*/
+	fmt_info = qr_rmqr_fmt_info_decode(&grid, _img, _width, _height, 0, 0);
+	if (fmt_info < 0)
+	    continue;
+	version = fmt_info & 0x1F;
+	qr_rmqr_grid_refine(&grid, _img, _width, _height, version);
+	if (qr_rmqr_code_decode(_qrdata, &_reader->gf, &grid, fmt_info, _img,
+				_width, _height) < 0) {
+	    int fmt_info2;
+	    /*Try the other copy of the format information, in case the first
+	       one was corrected wrong.*/
+	    fmt_info2 = qr_rmqr_fmt_info_decode(&grid, _img, _width, _height, 1,
+						version);
+	    if (fmt_info2 < 0 || fmt_info2 == fmt_info)
+		continue;
+	    version = fmt_info2 & 0x1F;
+	    grid    = grids[best];
+	    qr_rmqr_grid_refine(&grid, _img, _width, _height, version);
+	    if (qr_rmqr_code_decode(_qrdata, &_reader->gf, &grid, fmt_info2,
+				    _img, _width, _height) < 0)
+		continue;
+	}
+	_qrdata->mirrored = best >= 4;
+	_qrdata->format	  = QR_FORMAT_RMQR;
+	h		  = QR_RMQR_SIZES[version][0];
+	w		  = QR_RMQR_SIZES[version][1];
+	/*The corners, in the same order as for QR codes.*/
+	for (k = 0; k < 4; k++) {
+	    double du;
+	    double dv;
+	    du = (k & 1) ? w - 3.5 : -3.5;
+	    dv = (k & 2) ? h - 3.5 : -3.5;
+	    _qrdata->bbox[k][0] =
+		(int)floor(grid.c[0] + du * grid.u[0] + dv * grid.v[0] + 0.5);
+	    _qrdata->bbox[k][1] =
+		(int)floor(grid.c[1] + du * grid.u[1] + dv * grid.v[1] + 0.5);
+	}
+	return version + 1;
+    }
+}
+
+/*Tries the finder centers which are not inside any code found yet as Micro QR
+   and rMQR codes.*/
+static void qr_reader_match_single(qr_reader *_reader,
+				   qr_code_data_list *_qrlist,
+				   const qr_finder_center *_centers,
+				   int _ncenters, const unsigned char *_img,
+				   int _width, int _height, int _micro,
+				   int _rmqr)
+{
+    int nqrdata;
+    int i;
+    nqrdata = _qrlist->nqrdata;
+    for (i = 0; i < _ncenters; i++) {
+	qr_code_data qrdata;
+	qr_point p;
+	int j;
+	p[0] = _centers[i].pos[0] >> QR_FINDER_SUBPREC;
+	p[1] = _centers[i].pos[1] >> QR_FINDER_SUBPREC;
+	for (j = 0; j < nqrdata; j++) {
+	    const qr_code_data *qrdata;
+	    int ccw[4];
+	    qrdata = _qrlist->qrdata + j;
+	    ccw[0] = qr_point_ccw(qrdata->bbox[0], qrdata->bbox[1], p);
+	    ccw[1] = qr_point_ccw(qrdata->bbox[1], qrdata->bbox[3], p);
+	    ccw[2] = qr_point_ccw(qrdata->bbox[3], qrdata->bbox[2], p);
+	    ccw[3] = qr_point_ccw(qrdata->bbox[2], qrdata->bbox[0], p);
+	    /*Mirrored codes have their corners in the other direction.*/
+	    if (ccw[0] >= 0 && ccw[1] >= 0 && ccw[2] >= 0 && ccw[3] >= 0 ||
+		ccw[0] <= 0 && ccw[1] <= 0 && ccw[2] <= 0 && ccw[3] <= 0)
+		break;
+	}
+	if (j < nqrdata)
+	    continue;
+	if (_micro && qr_reader_try_micro(_reader, &qrdata, _img, _width,
+					  _height, _centers + i) >= 0 ||
+	    _rmqr && qr_reader_try_rmqr(_reader, &qrdata, _img, _width,
+					_height, _centers + i) >= 0)
+	    qr_code_data_list_add(_qrlist, &qrdata);
+    }
+}
+
 int _zbar_qr_found_line(qr_reader *reader, int dir, const qr_finder_line *line)
 {
     /* minimally intrusive brute force version */
@@ -4357,27 +5284,48 @@ int _zbar_qr_decode(qr_reader *reader, zbar_image_scanner_t *iscn,
     int nqrdata			= 0, ncenters;
     qr_finder_edge_pt *edge_pts = NULL;
     qr_finder_center *centers	= NULL;
+    int qr_enabled = 0, micro_enabled = 0, rmqr_enabled = 0, min_centers;
+
+    zbar_image_scanner_get_config(iscn, ZBAR_QRCODE, ZBAR_CFG_ENABLE,
+				  &qr_enabled);
+    zbar_image_scanner_get_config(iscn, ZBAR_MICROQR, ZBAR_CFG_ENABLE,
+				  &micro_enabled);
+    zbar_image_scanner_get_config(iscn, ZBAR_RMQR, ZBAR_CFG_ENABLE,
+				  &rmqr_enabled);
+    min_centers = micro_enabled || rmqr_enabled ? 1 : 3;
 
-    if (reader->finder_lines[0].nlines < 9 ||
-	reader->finder_lines[1].nlines < 9)
+    /* at least 3 lines per finder and direction */
+    if (reader->finder_lines[0].nlines < 3 * min_centers ||
+	reader->finder_lines[1].nlines < 3 * min_centers)
 	return (0);
 
     svg_group_start("finder", 0, 1. / (1 << QR_FINDER_SUBPREC), 0, 0, 0);
 
-    ncenters = qr_finder_centers_locate(&centers, &edge_pts, reader, 0, 0);
+    ncenters =
+	qr_finder_centers_locate(&centers, &edge_pts, reader, min_centers);
 
     zprintf(14, "%dx%d finders, %d centers:\n", reader->finder_lines[0].nlines,
 	    reader->finder_lines[1].nlines, ncenters);
     qr_svg_centers(centers, ncenters);
 
-    if (ncenters >= 3) {
+    if ((qr_enabled && ncenters >= 3) ||
+	((micro_enabled || rmqr_enabled) && ncenters >= 1)) {
 	void *bin = qr_binarize(img->data, img->width, img->height);
 
+	zbar_image_scanner_get_config(iscn, ZBAR_PARTIAL,
+				      ZBAR_CFG_TEST_MIRRORED,
+				      &reader->test_mirrored);
+
 	qr_code_data_list qrlist;
 	qr_code_data_list_init(&qrlist);
 
-	qr_reader_match_centers(reader, &qrlist, centers, ncenters, bin,
-				img->width, img->height);
+	if (qr_enabled && ncenters >= 3)
+	    qr_reader_match_centers(reader, &qrlist, centers, ncenters, bin,
+				    img->width, img->height);
+	if (micro_enabled || rmqr_enabled)
+	    qr_reader_match_single(reader, &qrlist, centers, ncenters, bin,
+				   img->width, img->height, micro_enabled,
+				   rmqr_enabled);
 
 	if (qrlist.nqrdata > 0)
 	    nqrdata = qr_code_data_list_extract_text(&qrlist, iscn, img);
@@ -38,6 +38,18 @@ typedef enum qr_mode
     QR_MODE_FNC1_2ND
 } qr_mode;
 
+/*The code formats sharing the QR finder pattern.*/
+typedef enum qr_format
+{
+    /*QR Code, with three finder patterns.*/
+    QR_FORMAT_QR,
+    /*Micro QR Code, with a single finder pattern.*/
+    QR_FORMAT_MICRO,
+    /*rMQR Code, a rectangular Micro QR Code with a smaller finder sub pattern
+     in its opposite corner.*/
+    QR_FORMAT_RMQR
+} qr_format;
+
 /*Check if a mode has a data buffer associated with it.
   Currently this is only modes with exactly one bit set.*/
 #define QR_MODE_HAS_DATA(_mode) (!((_mode) & (_mode)-1))
@@ -120,7 +132,8 @@ struct qr_code_data {
     /*The decoded data entries.*/
     qr_code_data_entry *entries;
     int nentries;
-    /*The code version (1...40).*/
+    /*The code version (1...40, 1...4 for M1...M4 Micro QR codes, or 1...32
+       for R7x43...R17x139 rMQR codes).*/
     unsigned char version;
     /*The ECC level (0...3, corresponding to 'L', 'M', 'Q', and 'H').*/
     unsigned char ecc_level;
@@ -136,6 +149,11 @@ struct qr_code_data {
     /*The parity of this code.
     If sa_size is zero, this is undefined.*/
     unsigned char self_parity;
+    /*Whether the code was read from the transposed module grid, i.e., it is
+       mirrored.*/
+    unsigned char mirrored;
+    /*The format of the code (see qr_format).*/
+    unsigned char format;
     /*An approximate bounding box for the code.
     Points appear in the order up-left, up-right, down-left, down-right,
      relative to the orientation of the QR code.*/
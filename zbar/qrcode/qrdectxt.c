@@ -89,8 +89,6 @@ int qr_code_data_list_extract_text(const qr_code_data_list *_qrlist,
     int ntext;
     int i;
     int raw_binary = 0;
-    zbar_image_scanner_get_config(iscn, ZBAR_QRCODE, ZBAR_CFG_BINARY,
-				  &raw_binary);
     qrdata  = _qrlist->qrdata;
     nqrdata = _qrlist->nqrdata;
     mark    = (unsigned char *)calloc(nqrdata, sizeof(*mark));
@@ -115,6 +113,7 @@ int qr_code_data_list_extract_text(const qr_code_data_list *_qrlist,
 	    size_t sa_ntext;
 	    size_t sa_ctext;
 	    int fnc1;
+	    int mirrored;
 	    int fnc1_2ai;
 	    int has_kanji;
 	    int eci;
@@ -122,6 +121,7 @@ int qr_code_data_list_extract_text(const qr_code_data_list *_qrlist,
 	    int j;
 	    int k;
 	    zbar_symbol_t *syms = NULL, **sym = &syms;
+	    zbar_symbol_type_t type;
 	    qr_point dir;
 	    int horiz;
 	    char *bytebuf_text;
@@ -151,14 +151,25 @@ int qr_code_data_list_extract_text(const qr_code_data_list *_qrlist,
 		sa_size = 1;
 	    }
 
+	    /*Micro QR and rMQR codes have no S-A header, so all the group is
+	       one type.*/
+	    type = qrdata[i].format == QR_FORMAT_MICRO ? ZBAR_MICROQR :
+		   qrdata[i].format == QR_FORMAT_RMQR  ? ZBAR_RMQR :
+							 ZBAR_QRCODE;
+	    zbar_image_scanner_get_config(iscn, type, ZBAR_CFG_BINARY,
+					  &raw_binary);
+
 	    sa_ctext  = 0;
 	    fnc1      = 0;
+	    mirrored  = 0;
 	    fnc1_2ai  = 0;
 	    has_kanji = 0;
 	    /*Step 1: Detect FNC1 markers and estimate the required buffer size.*/
 	    for (j = 0; j < sa_size; j++)
 		if (sa[j] >= 0) {
 		    qrdataj = qrdata + sa[j];
+		    if (qrdataj->mirrored)
+			mirrored = MOD(ZBAR_MOD_MIRRORED);
 		    for (k = 0; k < qrdataj->nentries; k++) {
 			int shift;
 			entry = qrdataj->entries + k;
@@ -220,7 +231,7 @@ int qr_code_data_list_extract_text(const qr_code_data_list *_qrlist,
 	    bytebuf_ntext = 0;
 
 	    for (j = 0; j < sa_size && !err; j++, sym = &(*sym)->next) {
-		*sym = _zbar_image_scanner_alloc_sym(iscn, ZBAR_QRCODE, 0);
+		*sym = _zbar_image_scanner_alloc_sym(iscn, type, 0);
 		(*sym)->datalen = sa_ntext;
 		if (sa[j] < 0) {
 		    /* generic placeholder for unfinished results */
@@ -235,19 +246,26 @@ int qr_code_data_list_extract_text(const qr_code_data_list *_qrlist,
 
 		    /* mark break in data */
 		    sa_text[sa_ntext++] = '\0';
-		    (*sym)->datalen	= sa_ntext;
 
 		    /* advance to next symbol */
 		    sym	 = &(*sym)->next;
-		    *sym = _zbar_image_scanner_alloc_sym(iscn, ZBAR_QRCODE, 0);
+		    *sym = _zbar_image_scanner_alloc_sym(iscn, type, 0);
+		    (*sym)->datalen = sa_ntext;
 		}
 
 		qrdataj = qrdata + sa[j];
+		(*sym)->modifiers = qrdataj->mirrored ? MOD(ZBAR_MOD_MIRRORED) : 0;
 		/* expose bounding box */
 		sym_add_point(*sym, qrdataj->bbox[0][0], qrdataj->bbox[0][1]);
 		sym_add_point(*sym, qrdataj->bbox[2][0], qrdataj->bbox[2][1]);
 		sym_add_point(*sym, qrdataj->bbox[3][0], qrdataj->bbox[3][1]);
 		sym_add_point(*sym, qrdataj->bbox[1][0], qrdataj->bbox[1][1]);
+		if (qrdataj->sa_size) {
+		    (*sym)->sa_index	   = qrdataj->sa_index;
+		    (*sym)->sa_size	   = qrdataj->sa_size;
+		    (*sym)->sa_parity	   = qrdataj->sa_parity;
+		    (*sym)->sa_data_parity = qrdataj->self_parity;
+		}
 
 		/* approx symbol "up" direction */
 		dir[0]	       = (qrdataj->bbox[0][0] - qrdataj->bbox[2][0] +
@@ -508,8 +526,7 @@ int qr_code_data_list_extract_text(const qr_code_data_list *_qrlist,
 		    int ymin = img->height, ymax = -2;
 
 		    /* create "virtual" container symbol for composite result */
-		    sa_sym =
-			_zbar_image_scanner_alloc_sym(iscn, ZBAR_QRCODE, 0);
+		    sa_sym = _zbar_image_scanner_alloc_sym(iscn, type, 0);
 		    sa_sym->syms       = _zbar_symbol_set_create();
 		    sa_sym->syms->head = syms;
 
@@ -533,12 +550,16 @@ int qr_code_data_list_extract_text(const qr_code_data_list *_qrlist,
 				    ymax = u + 1;
 			    }
 			syms->data = sa_text + syms->datalen;
-			next = (syms->next) ? syms->next->datalen : sa_ntext;
-			if (next > syms->datalen)
-			    syms->datalen = next - syms->datalen - 1;
+			next = (syms->next) ? syms->next->datalen :
+					      sa_ntext - 1;
+			/* skip the break following missing parts */
+			if (syms->type == ZBAR_PARTIAL && syms->next)
+			    next--;
+			if (next >= syms->datalen)
+			    syms->datalen = next - syms->datalen;
 			else {
-			    zprintf(
-				1, "Assertion `next > syms->datalen' failed\n");
+			    zprintf(1, "Assertion `next >= syms->datalen'"
+				       " failed\n");
 			    syms->datalen = 0;
 			}
 		    }
@@ -552,7 +573,7 @@ int qr_code_data_list_extract_text(const qr_code_data_list *_qrlist,
 		sa_sym->data	   = sa_text;
 		sa_sym->data_alloc = sa_ntext;
 		sa_sym->datalen	   = sa_ntext - 1;
-		sa_sym->modifiers  = fnc1;
+		sa_sym->modifiers  = fnc1 | mirrored;
 
 		_zbar_image_scanner_add_sym(iscn, sa_sym);
 	    } else {
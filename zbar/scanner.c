@@ -39,7 +39,7 @@
 #endif
 #define ROUND (1 << (ZBAR_FIXED - 1))
 
-/* FIXME add runtime config API for these */
+/* defaults of the runtime configurable filter parameters */
 #ifndef ZBAR_SCANNER_THRESH_MIN
 #define ZBAR_SCANNER_THRESH_MIN 4
 #endif
@@ -61,11 +61,15 @@
 #endif
 #define EWMA_WEIGHT \
     ((unsigned)((ZBAR_SCANNER_EWMA_WEIGHT * (1 << (ZBAR_FIXED + 1)) + 1) / 2))
+#define EWMA_PERCENT ((int)(ZBAR_SCANNER_EWMA_WEIGHT * 100 + .5))
 
 /* scanner state */
 struct zbar_scanner_s {
     zbar_decoder_t *decoder; /* associated bar width decoder */
     unsigned y1_min_thresh;  /* minimum threshold */
+    unsigned thresh_fade;    /* threshold decay rate */
+    unsigned ewma_weight;    /* moving average weight, ZBAR_FIXED */
+    int ewma_percent;	     /* moving average weight, as configured */
 
     unsigned x; /* relative scan position of next sample */
     int y0[4];	/* short circular buffer of average intensities */
@@ -83,6 +87,9 @@ zbar_scanner_t *zbar_scanner_create(zbar_decoder_t *dcode)
     zbar_scanner_t *scn = malloc(sizeof(zbar_scanner_t));
     scn->decoder	= dcode;
     scn->y1_min_thresh	= ZBAR_SCANNER_THRESH_MIN;
+    scn->thresh_fade	= ZBAR_SCANNER_THRESH_FADE;
+    scn->ewma_weight	= EWMA_WEIGHT;
+    scn->ewma_percent	= EWMA_PERCENT;
     zbar_scanner_reset(scn);
     return (scn);
 }
@@ -101,6 +108,50 @@ zbar_symbol_type_t zbar_scanner_reset(zbar_scanner_t *scn)
     return (ZBAR_NONE);
 }
 
+int zbar_scanner_set_config(zbar_scanner_t *scn, zbar_config_t cfg, int val)
+{
+    switch (cfg) {
+    case ZBAR_CFG_EDGE_THRESHOLD:
+	if (val < 1)
+	    return (1);
+	scn->y1_min_thresh = val;
+	if (scn->y1_thresh < scn->y1_min_thresh)
+	    scn->y1_thresh = scn->y1_min_thresh;
+	return (0);
+    case ZBAR_CFG_EWMA_WEIGHT:
+	if (val < 1 || val > 100)
+	    return (1);
+	scn->ewma_percent = val;
+	scn->ewma_weight  = ((val << (ZBAR_FIXED + 1)) / 100 + 1) / 2;
+	return (0);
+    case ZBAR_CFG_THRESHOLD_DECAY:
+	if (val < 1)
+	    return (1);
+	scn->thresh_fade = val;
+	return (0);
+    default:
+	return (1);
+    }
+}
+
+int zbar_scanner_get_config(const zbar_scanner_t *scn, zbar_config_t cfg,
+			    int *val)
+{
+    switch (cfg) {
+    case ZBAR_CFG_EDGE_THRESHOLD:
+	*val = scn->y1_min_thresh;
+	return (0);
+    case ZBAR_CFG_EWMA_WEIGHT:
+	*val = scn->ewma_percent;
+	return (0);
+    case ZBAR_CFG_THRESHOLD_DECAY:
+	*val = scn->thresh_fade;
+	return (0);
+    default:
+	return (1);
+    }
+}
+
 unsigned zbar_scanner_get_width(const zbar_scanner_t *scn)
 {
     return (scn->width);
@@ -137,7 +188,7 @@ static inline unsigned calc_thresh(zbar_scanner_t *scn)
     dx = (scn->x << ZBAR_FIXED) - scn->last_edge;
     t  = thresh * dx;
     t /= scn->width;
-    t /= ZBAR_SCANNER_THRESH_FADE;
+    t /= scn->thresh_fade;
     dbprintf(1, " thr=%d t=%ld x=%d last=%d.%d (%d)", thresh, t, scn->x,
 	     scn->last_edge >> ZBAR_FIXED,
 	     scn->last_edge & ((1 << ZBAR_FIXED) - 1), dx);
@@ -224,7 +275,7 @@ zbar_symbol_type_t zbar_scan_y(zbar_scanner_t *scn, int y)
     zbar_symbol_type_t edge;
     if (x) {
 	/* update weighted moving average */
-	y0_0 += ((int)((y - y0_1) * EWMA_WEIGHT)) >> ZBAR_FIXED;
+	y0_0 += ((int)((y - y0_1) * scn->ewma_weight)) >> ZBAR_FIXED;
 	scn->y0[x & 3] = y0_0;
     } else
 	y0_0 = y0_1 = scn->y0[0] = scn->y0[1] = scn->y0[2] = scn->y0[3] = y;
@@ -52,10 +52,20 @@ const char *zbar_get_symbol_name(zbar_symbol_type_t sym)
 	return ("COMPOSITE");
     case ZBAR_I25:
 	return ("I2/5");
+    case ZBAR_ITF14:
+	return ("ITF-14");
     case ZBAR_DATABAR:
 	return ("DataBar");
     case ZBAR_DATABAR_EXP:
 	return ("DataBar-Exp");
+    case ZBAR_DATABAR_LTD:
+	return ("DataBar-Ltd");
+    case ZBAR_DATABAR_STK:
+	return ("DataBar-Stk");
+    case ZBAR_DATABAR_STK_OMNI:
+	return ("DataBar-Stk-Omni");
+    case ZBAR_DATABAR_EXP_STK:
+	return ("DataBar-Exp-Stk");
     case ZBAR_CODABAR:
 	return ("Codabar");
     case ZBAR_CODE39:
@@ -68,8 +78,16 @@ const char *zbar_get_symbol_name(zbar_symbol_type_t sym)
 	return ("PDF417");
     case ZBAR_QRCODE:
 	return ("QR-Code");
+    case ZBAR_MICROQR:
+	return ("Micro-QR-Code");
+    case ZBAR_RMQR:
+	return ("rMQR-Code");
     case ZBAR_SQCODE:
 	return ("SQ-Code");
+    case ZBAR_DATAMATRIX:
+	return ("DataMatrix");
+    case ZBAR_AZTEC:
+	return ("Aztec");
     default:
 	return ("UNKNOWN");
     }
@@ -101,10 +119,22 @@ const char *zbar_get_config_name(zbar_config_t cfg)
 	return ("UNCERTAINTY");
     case ZBAR_CFG_POSITION:
 	return ("POSITION");
+    case ZBAR_CFG_TEST_INVERTED:
+	return ("TEST_INVERTED");
+    case ZBAR_CFG_TEST_MIRRORED:
+	return ("TEST_MIRRORED");
     case ZBAR_CFG_X_DENSITY:
 	return ("X_DENSITY");
     case ZBAR_CFG_Y_DENSITY:
 	return ("Y_DENSITY");
+    case ZBAR_CFG_DIAGONAL:
+	return ("DIAGONAL");
+    case ZBAR_CFG_EDGE_THRESHOLD:
+	return ("EDGE_THRESHOLD");
+    case ZBAR_CFG_EWMA_WEIGHT:
+	return ("EWMA_WEIGHT");
+    case ZBAR_CFG_THRESHOLD_DECAY:
+	return ("THRESHOLD_DECAY");
     default:
 	return ("");
     }
@@ -117,6 +147,12 @@ const char *zbar_get_modifier_name(zbar_modifier_t mod)
 	return ("GS1");
     case ZBAR_MOD_AIM:
 	return ("AIM");
+    case ZBAR_MOD_MIRRORED:
+	return ("MIRRORED");
+    case ZBAR_MOD_APPEND:
+	return ("APPEND");
+    case ZBAR_MOD_READER_INIT:
+	return ("READER-INIT");
     default:
 	return ("");
     }
@@ -162,6 +198,15 @@ static const signed char _zbar_symbol_hash[ZBAR_CODE128 + 1] = {
     [ZBAR_EAN5]	       = 17,
     [ZBAR_COMPOSITE]   = 18,
     [ZBAR_CODABAR]     = 19,
+    [ZBAR_DATAMATRIX]  = 20,
+    [ZBAR_AZTEC]       = 21,
+    [ZBAR_MICROQR]     = 22,
+    [ZBAR_RMQR]	       = 23,
+    [ZBAR_ITF14]       = 24,
+    [ZBAR_DATABAR_LTD] = 25,
+    [ZBAR_DATABAR_STK] = 26,
+    [ZBAR_DATABAR_STK_OMNI] = 27,
+    [ZBAR_DATABAR_EXP_STK]  = 28,
 
     /* Please update NUM_SYMS accordingly */
 };
@@ -192,7 +237,11 @@ static const signed char *_init_hash()
     hash[ZBAR_I25] = 10, hash[ZBAR_PDF417] = 11, hash[ZBAR_QRCODE] = 12,
     hash[ZBAR_DATABAR] = 13, hash[ZBAR_DATABAR_EXP] = 14,
     hash[ZBAR_CODE93] = 15, hash[ZBAR_EAN2] = 16, hash[ZBAR_EAN5] = 17,
-    hash[ZBAR_COMPOSITE] = 18, hash[ZBAR_CODABAR] = 19;
+    hash[ZBAR_COMPOSITE] = 18, hash[ZBAR_CODABAR] = 19,
+    hash[ZBAR_DATAMATRIX] = 20, hash[ZBAR_AZTEC] = 21,
+    hash[ZBAR_MICROQR] = 22, hash[ZBAR_RMQR] = 23, hash[ZBAR_ITF14] = 24,
+    hash[ZBAR_DATABAR_LTD] = 25, hash[ZBAR_DATABAR_STK] = 26,
+    hash[ZBAR_DATABAR_STK_OMNI] = 27, hash[ZBAR_DATABAR_EXP_STK] = 28;
 
     was_initialized = 1;
 
@@ -293,6 +342,23 @@ zbar_orientation_t zbar_symbol_get_orientation(const zbar_symbol_t *sym)
     return (sym->orient);
 }
 
+int zbar_symbol_get_structured_append(const zbar_symbol_t *sym,
+				      unsigned *index, unsigned *size,
+				      unsigned *parity, unsigned *data_parity)
+{
+    if (!sym->sa_size)
+	return (-1);
+    if (index)
+	*index = sym->sa_index;
+    if (size)
+	*size = sym->sa_size;
+    if (parity)
+	*parity = sym->sa_parity;
+    if (data_parity)
+	*data_parity = sym->sa_data_parity;
+    return (0);
+}
+
 const zbar_symbol_t *zbar_symbol_next(const zbar_symbol_t *sym)
 {
     return ((sym) ? sym->next : NULL);
@@ -315,11 +381,12 @@ unsigned base64_encode(char *dst, const char *src, unsigned srclen)
     char *start = dst;
     int nline	= 19;
     for (; srclen; srclen -= 3) {
-	unsigned int buf = *(src++) << 16;
+	/* bytes above 0x7f must not be sign extended */
+	unsigned int buf = (unsigned char)*(src++) << 16;
 	if (srclen > 1)
-	    buf |= *(src++) << 8;
+	    buf |= (unsigned char)*(src++) << 8;
 	if (srclen > 2)
-	    buf |= *(src++);
+	    buf |= (unsigned char)*(src++);
 	*(dst++) = alphabet[(buf >> 18) & 0x3f];
 	*(dst++) = alphabet[(buf >> 12) & 0x3f];
 	*(dst++) = (srclen > 1) ? alphabet[(buf >> 6) & 0x3f] : '=';
@@ -27,7 +27,7 @@
 #include <zbar.h>
 #include "refcnt.h"
 
-#define NUM_SYMS 20
+#define NUM_SYMS 29
 
 typedef struct point_s {
     int x, y;
@@ -59,6 +59,10 @@ struct zbar_symbol_s {
     unsigned long time;	     /* relative symbol capture time */
     int cache_count;	     /* cache state */
     int quality;	     /* relative symbol reliability metric */
+    unsigned sa_index;	     /* structured append part index */
+    unsigned sa_size;	     /* structured append part count (0 if none) */
+    unsigned sa_parity;	     /* structured append message parity */
+    unsigned sa_data_parity; /* parity of this part's data */
 };
 
 extern int _zbar_get_symbol_hash(zbar_symbol_type_t);
@@ -91,7 +91,9 @@ static inline int window_draw_overlay(zbar_window_t *w)
 	const zbar_symbol_t *sym = w->image->syms->head;
 	for (; sym; sym = sym->next) {
 	    uint32_t color = ((sym->cache_count < 0) ? 4 : 2);
-	    if (sym->type == ZBAR_QRCODE || sym->type == ZBAR_SQCODE)
+	    if (sym->type == ZBAR_QRCODE || sym->type == ZBAR_MICROQR ||
+		sym->type == ZBAR_RMQR || sym->type == ZBAR_SQCODE ||
+		sym->type == ZBAR_DATAMATRIX || sym->type == ZBAR_AZTEC)
 		window_outline_symbol(w, color, sym);
 	    else {
 		/* FIXME linear bbox broken */
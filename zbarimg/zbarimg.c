@@ -514,6 +514,9 @@ int main(int argc, const char *argv[])
 #endif
 #if ENABLE_DATABAR == 1
 	    fprintf(stderr, _("\t. DataBar, DataBar Expanded\n"));
+	    fprintf(stderr, _("\t. DataBar Limited\n"));
+	    fprintf(stderr,
+		    _("\t. DataBar Stacked, DataBar Expanded Stacked\n"));
 #endif
 #if ENABLE_CODE128 == 1
 	    fprintf(stderr, _("\t. Code 128\n"));
@@ -529,15 +532,24 @@ int main(int argc, const char *argv[])
 #endif
 #if ENABLE_I25 == 1
 	    fprintf(stderr, _("\t. Interleaved 2 of 5\n"));
+	    fprintf(stderr, _("\t. ITF-14\n"));
 #endif
 #if ENABLE_QRCODE == 1
 	    fprintf(stderr, _("\t. QR code\n"));
+	    fprintf(stderr, _("\t. Micro QR code\n"));
+	    fprintf(stderr, _("\t. rMQR code\n"));
 #endif
 #if ENABLE_SQCODE == 1
 	    fprintf(stderr, _("\t. SQ code\n"));
 #endif
 #if ENABLE_PDF417 == 1
 	    fprintf(stderr, _("\t. PDF 417\n"));
+#endif
+#if ENABLE_DATAMATRIX == 1
+	    fprintf(stderr, _("\t. Data Matrix\n"));
+#endif
+#if ENABLE_AZTEC == 1
+	    fprintf(stderr, _("\t. Aztec\n"));
 #endif
 	    fprintf(stderr, "%s", _(warning_not_found_tail));
 	}